@@ -0,0 +1,361 @@
+// Offline calculator, opened by the service worker for pages that were not calculated before.
+//
+// It runs the mass and balance, fuel and performance calculations in the browser with the
+// WebAssembly build of the calculation core, on the last copy of the aircraft registry received
+// from /fleet.json. Without the WebAssembly build or a copy of the registry the page keeps its
+// offline notice.
+
+const CORE = '/assets/pkg/acm_weight_and_balance.js';
+const FLEET = '/fleet.json';
+
+const SEATS = [['b', 'Achter'], ['m', 'Midden'], ['f', 'Voor']];
+const FUEL_PLAN = [
+    ['taxi', 'Taxi'],
+    ['trip', 'Reis'],
+    ['alternate', 'Alternatief'],
+    ['reserve', 'Reserve'],
+    ['contingency', 'Onvoorzien'],
+    ['extra', 'Extra'],
+    ['total', 'Totaal aan boord'],
+];
+const PERFORMANCE = [
+    ['torr', 'Startrol'],
+    ['todr', 'Startafstand'],
+    ['lgrr', 'Landingsrol'],
+    ['ldr', 'Landingsafstand'],
+];
+const SVG = 'http://www.w3.org/2000/svg';
+
+let core;
+let fleet;
+
+const $ = (id) => document.getElementById(id);
+const call = (fn, input) => JSON.parse(fn(JSON.stringify(input)));
+const round = (value, decimals) => value.toFixed(decimals).replace('.', ',');
+
+async function start() {
+    try {
+        const response = await fetch(FLEET);
+        if (!response.ok) {
+            throw new Error(`${FLEET}: ${response.status}`);
+        }
+        fleet = await response.json();
+        core = await import(CORE);
+        await core.default();
+    } catch (e) {
+        console.warn('offline calculator unavailable', e);
+        return;
+    }
+
+    const aircraft = $('aircraft');
+    for (const a of fleet.aircraft) {
+        aircraft.add(new Option(a.callsign, a.callsign));
+    }
+    aircraft.addEventListener('change', showAircraft);
+    $('fuel-max').addEventListener('change', () => {
+        for (const input of document.querySelectorAll('.tank')) {
+            input.disabled = $('fuel-max').checked;
+        }
+    });
+    $('offline-form').addEventListener('submit', calculate);
+    $('print').addEventListener('click', () => window.print());
+
+    showAircraft();
+    $('unavailable').classList.add('hidden');
+    $('calculator').classList.remove('hidden');
+}
+
+function selected() {
+    const aircraft = fleet.aircraft.find((a) => a.callsign === $('aircraft').value);
+    const type = fleet.aircraft_type.find((t) => t.id === aircraft.type);
+    return { aircraft, type };
+}
+
+// Tanks of the type, a type without tanks has a single tank.
+function tanks(type) {
+    return type.fuel.tank.length > 0 ? type.fuel.tank : [{ name: 'Tank' }];
+}
+
+function field(id, label, input) {
+    const div = document.createElement('div');
+    div.className = 'mb-4 w-full';
+    const text = document.createElement('label');
+    text.htmlFor = id;
+    text.className = 'uppercase block text-gray-700 font-bold mb-2';
+    text.textContent = label;
+    input.id = id;
+    input.className += ' shadow appearance-none border rounded w-full py-2 px-3 text-gray-700 '
+        + 'leading-tight focus:outline-none';
+    div.append(text, input);
+    return div;
+}
+
+function number(className, max) {
+    const input = document.createElement('input');
+    input.type = 'number';
+    input.min = '0';
+    input.step = 'any';
+    input.className = className;
+    if (max !== undefined) {
+        input.max = String(max);
+    }
+    return input;
+}
+
+function showAircraft() {
+    const { aircraft, type } = selected();
+
+    const stations = $('stations');
+    stations.replaceChildren();
+    type.station.forEach((station, i) => {
+        const row = document.createElement('div');
+        row.className = 'flex';
+        const label = station.max_mass ? `${station.name} (max ${station.max_mass} kg)` : station.name;
+        row.append(field(`station-${i}`, label, number('station-mass', station.max_mass ?? undefined)));
+        if (station.positions) {
+            const seat = document.createElement('select');
+            seat.className = 'station-seat cursor-pointer';
+            for (const [value, text] of SEATS) {
+                seat.add(new Option(text, value, false, value === 'm'));
+            }
+            const position = field(`station-${i}-seat`, 'Stoel', seat);
+            position.classList.replace('w-full', 'w-1/2');
+            position.classList.add('ml-1');
+            row.append(position);
+        }
+        stations.append(row);
+    });
+
+    const equipment = $('equipment');
+    equipment.replaceChildren();
+    for (const item of aircraft.equipment) {
+        const label = document.createElement('label');
+        label.className = 'block text-gray-700 text-sm';
+        const checkbox = document.createElement('input');
+        checkbox.type = 'checkbox';
+        checkbox.value = item.id;
+        checkbox.className = 'equipment mr-2';
+        label.append(checkbox, `${item.name} (${item.mass} kg)`);
+        equipment.append(label);
+    }
+
+    const fuelType = $('fuel-type');
+    fuelType.replaceChildren();
+    for (const grade of type.fuel.grades) {
+        fuelType.add(new Option(grade === 'avgas' ? 'Avgas' : 'Mogas', grade));
+    }
+
+    const policy = fleet.fuel_policies[type.id];
+    const tankFields = $('tanks');
+    tankFields.replaceChildren();
+    for (const [i, tank] of tanks(type).entries()) {
+        const capacity = tank.capacity ?? policy.max_fuel;
+        const input = number('tank', capacity);
+        input.disabled = $('fuel-max').checked;
+        tankFields.append(field(`tank-${i}`, `${tank.name} (max ${round(capacity, 1)} L)`, input));
+    }
+}
+
+// Minutes of a duration entered as uu:mm.
+function minutes(id, name) {
+    const match = /^\s*(\d{1,2}):([0-5]\d)\s*$/.exec($(id).value);
+    if (!match) {
+        throw new Error(`Vul de ${name} in als uu:mm.`);
+    }
+    return Number(match[1]) * 60 + Number(match[2]);
+}
+
+function value(input) {
+    return input.value.trim() === '' ? null : Number(input.value);
+}
+
+function today() {
+    const now = new Date();
+    const pad = (n) => String(n).padStart(2, '0');
+    return `${now.getFullYear()}-${pad(now.getMonth() + 1)}-${pad(now.getDate())}`;
+}
+
+function calculate(event) {
+    event.preventDefault();
+    const { aircraft, type } = selected();
+    const errors = $('errors');
+
+    try {
+        const seats = Array.from(document.querySelectorAll('#stations .flex'), (row) =>
+            row.querySelector('.station-seat')?.value ?? null);
+        const loads = Array.from(document.querySelectorAll('.station-mass'), (input, i) => {
+            const mass = value(input);
+            return mass === null ? null : { mass, seat: seats[i] };
+        });
+        if (loads[0] === null) {
+            throw new Error('Vul het gewicht van de piloot in.');
+        }
+
+        const fuelMax = $('fuel-max').checked;
+        const fuelTanks = Array.from(document.querySelectorAll('.tank'), (input) => value(input) ?? 0);
+        const tripMinutes = minutes('trip-duration', 'reisduur');
+        const alternateMinutes = minutes('alternate-duration', 'reisduur naar de alternatieve luchthaven');
+
+        const massAndBalance = call(core.massAndBalance, {
+            aircraft,
+            aircraft_type: type,
+            fuel_policy: fleet.fuel_policy,
+            weighing_policy: fleet.weighing_policy,
+            loads,
+            equipment: Array.from(document.querySelectorAll('.equipment:checked'), (c) => c.value),
+            fuel_type: $('fuel-type').value,
+            fuel_tanks: fuelMax ? null : fuelTanks,
+            fuel_max: fuelMax,
+            trip_minutes: tripMinutes,
+            flight_date: today(),
+        });
+
+        const fuelPlan = call(core.fuelPlan, {
+            fuel_policy: fleet.fuel_policies[type.id],
+            fuel: massAndBalance.fuel_on_board,
+            trip_minutes: tripMinutes,
+            alternate_minutes: alternateMinutes,
+        });
+
+        let performance = null;
+        if (type.performance) {
+            if (value($('oat')) === null || value($('pressure-altitude')) === null) {
+                throw new Error('Vul de buitentemperatuur en de drukhoogte in.');
+            }
+            const wind = value($('wind')) ?? 0;
+            performance = call(core.performance, {
+                aircraft_type: type,
+                oat: value($('oat')),
+                pressure_altitude: value($('pressure-altitude')),
+                wind: $('wind-direction').value === 'tailwind' ? -wind : wind,
+                gust: value($('gust')),
+                takeoff_mass: massAndBalance.takeoff_mass,
+                landing_mass: massAndBalance.landing_mass,
+            });
+        }
+
+        errors.classList.add('hidden');
+        show(aircraft, type, massAndBalance, fuelPlan, performance);
+    } catch (e) {
+        // Errors of the calculation core are in English, the form checks above are in Dutch.
+        errors.textContent = `Berekening niet mogelijk: ${e.message ?? e}`;
+        errors.classList.remove('hidden');
+        $('results').classList.add('hidden');
+    }
+}
+
+function row(cells) {
+    const tr = document.createElement('tr');
+    cells.forEach((text, i) => {
+        const td = document.createElement('td');
+        td.textContent = text;
+        if (i > 0) {
+            td.className = 'text-right';
+        }
+        tr.append(td);
+    });
+    return tr;
+}
+
+function show(aircraft, type, massAndBalance, fuelPlan, performance) {
+    const limits = $('limits');
+    const ok = massAndBalance.within_limits && fuelPlan.sufficient;
+    limits.className = ok
+        ? 'mb-4 px-4 py-3 rounded border bg-green-100 border-green-400 text-green-700'
+        : 'mb-4 px-4 py-3 rounded border bg-red-100 border-red-400 text-red-700';
+    limits.textContent = [
+        massAndBalance.within_limits
+            ? 'Massa en zwaartepunt binnen de grenzen.'
+            : 'Massa of zwaartepunt buiten de grenzen.',
+        fuelPlan.sufficient ? 'Voldoende brandstof.' : 'Onvoldoende brandstof.',
+    ].join(' ');
+
+    $('loads').replaceChildren(
+        ...massAndBalance.loads.map((l) => row([
+            l.name, round(l.mass, 1), round(l.lever_arm, 3), round(l.mass * l.lever_arm, 1),
+        ])),
+        row(['Start', round(massAndBalance.takeoff_mass, 1), round(massAndBalance.takeoff_cg, 3),
+            round(massAndBalance.takeoff_mass * massAndBalance.takeoff_cg, 1)]),
+        row(['Landing', round(massAndBalance.landing_mass, 1), round(massAndBalance.landing_cg, 3),
+            round(massAndBalance.landing_mass * massAndBalance.landing_cg, 1)]),
+    );
+
+    $('fuel-plan').replaceChildren(
+        ...FUEL_PLAN.map(([key, name]) => row([name, round(fuelPlan[key], 1)])),
+        row(['Uithoudingsvermogen (min)', String(fuelPlan.endurance_minutes)]),
+    );
+
+    $('performance').replaceChildren(...(performance
+        ? PERFORMANCE.map(([key, name]) => row([name, round(performance[key], 0)]))
+        : [row(['Geen prestatiegegevens voor dit type', ''])]));
+
+    chart(type, massAndBalance);
+
+    $('print-callsign').textContent = aircraft.callsign;
+    $('print-time').textContent = new Date().toLocaleString('nl-NL');
+    $('results').classList.remove('hidden');
+}
+
+// Envelope in moment (kg m) against mass (kg), as the chart of the application, with the
+// takeoff and landing points.
+function chart(type, massAndBalance) {
+    const { min_mass, max_mass, forward_cg, aft_cg } = type.envelope;
+    const envelope = [
+        [min_mass * forward_cg, min_mass],
+        [max_mass * forward_cg, max_mass],
+        [max_mass * aft_cg, max_mass],
+        [min_mass * aft_cg, min_mass],
+    ];
+    const points = [
+        [massAndBalance.takeoff_mass * massAndBalance.takeoff_cg, massAndBalance.takeoff_mass],
+        [massAndBalance.landing_mass * massAndBalance.landing_cg, massAndBalance.landing_mass],
+    ];
+
+    const all = envelope.concat(points);
+    const margin = (min, max) => [min - (max - min) * 0.1, max + (max - min) * 0.1];
+    const [x0, x1] = type.chart?.moment
+        ?? margin(Math.min(...all.map((p) => p[0])), Math.max(...all.map((p) => p[0])));
+    const [y0, y1] = type.chart?.mass
+        ?? margin(Math.min(...all.map((p) => p[1])), Math.max(...all.map((p) => p[1])));
+
+    const [width, height, pad] = [400, 300, 40];
+    const x = (moment) => pad + (moment - x0) / (x1 - x0) * (width - 2 * pad);
+    const y = (mass) => height - pad - (mass - y0) / (y1 - y0) * (height - 2 * pad);
+    const element = (name, attributes, text) => {
+        const e = document.createElementNS(SVG, name);
+        for (const [key, value] of Object.entries(attributes)) {
+            e.setAttribute(key, value);
+        }
+        if (text !== undefined) {
+            e.textContent = text;
+        }
+        return e;
+    };
+    const coordinates = (list) => list.map(([m, kg]) => `${x(m)},${y(kg)}`).join(' ');
+
+    $('chart').replaceChildren(
+        element('rect', {
+            x: pad, y: pad, width: width - 2 * pad, height: height - 2 * pad,
+            fill: 'none', stroke: '#9ca3af',
+        }),
+        element('polygon', {
+            points: coordinates(envelope), fill: '#e0e7ff', stroke: '#4f46e5', 'stroke-width': 2,
+        }),
+        element('polyline', {
+            points: coordinates(points), fill: 'none', stroke: '#111827', 'stroke-dasharray': '4 2',
+        }),
+        ...points.map(([m, kg], i) => element('circle', {
+            cx: x(m), cy: y(kg), r: 4, fill: i === 0 ? '#b91c1c' : '#1d4ed8',
+        })),
+        element('text', { x: width / 2, y: height - 8, 'text-anchor': 'middle', 'font-size': 12 },
+            'Moment (kg m)'),
+        element('text', {
+            x: 12, y: height / 2, 'text-anchor': 'middle', 'font-size': 12,
+            transform: `rotate(-90 12 ${height / 2})`,
+        }, 'Massa (kg)'),
+        element('text', { x: pad, y: pad - 8, 'font-size': 12, fill: '#b91c1c' }, '● Start'),
+        element('text', { x: pad + 80, y: pad - 8, 'font-size': 12, fill: '#1d4ed8' }, '● Landing'),
+    );
+}
+
+start();
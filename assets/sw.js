@@ -0,0 +1,131 @@
+// Service worker that keeps the calculator usable without a connection.
+//
+// Static assets, all served by the application itself, are served from the cache first. Pages
+// and HTMX fragments are fetched from the network first and fall back to the last response
+// received for the same URL, so steps that were calculated before remain available at the
+// aircraft. Other steps open the offline calculator, which calculates with the WebAssembly build
+// of the calculation core and the last copy of the aircraft registry.
+
+const VERSION = '__VERSION__';
+const STATIC_CACHE = `acm-static-${VERSION}`;
+const PAGE_CACHE = `acm-pages-${VERSION}`;
+const FRAGMENT_CACHE = `acm-fragments-${VERSION}`;
+const OFFLINE_PAGE = '/assets/offline.html';
+const FLEET = '/fleet.json';
+
+const PRECACHE = [
+    '/',
+    OFFLINE_PAGE,
+    FLEET,
+    '/assets/app.css',
+    '/assets/icons.svg',
+    '/assets/htmx.min.js',
+    '/assets/offline.js',
+    '/assets/manifest.webmanifest',
+    '/assets/icon.svg',
+];
+
+// The WebAssembly build is only present when it was built before the server, see the README.
+const OPTIONAL_PRECACHE = [
+    '/assets/pkg/acm_weight_and_balance.js',
+    '/assets/pkg/acm_weight_and_balance_bg.wasm',
+];
+
+// Endpoints that must always reflect the live server.
+const UNCACHED = ['/health', '/ready', '/healthz', '/readyz', '/metrics', '/sw.js'];
+
+self.addEventListener('install', (event) => {
+    event.waitUntil((async () => {
+        const cache = await caches.open(STATIC_CACHE);
+        await cache.addAll(PRECACHE);
+        await Promise.all(OPTIONAL_PRECACHE.map(async (url) => {
+            try {
+                const response = await fetch(url);
+                if (response.ok) {
+                    await cache.put(url, response);
+                }
+            } catch (e) {
+                console.warn('failed to precache', url, e);
+            }
+        }));
+        await self.skipWaiting();
+    })());
+});
+
+self.addEventListener('activate', (event) => {
+    const current = [STATIC_CACHE, PAGE_CACHE, FRAGMENT_CACHE];
+    event.waitUntil((async () => {
+        const names = await caches.keys();
+        await Promise.all(names
+            .filter((name) => name.startsWith('acm-') && !current.includes(name))
+            .map((name) => caches.delete(name)));
+        await self.clients.claim();
+    })());
+});
+
+self.addEventListener('fetch', (event) => {
+    const request = event.request;
+    if (request.method !== 'GET') {
+        return;
+    }
+
+    const url = new URL(request.url);
+    if (url.origin !== self.location.origin || UNCACHED.includes(url.pathname)) {
+        return;
+    }
+
+    // The registry is refreshed whenever there is a connection, the precached copy is the fallback.
+    if (url.pathname === FLEET) {
+        event.respondWith(networkFirst(request, STATIC_CACHE, false));
+        return;
+    }
+
+    if (url.pathname.startsWith('/assets/')) {
+        event.respondWith(cacheFirst(request, STATIC_CACHE));
+        return;
+    }
+
+    // The same URL renders either a full page or an HTMX fragment, keep them apart.
+    const fragment = request.headers.has('HX-Request');
+    event.respondWith(networkFirst(request, fragment ? FRAGMENT_CACHE : PAGE_CACHE, !fragment));
+});
+
+async function cacheFirst(request, cacheName) {
+    const cached = await caches.match(request, { cacheName });
+    if (cached) {
+        return cached;
+    }
+
+    const response = await fetch(request);
+    if (response.ok) {
+        const cache = await caches.open(cacheName);
+        await cache.put(request, response.clone());
+    }
+    return response;
+}
+
+async function networkFirst(request, cacheName, navigation) {
+    const cache = await caches.open(cacheName);
+    try {
+        const response = await fetch(request);
+        if (response.ok) {
+            await cache.put(request, response.clone());
+        }
+        return response;
+    } catch (e) {
+        const cached = await cache.match(request);
+        if (cached) {
+            return cached;
+        }
+
+        const offline = await caches.match(OFFLINE_PAGE, { cacheName: STATIC_CACHE });
+        if (navigation && offline) {
+            return offline;
+        }
+
+        return new Response('Geen verbinding', {
+            status: 503,
+            headers: { 'Content-Type': 'text/plain; charset=utf-8' },
+        });
+    }
+}
@@ -0,0 +1,805 @@
+use std::{fs, path::Path};
+
+use airplane::types::FuelType;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    date::{self, Date},
+    fuel,
+    performance::{self, PerformanceModel},
+};
+
+const EMBEDDED_REGISTRY: &str = include_str!("../data/aircraft.toml");
+
+/// Type of the aircraft in registries that predate aircraft types.
+pub const DEFAULT_AIRCRAFT_TYPE: &str = "AT01";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Aircraft {
+    pub callsign: String,
+    #[serde(rename = "type", default = "default_aircraft_type")]
+    pub aircraft_type: String,
+    /// Weighing reports, the report valid on the flight date gives the empty mass.
+    #[serde(default, rename = "weighing")]
+    pub weighings: Vec<Weighing>,
+    /// Empty mass of registries without weighing reports.
+    pub empty_mass: Option<f64>,
+    pub empty_mass_arm: Option<f64>,
+    /// Removable items that can be selected per flight.
+    #[serde(default)]
+    pub equipment: Vec<Equipment>,
+}
+
+impl Aircraft {
+    /// Weighing report valid on the given date, the most recent report when no date is given.
+    /// Aircraft without reports have a single report without date and reference.
+    pub fn weighing(&self, on: Option<Date>) -> Option<Weighing> {
+        if self.weighings.is_empty() {
+            return Some(Weighing {
+                date: None,
+                reference: None,
+                empty_mass: self.empty_mass?,
+                empty_mass_arm: self.empty_mass_arm?,
+            });
+        }
+
+        self.weighings
+            .iter()
+            .filter_map(|w| Some((w.date.as_deref().and_then(date::parse_date)?, w)))
+            .filter(|(d, _)| on.is_none_or(|on| *d <= on))
+            .max_by_key(|(d, _)| *d)
+            .map(|(_, w)| w.clone())
+    }
+}
+
+/// Removable item of optional equipment, such as a tow hook or a camera, with its mass in
+/// kilograms and its arm in meters. Items are included in a flight on top of the empty mass.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Equipment {
+    /// Identifier used to select the item, unique per aircraft.
+    pub id: String,
+    pub name: String,
+    pub mass: f64,
+    pub arm: f64,
+}
+
+/// Report of a weighing or modification, with the empty mass in kilograms and its arm in meters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Weighing {
+    /// Date of the report as `YYYY-MM-DD`.
+    pub date: Option<String>,
+    /// Reference of the weighing report or modification document.
+    pub reference: Option<String>,
+    pub empty_mass: f64,
+    pub empty_mass_arm: f64,
+}
+
+fn default_aircraft_type() -> String {
+    DEFAULT_AIRCRAFT_TYPE.to_string()
+}
+
+/// Period after which a weighing report is no longer valid.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeighingPolicy {
+    pub validity_months: Option<u32>,
+}
+
+impl WeighingPolicy {
+    /// Checks whether the report is still valid on the given date, reports without a date are
+    /// only valid when there is no validity period.
+    pub fn valid(&self, weighing: &Weighing, on: Date) -> bool {
+        let Some(months) = self.validity_months else {
+            return true;
+        };
+
+        match weighing.date.as_deref().and_then(date::parse_date) {
+            Some(weighed) => on <= date::add_months(weighed, months),
+            None => false,
+        }
+    }
+}
+
+/// Aircraft type with the data from its flight manual, masses are in kilograms, arms in meters
+/// and volumes in liters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AircraftType {
+    pub id: String,
+    pub name: String,
+    pub envelope: Envelope,
+    /// Seats and baggage areas, the pilot sits on the first station.
+    #[serde(rename = "station")]
+    pub stations: Vec<Station>,
+    pub fuel: FuelSystem,
+    #[serde(default)]
+    pub wind_limits: WindLimits,
+    /// Axes of the weight and balance chart, derived from the envelope when left out.
+    #[serde(default)]
+    pub chart: Option<ChartRanges>,
+    /// Oil and other fluids that are not part of the empty mass.
+    #[serde(default)]
+    pub fluids: Vec<Fluid>,
+    /// Name of the performance model, types without one are calculated without performance.
+    pub performance: Option<String>,
+}
+
+impl AircraftType {
+    pub fn performance_model(&self) -> Option<&'static dyn PerformanceModel> {
+        self.performance.as_deref().and_then(performance::model)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("envelope.min_mass", self.envelope.min_mass),
+            ("envelope.max_mass", self.envelope.max_mass),
+            ("envelope.forward_cg", self.envelope.forward_cg),
+            ("envelope.aft_cg", self.envelope.aft_cg),
+        ];
+
+        for (name, value) in positive {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("{}: {} must be positive", self.id, name));
+            }
+        }
+
+        if self.envelope.min_mass >= self.envelope.max_mass
+            || self.envelope.forward_cg >= self.envelope.aft_cg
+        {
+            return Err(format!("{}: envelope is empty", self.id));
+        }
+
+        if self.stations.is_empty() {
+            return Err(format!(
+                "{}: needs at least the station of the pilot",
+                self.id
+            ));
+        }
+
+        for (i, station) in self.stations.iter().enumerate() {
+            station
+                .validate()
+                .map_err(|e| format!("{}: station {}: {}", self.id, i + 1, e))?;
+
+            if self.stations[..i].iter().any(|s| s.name == station.name) {
+                return Err(format!("{}: duplicate station {}", self.id, station.name));
+            }
+        }
+
+        self.fuel
+            .validate()
+            .map_err(|e| format!("{}: {}", self.id, e))?;
+
+        for fluid in &self.fluids {
+            if fluid.name.trim().is_empty() {
+                return Err(format!("{}: fluids need a name", self.id));
+            }
+
+            if !(fluid.mass.is_finite() && fluid.mass > 0.0) {
+                return Err(format!(
+                    "{}: mass of {} must be positive",
+                    self.id, fluid.name
+                ));
+            }
+
+            if !fluid.arm.is_finite() {
+                return Err(format!("{}: arm of {} is invalid", self.id, fluid.name));
+            }
+        }
+
+        if let Some(chart) = &self.chart {
+            for (name, (min, max)) in [("chart.moment", chart.moment), ("chart.mass", chart.mass)] {
+                if !(min.is_finite() && max.is_finite() && min < max) {
+                    return Err(format!("{}: {} must be an increasing range", self.id, name));
+                }
+            }
+        }
+
+        let max_tailwind = match &self.performance {
+            Some(name) => match performance::model(name) {
+                Some(model) => model.scale().max_tailwind,
+                None => {
+                    return Err(format!(
+                        "{}: unknown performance model {}, expected one of {}",
+                        self.id,
+                        name,
+                        performance::MODELS.join(", ")
+                    ))
+                }
+            },
+            None => f64::INFINITY,
+        };
+
+        self.wind_limits
+            .validate(max_tailwind)
+            .map_err(|e| format!("{}: {}", self.id, e))
+    }
+}
+
+/// Mass and center of gravity limits in kilograms and meters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Envelope {
+    pub min_mass: f64,
+    pub max_mass: f64,
+    pub forward_cg: f64,
+    pub aft_cg: f64,
+}
+
+/// Loading station of a type, a seat or a baggage area, with its lever arm in meters. A seat
+/// that can be adjusted has an arm for each of its positions instead of a single arm.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Station {
+    pub name: String,
+    pub arm: Option<f64>,
+    pub positions: Option<SeatPositions>,
+    /// Maximum mass on the station in kilograms, such as the limit of a baggage area.
+    pub max_mass: Option<f64>,
+}
+
+/// Lever arms of an adjustable seat in its forward, middle and backward positions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeatPositions {
+    pub forward: f64,
+    pub middle: f64,
+    pub backward: f64,
+}
+
+impl Station {
+    /// Whether the seat can be put in another position.
+    pub fn adjustable(&self) -> bool {
+        self.positions.is_some()
+    }
+
+    /// Lever arm of the station, of an adjustable seat in the forward (`f`), middle or backward
+    /// (`b`) position.
+    pub fn lever_arm(&self, position: &str) -> f64 {
+        match &self.positions {
+            Some(p) => match position {
+                "f" => p.forward,
+                "b" => p.backward,
+                _ => p.middle,
+            },
+            None => self.arm.unwrap_or_default(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("needs a name".to_string());
+        }
+
+        let arms = match (&self.positions, self.arm) {
+            (Some(p), None) => vec![p.forward, p.middle, p.backward],
+            (None, Some(arm)) => vec![arm],
+            _ => return Err("needs either an arm or positions".to_string()),
+        };
+
+        if !arms.iter().all(|a| a.is_finite() && *a > 0.0) {
+            return Err("lever arm must be positive".to_string());
+        }
+
+        if self.max_mass.is_some_and(|m| !(m.is_finite() && m > 0.0)) {
+            return Err("max_mass must be positive".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Fuel system of the type, either a single tank described by its lever arm or a set of tanks.
+/// The maximum fuel and the consumption replace those of the fuel policy when they are set.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FuelSystem {
+    /// Lever arm of the single tank of types without tanks.
+    pub lever_arm: Option<f64>,
+    /// Fuel the single tank holds, including the unusable fuel.
+    pub max_fuel: Option<f64>,
+    pub consumption_per_hour: Option<f64>,
+    /// Unusable fuel in liters of the single tank that is not part of the empty mass. It is
+    /// loaded at the lever arm of the tank on top of the fuel on board, which is the usable fuel.
+    pub unusable: Option<f64>,
+    /// Tanks of types with more than one tank, in the order they are filled.
+    #[serde(default, rename = "tank")]
+    pub tanks: Vec<Tank>,
+    /// Fuel grades the type may use, `avgas` and `mogas` by default.
+    #[serde(default = "default_grades")]
+    pub grades: Vec<String>,
+}
+
+fn default_grades() -> Vec<String> {
+    vec![
+        fuel::grade(&FuelType::Avgas).to_string(),
+        fuel::grade(&FuelType::Mogas).to_string(),
+    ]
+}
+
+impl FuelSystem {
+    /// Whether the type may use the fuel grade.
+    pub fn allows(&self, fuel_type: &FuelType) -> bool {
+        let grade = fuel::grade(fuel_type);
+        self.grades.iter().any(|g| g == grade)
+    }
+
+    /// Tanks of the type, types without tanks have a single tank.
+    pub fn tanks(&self) -> Vec<Tank> {
+        if !self.tanks.is_empty() {
+            return self.tanks.clone();
+        }
+
+        vec![Tank {
+            name: crate::plane::FUEL.to_string(),
+            capacity: None,
+            unusable: self.unusable,
+            lever_arm: self.lever_arm.unwrap_or_default(),
+            sequence: 1,
+        }]
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("fuel.max_fuel", self.max_fuel),
+            ("fuel.consumption_per_hour", self.consumption_per_hour),
+        ] {
+            if value.is_some_and(|v| !(v.is_finite() && v > 0.0)) {
+                return Err(format!("{} must be positive", name));
+            }
+        }
+
+        if self.tanks.is_empty() {
+            if !self.lever_arm.is_some_and(|a| a.is_finite() && a > 0.0) {
+                return Err("fuel.lever_arm must be positive".to_string());
+            }
+        } else if self.lever_arm.is_some() || self.max_fuel.is_some() || self.unusable.is_some() {
+            return Err(
+                "fuel.lever_arm, fuel.max_fuel and fuel.unusable are given by the tanks"
+                    .to_string(),
+            );
+        }
+
+        if self.grades.is_empty() {
+            return Err("fuel.grades needs at least one grade".to_string());
+        }
+
+        for (i, grade) in self.grades.iter().enumerate() {
+            if fuel::parse_grade(grade).is_none() || self.grades[..i].contains(grade) {
+                return Err(format!(
+                    "fuel.grades has an unknown or duplicate grade {}",
+                    grade
+                ));
+            }
+        }
+
+        for (i, tank) in self.tanks().iter().enumerate() {
+            if tank.name.trim().is_empty() {
+                return Err(format!("fuel tank {} has no name", i + 1));
+            }
+
+            if self.tanks[..i].iter().any(|t| t.name == tank.name) {
+                return Err(format!("duplicate fuel tank {}", tank.name));
+            }
+
+            if !self.tanks.is_empty() {
+                if !tank.capacity.is_some_and(|c| c.is_finite() && c > 0.0) {
+                    return Err(format!(
+                        "capacity of fuel tank {} must be positive",
+                        tank.name
+                    ));
+                }
+
+                if !(tank.lever_arm.is_finite() && tank.lever_arm > 0.0) {
+                    return Err(format!(
+                        "lever arm of fuel tank {} must be positive",
+                        tank.name
+                    ));
+                }
+            }
+
+            if tank.unusable.is_some_and(|v| !(v.is_finite() && v >= 0.0)) {
+                return Err(format!(
+                    "unusable fuel of {} must not be negative",
+                    tank.name
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Fuel tank with its usable capacity and unusable fuel in liters and its lever arm in meters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tank {
+    pub name: String,
+    /// Usable capacity, the single tank of types without tanks holds the maximum fuel of the
+    /// fuel policy.
+    pub capacity: Option<f64>,
+    /// Unusable fuel that is not part of the empty mass.
+    pub unusable: Option<f64>,
+    pub lever_arm: f64,
+    /// Position in the burn sequence, tanks with the same position are used together in
+    /// proportion to their contents.
+    #[serde(default = "default_sequence")]
+    pub sequence: u32,
+}
+
+fn default_sequence() -> u32 {
+    1
+}
+
+/// Oil or another fluid with its mass in kilograms and its arm in meters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fluid {
+    pub name: String,
+    pub mass: f64,
+    pub arm: f64,
+}
+
+/// Ranges of the axes of the weight and balance chart, in kilogram meters and kilograms.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChartRanges {
+    pub moment: (f64, f64),
+    pub mass: (f64, f64),
+}
+
+/// Fuel planning policy, volumes are in liters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FuelPolicy {
+    pub consumption_per_hour: f64,
+    pub taxi: f64,
+    pub reserve_minutes: f64,
+    pub contingency_percentage: f64,
+    pub max_fuel: f64,
+}
+
+impl Default for FuelPolicy {
+    fn default() -> FuelPolicy {
+        FuelPolicy {
+            consumption_per_hour: 17.0,
+            taxi: 2.0,
+            reserve_minutes: 45.0,
+            contingency_percentage: 10.0,
+            max_fuel: 110.0,
+        }
+    }
+}
+
+impl FuelPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        let values = [
+            ("consumption_per_hour", self.consumption_per_hour),
+            ("taxi", self.taxi),
+            ("reserve_minutes", self.reserve_minutes),
+            ("contingency_percentage", self.contingency_percentage),
+            ("max_fuel", self.max_fuel),
+        ];
+
+        for (name, value) in values {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(format!("fuel policy: {} must not be negative", name));
+            }
+        }
+
+        if self.consumption_per_hour == 0.0 || self.max_fuel == 0.0 {
+            return Err("fuel policy: consumption and max fuel must be positive".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Fuel prices per liter in the given currency, grades without a price get no cost estimate.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FuelPrices {
+    pub currency: String,
+    pub avgas: Option<f64>,
+    pub mogas: Option<f64>,
+}
+
+impl Default for FuelPrices {
+    fn default() -> FuelPrices {
+        FuelPrices {
+            currency: "EUR".to_string(),
+            avgas: None,
+            mogas: None,
+        }
+    }
+}
+
+impl FuelPrices {
+    pub fn price(&self, fuel_type: &FuelType) -> Option<f64> {
+        match fuel_type {
+            FuelType::Avgas => self.avgas,
+            FuelType::Mogas => self.mogas,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.currency.trim().is_empty() {
+            return Err("fuel prices: currency is required".to_string());
+        }
+
+        for (name, value) in [("avgas", self.avgas), ("mogas", self.mogas)] {
+            if value.is_some_and(|v| !(v.is_finite() && v >= 0.0)) {
+                return Err(format!("fuel prices: {} must not be negative", name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Wind limits from the flight manual, in knots.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindLimits {
+    pub max_tailwind: f64,
+}
+
+impl Default for WindLimits {
+    fn default() -> WindLimits {
+        WindLimits {
+            max_tailwind: performance::MAX_TAILWIND,
+        }
+    }
+}
+
+impl WindLimits {
+    /// Checks the limits against the largest tailwind the performance charts can read.
+    pub fn validate(&self, chart_max_tailwind: f64) -> Result<(), String> {
+        if !(self.max_tailwind.is_finite()
+            && self.max_tailwind >= 0.0
+            && self.max_tailwind <= chart_max_tailwind)
+        {
+            return Err(format!(
+                "wind limits: max_tailwind must be between 0 and the chart scale of {} kt",
+                chart_max_tailwind
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AircraftRegistry {
+    #[serde(default)]
+    pub fuel_policy: FuelPolicy,
+    #[serde(default)]
+    pub fuel_prices: FuelPrices,
+    #[serde(default)]
+    pub weighing_policy: WeighingPolicy,
+    /// Aircraft types of the fleet, the types of the embedded registry when none are given.
+    #[serde(default, rename = "aircraft_type")]
+    pub types: Vec<AircraftType>,
+    pub aircraft: Vec<Aircraft>,
+}
+
+impl AircraftRegistry {
+    /// Loads the registry from the given file, or the embedded registry when no file is given.
+    pub fn load(path: Option<&Path>) -> Result<AircraftRegistry, String> {
+        let content = match path {
+            Some(p) => fs::read_to_string(p)
+                .map_err(|e| format!("unable to read {}: {}", p.display(), e))?,
+            None => EMBEDDED_REGISTRY.to_string(),
+        };
+
+        let mut registry = parse(&content)?;
+        if registry.types.is_empty() {
+            registry.types = parse(EMBEDDED_REGISTRY)?.types;
+        }
+        registry.validate()?;
+
+        Ok(registry)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.fuel_policy.validate()?;
+        self.fuel_prices.validate()?;
+
+        for (i, aircraft_type) in self.types.iter().enumerate() {
+            if self.types[..i].iter().any(|t| t.id == aircraft_type.id) {
+                return Err(format!("duplicate aircraft type {}", aircraft_type.id));
+            }
+
+            aircraft_type.validate()?;
+
+            let policy = self.fuel_policy(aircraft_type);
+            if policy.max_fuel <= 0.0 {
+                return Err(format!(
+                    "{}: fuel.unusable must be less than the maximum fuel",
+                    aircraft_type.id
+                ));
+            }
+            policy
+                .validate()
+                .map_err(|e| format!("{}: {}", aircraft_type.id, e))?;
+        }
+
+        if self.aircraft.is_empty() {
+            return Err("aircraft registry is empty".to_string());
+        }
+
+        for (i, aircraft) in self.aircraft.iter().enumerate() {
+            if aircraft.callsign.trim().is_empty() {
+                return Err(format!("aircraft {} has no callsign", i + 1));
+            }
+
+            if self.aircraft[..i]
+                .iter()
+                .any(|a| a.callsign == aircraft.callsign)
+            {
+                return Err(format!("duplicate callsign {}", aircraft.callsign));
+            }
+
+            if self.aircraft_type(&aircraft.aircraft_type).is_none() {
+                return Err(format!(
+                    "{}: unknown aircraft type {}",
+                    aircraft.callsign, aircraft.aircraft_type
+                ));
+            }
+
+            if aircraft.weighings.is_empty() {
+                match aircraft.weighing(None) {
+                    Some(w) => check_empty_mass(&aircraft.callsign, &w)?,
+                    None => {
+                        return Err(format!(
+                            "{}: needs an empty mass and arm or weighing reports",
+                            aircraft.callsign
+                        ))
+                    }
+                }
+            } else if aircraft.empty_mass.is_some() || aircraft.empty_mass_arm.is_some() {
+                return Err(format!(
+                    "{}: the empty mass is given by the weighing reports",
+                    aircraft.callsign
+                ));
+            }
+
+            for weighing in &aircraft.weighings {
+                if weighing
+                    .date
+                    .as_deref()
+                    .and_then(date::parse_date)
+                    .is_none()
+                {
+                    return Err(format!(
+                        "{}: weighing reports need a date formatted as YYYY-MM-DD",
+                        aircraft.callsign
+                    ));
+                }
+
+                if weighing
+                    .reference
+                    .as_deref()
+                    .is_none_or(|r| r.trim().is_empty())
+                {
+                    return Err(format!(
+                        "{}: weighing reports need a reference",
+                        aircraft.callsign
+                    ));
+                }
+
+                check_empty_mass(&aircraft.callsign, weighing)?;
+            }
+
+            for (j, item) in aircraft.equipment.iter().enumerate() {
+                check_equipment(&aircraft.callsign, item)?;
+
+                if aircraft.equipment[..j].iter().any(|e| e.id == item.id) {
+                    return Err(format!(
+                        "{}: duplicate equipment {}",
+                        aircraft.callsign, item.id
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, callsign: &str) -> Option<&Aircraft> {
+        self.aircraft.iter().find(|a| a.callsign == callsign)
+    }
+
+    pub fn callsigns(&self) -> Vec<&str> {
+        self.aircraft.iter().map(|a| a.callsign.as_str()).collect()
+    }
+
+    pub fn aircraft_type(&self, id: &str) -> Option<&AircraftType> {
+        self.types.iter().find(|t| t.id == id)
+    }
+
+    /// Type of a registered aircraft, every aircraft refers to a known type once the registry
+    /// is validated.
+    pub fn type_of(&self, aircraft: &Aircraft) -> &AircraftType {
+        self.aircraft_type(&aircraft.aircraft_type)
+            .expect("aircraft type should be validated")
+    }
+
+    /// Fuel policy for an aircraft type, with the maximum fuel and consumption of the type. The
+    /// maximum fuel is the usable fuel: that of the single tank without its unusable fuel, or
+    /// the total capacity of the tanks of a type with tanks.
+    pub fn fuel_policy(&self, aircraft_type: &AircraftType) -> FuelPolicy {
+        FuelPolicy {
+            consumption_per_hour: aircraft_type
+                .fuel
+                .consumption_per_hour
+                .unwrap_or(self.fuel_policy.consumption_per_hour),
+            max_fuel: match aircraft_type.fuel.tanks.as_slice() {
+                [] => {
+                    aircraft_type
+                        .fuel
+                        .max_fuel
+                        .unwrap_or(self.fuel_policy.max_fuel)
+                        - aircraft_type.fuel.unusable.unwrap_or_default()
+                }
+                tanks => tanks.iter().filter_map(|t| t.capacity).sum(),
+            },
+            ..self.fuel_policy.clone()
+        }
+    }
+}
+
+fn check_empty_mass(callsign: &str, weighing: &Weighing) -> Result<(), String> {
+    if !(weighing.empty_mass.is_finite() && weighing.empty_mass > 0.0) {
+        return Err(format!("{}: empty mass must be positive", callsign));
+    }
+
+    if !(weighing.empty_mass_arm.is_finite() && weighing.empty_mass_arm > 0.0) {
+        return Err(format!("{}: empty mass arm must be positive", callsign));
+    }
+
+    Ok(())
+}
+
+fn parse(content: &str) -> Result<AircraftRegistry, String> {
+    toml::from_str(content).map_err(|e| format!("invalid aircraft registry: {}", e))
+}
+
+fn check_equipment(callsign: &str, item: &Equipment) -> Result<(), String> {
+    if item.id.is_empty()
+        || !item
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err(format!(
+            "{}: equipment id {:?} may only contain letters, digits and dashes",
+            callsign, item.id
+        ));
+    }
+
+    if item.name.trim().is_empty() {
+        return Err(format!("{}: equipment {} has no name", callsign, item.id));
+    }
+
+    if !(item.mass.is_finite() && item.mass > 0.0) {
+        return Err(format!(
+            "{}: mass of equipment {} must be positive",
+            callsign, item.id
+        ));
+    }
+
+    // Items ahead of the datum have a negative arm.
+    if !item.arm.is_finite() {
+        return Err(format!(
+            "{}: arm of equipment {} is invalid",
+            callsign, item.id
+        ));
+    }
+
+    Ok(())
+}
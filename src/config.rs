@@ -0,0 +1,194 @@
+use std::{collections::BTreeSet, fs, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Feature {
+    ImperialUnits,
+    Metrics,
+}
+
+impl Feature {
+    const ALL: [Feature; 2] = [Feature::ImperialUnits, Feature::Metrics];
+
+    fn parse(value: &str) -> Result<Feature, String> {
+        Feature::ALL
+            .into_iter()
+            .find(|f| f.as_str() == value.trim())
+            .ok_or_else(|| format!("unknown feature `{}`", value.trim()))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feature::ImperialUnits => "imperial-units",
+            Feature::Metrics => "metrics",
+        }
+    }
+}
+
+/// Command line flags, each of which can also be set through an environment variable.
+#[derive(Parser)]
+#[command(version, about = "ACM weight and balance calculator")]
+struct Cli {
+    /// Optional TOML configuration file
+    #[arg(long, env = "ACM_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Listen address of the application server
+    #[arg(long, env = "ACM_LISTEN")]
+    listen: Option<String>,
+
+    /// Listen address of the health and metrics server
+    #[arg(long, env = "ACM_HEALTH_LISTEN")]
+    health_listen: Option<String>,
+
+    /// Number of HTTP workers per server, defaults to the number of CPU cores
+    #[arg(long, env = "ACM_WORKERS")]
+    workers: Option<usize>,
+
+    /// TOML file with the aircraft registry, defaults to the embedded fleet
+    #[arg(long, env = "ACM_AIRCRAFT_FILE")]
+    aircraft_file: Option<PathBuf>,
+
+    /// TOML file with the airports used to plan routes, defaults to the embedded database
+    #[arg(long, env = "ACM_AIRPORTS_FILE")]
+    airports_file: Option<PathBuf>,
+
+    /// Directory with templates overriding the embedded ones
+    #[arg(long, env = "ACM_TEMPLATE_DIR")]
+    template_dir: Option<PathBuf>,
+
+    /// Directory where loadsheets are stored
+    #[arg(long, env = "ACM_STORAGE_DIR")]
+    storage_dir: Option<PathBuf>,
+
+    /// File with the key used to sign printed loadsheets, signing is disabled when not set
+    #[arg(long, env = "ACM_SIGNING_KEY_FILE")]
+    signing_key_file: Option<PathBuf>,
+
+    /// Comma separated list of enabled features, defaults to all features
+    #[arg(long, env = "ACM_FEATURES", value_delimiter = ',')]
+    features: Option<Vec<String>>,
+
+    /// Seconds between checks of the aircraft file and template directory for changes,
+    /// disabled when not set
+    #[arg(long, env = "ACM_RELOAD_INTERVAL")]
+    reload_interval: Option<u64>,
+
+    /// Seconds to wait for requests in flight to finish on shutdown
+    #[arg(long, env = "ACM_SHUTDOWN_TIMEOUT")]
+    shutdown_timeout: Option<u64>,
+
+    /// Report the server as not ready when the performance self-check finds violations
+    #[arg(long, env = "ACM_STRICT", num_args = 0..=1, default_missing_value = "true")]
+    strict: Option<bool>,
+
+    /// Run the performance self-check, print the violations found and exit
+    #[arg(long)]
+    self_check: bool,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    listen: Option<String>,
+    health_listen: Option<String>,
+    workers: Option<usize>,
+    aircraft_file: Option<PathBuf>,
+    airports_file: Option<PathBuf>,
+    template_dir: Option<PathBuf>,
+    storage_dir: Option<PathBuf>,
+    signing_key_file: Option<PathBuf>,
+    features: Option<Vec<String>>,
+    reload_interval: Option<u64>,
+    shutdown_timeout: Option<u64>,
+    strict: Option<bool>,
+}
+
+/// Resolved configuration; command line flags take precedence over environment variables,
+/// which take precedence over the configuration file.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub listen: String,
+    pub health_listen: String,
+    pub workers: Option<usize>,
+    pub aircraft_file: Option<PathBuf>,
+    pub airports_file: Option<PathBuf>,
+    pub template_dir: Option<PathBuf>,
+    pub storage_dir: Option<PathBuf>,
+    pub signing_key_file: Option<PathBuf>,
+    pub features: BTreeSet<Feature>,
+    pub reload_interval: Option<Duration>,
+    pub shutdown_timeout: u64,
+    pub strict: bool,
+    pub self_check: bool,
+}
+
+impl Config {
+    pub fn load() -> Result<Config, String> {
+        let cli = Cli::parse();
+
+        let file = match &cli.config {
+            Some(path) => {
+                let content = fs::read_to_string(path)
+                    .map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+                toml::from_str(&content)
+                    .map_err(|e| format!("unable to parse {}: {}", path.display(), e))?
+            }
+            None => FileConfig::default(),
+        };
+
+        let features = match cli.features.or(file.features) {
+            Some(names) => names
+                .iter()
+                .filter(|n| !n.trim().is_empty())
+                .map(|n| Feature::parse(n))
+                .collect::<Result<BTreeSet<Feature>, String>>()?,
+            None => Feature::ALL.into_iter().collect(),
+        };
+
+        let config = Config {
+            listen: cli
+                .listen
+                .or(file.listen)
+                .unwrap_or_else(|| "0.0.0.0:80".to_string()),
+            health_listen: cli
+                .health_listen
+                .or(file.health_listen)
+                .unwrap_or_else(|| "0.0.0.0:8081".to_string()),
+            workers: cli.workers.or(file.workers),
+            aircraft_file: cli.aircraft_file.or(file.aircraft_file),
+            airports_file: cli.airports_file.or(file.airports_file),
+            template_dir: cli.template_dir.or(file.template_dir),
+            storage_dir: cli.storage_dir.or(file.storage_dir),
+            signing_key_file: cli.signing_key_file.or(file.signing_key_file),
+            features,
+            reload_interval: cli
+                .reload_interval
+                .or(file.reload_interval)
+                .map(Duration::from_secs),
+            shutdown_timeout: cli.shutdown_timeout.or(file.shutdown_timeout).unwrap_or(30),
+            strict: cli.strict.or(file.strict).unwrap_or_default(),
+            self_check: cli.self_check,
+        };
+
+        if config.workers == Some(0) {
+            return Err("workers must be at least 1".to_string());
+        }
+
+        if config.reload_interval == Some(Duration::ZERO) {
+            return Err("reload interval must be at least 1 second".to_string());
+        }
+
+        Ok(config)
+    }
+
+    pub fn enabled(&self, feature: Feature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    pub fn feature_names(&self) -> Vec<&'static str> {
+        self.features.iter().map(|f| f.as_str()).collect()
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Calendar date as year, month and day, tuples of this form order chronologically.
+pub type Date = (u32, u32, u32);
+
+/// Parses a date formatted as `YYYY-MM-DD`, as submitted by a date input.
+pub fn parse_date(value: &str) -> Option<Date> {
+    let mut parts = value.split('-');
+    let year: u32 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+
+    if parts.next().is_some() || !(1900..=9999).contains(&year) {
+        return None;
+    }
+
+    if day == 0 || day > days_in_month(year, month)? {
+        return None;
+    }
+
+    Some((year, month, day))
+}
+
+pub fn format_date((year, month, day): Date) -> String {
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Current date in UTC.
+pub fn today() -> Date {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or_default() as i64;
+
+    // Civil date from the days since 1970-01-01, counted in eras of 400 years from 0000-03-01.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year as u32, month as u32, day as u32)
+}
+
+/// Adds a number of months, the day is clamped to the length of the resulting month.
+pub fn add_months((year, month, day): Date, months: u32) -> Date {
+    let index = year * 12 + month - 1 + months;
+    let (year, month) = (index / 12, index % 12 + 1);
+    let days = days_in_month(year, month).unwrap_or(28);
+
+    (year, month, day.min(days))
+}
+
+fn days_in_month(year: u32, month: u32) -> Option<u32> {
+    let leap = (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400);
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => Some(31),
+        4 | 6 | 9 | 11 => Some(30),
+        2 if leap => Some(29),
+        2 => Some(28),
+        _ => None,
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use airplane::types::FuelType;
+
+use crate::aircraft::FuelPolicy;
+
+/// Name of a fuel grade as used in the aircraft file and the forms.
+pub fn grade(fuel_type: &FuelType) -> &'static str {
+    match fuel_type {
+        FuelType::Avgas => "avgas",
+        FuelType::Mogas => "mogas",
+    }
+}
+
+pub fn parse_grade(value: &str) -> Option<FuelType> {
+    match value {
+        "avgas" => Some(FuelType::Avgas),
+        "mogas" => Some(FuelType::Mogas),
+        _ => None,
+    }
+}
+
+/// Breakdown of the fuel on board according to the fuel policy, volumes are in liters.
+#[derive(Clone, Debug, Serialize)]
+pub struct FuelPlan {
+    pub taxi: f64,
+    pub trip: f64,
+    pub alternate: f64,
+    pub reserve: f64,
+    pub contingency: f64,
+    /// Fuel left after all required fuel, negative when there is not enough fuel on board.
+    pub extra: f64,
+    pub total: f64,
+    pub endurance_minutes: u64,
+}
+
+impl FuelPlan {
+    pub fn new(
+        policy: &FuelPolicy,
+        total: f64,
+        trip_duration: Duration,
+        alternate_duration: Duration,
+    ) -> FuelPlan {
+        let consumption = |d: Duration| policy.consumption_per_hour * d.as_secs_f64() / 60.0 / 60.0;
+
+        let trip = consumption(trip_duration);
+        let alternate = consumption(alternate_duration);
+        let reserve = policy.consumption_per_hour * policy.reserve_minutes / 60.0;
+        let contingency = trip * policy.contingency_percentage / 100.0;
+
+        FuelPlan {
+            taxi: policy.taxi,
+            trip,
+            alternate,
+            reserve,
+            contingency,
+            extra: total - policy.taxi - reserve - trip - alternate - contingency,
+            total,
+            endurance_minutes: (total / policy.consumption_per_hour * 60.0 * 60.0) as u64 / 60,
+        }
+    }
+
+    pub fn sufficient(&self) -> bool {
+        self.extra.is_sign_positive()
+    }
+}
+
+/// Fuel to add at the pump to get from the fuel on board before refuelling to the fuel for the
+/// flight, volumes are in liters.
+#[derive(Clone, Debug, Serialize)]
+pub struct Refuel {
+    pub uplift: f64,
+    /// Estimated cost of the uplift, when the grade has a price.
+    pub cost: Option<f64>,
+}
+
+impl Refuel {
+    pub fn new(before: f64, on_board: f64, price: Option<f64>) -> Refuel {
+        let uplift = (on_board - before).max(0.0);
+
+        Refuel {
+            uplift,
+            cost: price.map(|p| uplift * p),
+        }
+    }
+}
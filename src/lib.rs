@@ -0,0 +1,15 @@
+//! Calculation core of ACM Weight And Balance: mass and balance, fuel planning and the
+//! performance charts of the aircraft types. It has no server dependencies, so the same code
+//! runs in the server and, built for `wasm32-unknown-unknown`, in the browser and other tools.
+
+pub mod aircraft;
+pub mod date;
+pub mod fuel;
+pub mod performance;
+pub mod performance_check;
+pub mod plane;
+pub mod route;
+pub mod units;
+
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
@@ -1,16 +1,29 @@
+mod config;
+mod metrics;
 mod routes;
 mod models;
+mod runtime;
+mod signing;
+mod storage;
 mod utils;
 
-use actix_web::{web, App, HttpServer, HttpRequest, HttpResponse, Responder};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use acm_weight_and_balance::performance_check::{self, Grid};
+use actix_web::{dev::Service, http::header, web, App, HttpServer, HttpRequest, HttpResponse, Responder};
 use rust_embed::RustEmbed;
-use tera::Tera;
 use mime_guess::from_path;
 use tokio;
+use tracing_actix_web::TracingLogger;
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
-#[derive(RustEmbed)]
-#[folder = "templates/"]
-struct Templates;
+use config::{Config, Feature};
+use routes::health::{self, PerformanceCheck};
+use runtime::{Runtime, SharedRuntime};
+use storage::Storage;
 
 #[derive(RustEmbed)]
 #[folder = "assets/"]
@@ -31,36 +44,169 @@ async fn serve_asset(req: HttpRequest) -> impl Responder {
     }
 }
 
-async fn health_check() -> impl Responder {
-    HttpResponse::Ok().body("OK")
+/// Serves the service worker from the root so its scope covers the whole application. The cache
+/// names include the package version, so a new release replaces the cached assets.
+async fn serve_service_worker() -> impl Responder {
+    match Assets::get("sw.js") {
+        Some(content) => {
+            let script = String::from_utf8_lossy(&content.data)
+                .replace("__VERSION__", env!("CARGO_PKG_VERSION"));
+            HttpResponse::Ok()
+                .content_type("text/javascript")
+                .insert_header((header::CACHE_CONTROL, "no-cache"))
+                .body(script)
+        }
+        None => HttpResponse::NotFound().body("Asset not found"),
+    }
+}
+
+fn init_tracing() {
+    tracing_subscriber::fmt()
+        .json()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_span_events(FmtSpan::CLOSE)
+        .with_current_span(false)
+        .init();
+}
+
+fn reload(shared: &SharedRuntime, config: &Config, trigger: &str) {
+    match shared.reload(config) {
+        Ok(()) => tracing::info!(trigger, "reloaded aircraft registry and templates"),
+        Err(e) => tracing::error!(trigger, error = %e, "reload failed, keeping current data"),
+    }
+}
+
+/// Reloads the aircraft registry, fuel policy and templates on SIGHUP.
+#[cfg(unix)]
+fn spawn_reload_on_hangup(shared: SharedRuntime, config: Config) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!(error = %e, "unable to listen for SIGHUP, reload on signal disabled");
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            reload(&shared, &config, "SIGHUP");
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_reload_on_hangup(_: SharedRuntime, _: Config) {}
+
+/// Polls the aircraft file and template directory, reloading when one of them changed.
+fn spawn_reload_on_change(shared: SharedRuntime, config: Config, interval: Duration) {
+    tokio::spawn(async move {
+        let mut last_modified = runtime::last_modified(&config);
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            let modified = runtime::last_modified(&config);
+            if modified != last_modified {
+                last_modified = modified;
+                reload(&shared, &config, "file change");
+            }
+        }
+    });
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let mut tera = Tera::default();
-    for file in Templates::iter() {
-        if let Some(content) = Templates::get(file.as_ref()) {
-            let content_str = std::str::from_utf8(&content.data).unwrap();
-            tera.add_raw_template(file.as_ref(), content_str).unwrap();
+    init_tracing();
+
+    let config = Config::load().map_err(io::Error::other)?;
+
+    if config.self_check {
+        let sweep = performance_check::sweep(&Grid::default());
+        for violation in &sweep.violations {
+            println!("{}", violation);
         }
+        println!(
+            "checked {} points, found {} violations",
+            sweep.points,
+            sweep.violations.len()
+        );
+        std::process::exit(if sweep.violations.is_empty() { 0 } else { 1 });
+    }
+
+    tracing::info!(config = ?config, "resolved configuration");
+
+    let shared = SharedRuntime::new(Runtime::load(&config).map_err(io::Error::other)?);
+    let storage = Storage::new(config.storage_dir.clone());
+    let performance_check = web::Data::new(PerformanceCheck::spawn(config.strict));
+
+    spawn_reload_on_hangup(shared.clone(), config.clone());
+    if let Some(interval) = config.reload_interval {
+        spawn_reload_on_change(shared.clone(), config.clone(), interval);
     }
 
-    let tera_clone = tera.clone();
+    let shared_clone = shared.clone();
+    let storage_clone = storage.clone();
+    let performance_check_clone = performance_check.clone();
 
     let main_server = HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(tera_clone.clone()))
+            .wrap_fn(|req, srv| {
+                let route = req
+                    .match_pattern()
+                    .unwrap_or_else(|| "unmatched".to_string());
+                let method = req.method().to_string();
+                let start = Instant::now();
+                let response = srv.call(req);
+
+                async move {
+                    // Errors are turned into a response further up, count them with their status.
+                    let response = response.await;
+                    let status = match &response {
+                        Ok(r) => r.status(),
+                        Err(e) => e.as_response_error().status_code(),
+                    };
+                    metrics::observe_request(&route, &method, status.as_u16(), start.elapsed());
+                    response
+                }
+            })
+            .wrap(TracingLogger::default())
+            .app_data(web::Data::new(shared_clone.clone()))
+            .app_data(web::Data::new(storage_clone.clone()))
+            .app_data(performance_check_clone.clone())
             .route("/assets/{filename:.*}", web::get().to(serve_asset))
+            .route("/sw.js", web::get().to(serve_service_worker))
             .configure(routes::init)
     })
-    .bind("0.0.0.0:80")?;
+    .shutdown_timeout(config.shutdown_timeout)
+    .bind(&config.listen)?;
 
     // Health check server
-    let health_server = HttpServer::new(|| {
+    let metrics_enabled = config.enabled(Feature::Metrics);
+    let health_server = HttpServer::new(move || {
         App::new()
-            .route("/healthz", web::get().to(health_check))
+            .app_data(web::Data::new(shared.clone()))
+            .app_data(web::Data::new(storage.clone()))
+            .app_data(performance_check.clone())
+            .route("/healthz", web::get().to(health::health_check))
+            .route("/readyz", web::get().to(health::readiness_check))
+            .configure(|cfg| {
+                if metrics_enabled {
+                    cfg.route("/metrics", web::get().to(metrics::metrics));
+                }
+            })
     })
-    .bind("0.0.0.0:8081")?; // Different port for health checks
+    .shutdown_timeout(config.shutdown_timeout)
+    .bind(&config.health_listen)?; // Different port for health checks
+
+    let (main_server, health_server) = match config.workers {
+        Some(workers) => (main_server.workers(workers), health_server.workers(workers)),
+        None => (main_server, health_server),
+    };
 
     tokio::try_join!(main_server.run(), health_server.run())?;
     Ok(())
@@ -0,0 +1,87 @@
+use std::{sync::LazyLock, time::Duration};
+
+use actix_web::{HttpResponse, Responder};
+use prometheus::{
+    core::Collector, histogram_opts, opts, Encoder, HistogramVec, IntCounter, IntCounterVec,
+    Registry, TextEncoder,
+};
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+pub static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            opts!("http_requests_total", "Number of handled HTTP requests"),
+            &["route", "method", "status"],
+        )
+        .unwrap(),
+    )
+});
+
+pub static HTTP_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            histogram_opts!(
+                "http_request_duration_seconds",
+                "Latency of handled HTTP requests"
+            ),
+            &["route", "method"],
+        )
+        .unwrap(),
+    )
+});
+
+pub static RENDER_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            opts!(
+                "template_render_errors_total",
+                "Number of failed template renders"
+            ),
+            &["template"],
+        )
+        .unwrap(),
+    )
+});
+
+pub static OUT_OF_LIMITS_LOADSHEETS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register(
+        IntCounter::new(
+            "loadsheets_out_of_limits_total",
+            "Number of printed loadsheets outside the weight and balance limits",
+        )
+        .unwrap(),
+    )
+});
+
+fn register<C: Collector + Clone + 'static>(collector: C) -> C {
+    REGISTRY.register(Box::new(collector.clone())).unwrap();
+    collector
+}
+
+pub fn observe_request(route: &str, method: &str, status: u16, elapsed: Duration) {
+    HTTP_REQUESTS
+        .with_label_values(&[route, method, &status.to_string()])
+        .inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[route, method])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub async fn metrics() -> impl Responder {
+    // Make sure all metrics are exported, even before their first observation.
+    LazyLock::force(&HTTP_REQUESTS);
+    LazyLock::force(&HTTP_REQUEST_DURATION);
+    LazyLock::force(&RENDER_ERRORS);
+    LazyLock::force(&OUT_OF_LIMITS_LOADSHEETS);
+
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+
+    match encoder.encode(&REGISTRY.gather(), &mut buffer) {
+        Ok(_) => HttpResponse::Ok()
+            .content_type(encoder.format_type())
+            .body(buffer),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
@@ -0,0 +1,200 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use airplane::{
+    types::{FuelType, VolumeType},
+    weight_and_balance::{Airplane, LeverArm},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::plane;
+
+use super::{aircraft::Weighing, signoff::SignOff, state::ApplicationState};
+
+pub const LOADSHEET_VERSION: u32 = 1;
+
+/// Canonical record of a finalized loadsheet. Masses are in kilograms, lever arms and distances
+/// in meters and durations in minutes, regardless of the units selected by the user.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Loadsheet {
+    pub version: u32,
+    pub issued_at: u64,
+    pub reference: Option<String>,
+    pub sign_off: Option<SignOff>,
+    pub state: LoadsheetState,
+    pub results: LoadsheetResults,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoadsheetState {
+    pub units: String,
+    pub callsign: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aircraft_type: Option<String>,
+    /// Weighing report the empty mass was taken from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weighing_reference: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weighing_date: Option<String>,
+    pub loads: Vec<LoadsheetItem>,
+    pub oat: f64,
+    pub pressure_altitude: f64,
+    pub wind: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gust: Option<f64>,
+    /// Conditions of the landing performance, absent when they are those of the departure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub landing_oat: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub landing_pressure_altitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub landing_wind: Option<f64>,
+    pub fuel_type: String,
+    pub fuel_unit: String,
+    pub fuel_max: bool,
+    pub trip_minutes: u64,
+    pub alternate_minutes: u64,
+    /// Last waypoint of a planned route and the selected alternate aerodrome.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoadsheetItem {
+    pub name: String,
+    pub mass: f64,
+    pub lever_arm: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoadsheetResults {
+    pub takeoff_mass: f64,
+    pub takeoff_cg: f64,
+    pub landing_mass: f64,
+    pub landing_cg: f64,
+    pub within_limits: bool,
+    pub fuel_sufficient: bool,
+    /// Performance distances, absent for aircraft types without a performance model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub torr: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub todr: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lgrr: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ldr: Option<f64>,
+    /// Landing distances at the alternate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate_lgrr: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate_ldr: Option<f64>,
+}
+
+/// Performance distances in meters.
+pub struct Performance {
+    pub torr: f64,
+    pub todr: f64,
+    pub lgrr: f64,
+    pub ldr: f64,
+    /// Landing ground roll and landing distance at the alternate.
+    pub alternate: Option<(f64, f64)>,
+}
+
+impl Loadsheet {
+    pub fn new(
+        app_state: &ApplicationState,
+        plane: &Airplane,
+        weighing: &Weighing,
+        performance: Option<Performance>,
+        fuel_sufficient: bool,
+    ) -> Loadsheet {
+        let loads = plane
+            .moments()
+            .iter()
+            .map(|m| {
+                let LeverArm::Meter(arm) = m.lever_arm();
+                LoadsheetItem {
+                    name: m.name().to_string(),
+                    mass: m.mass().kilo(),
+                    lever_arm: *arm,
+                }
+            })
+            .collect();
+
+        let takeoff_mass = plane.total_mass().kilo();
+        let landing_mass = plane.total_mass_landing().kilo();
+        let landing = |value: Option<f64>| value.filter(|_| app_state.has_landing_conditions());
+
+        Loadsheet {
+            version: LOADSHEET_VERSION,
+            issued_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            reference: None,
+            sign_off: None,
+            state: LoadsheetState {
+                units: app_state.unit_system.as_str().to_string(),
+                callsign: app_state.callsign.clone().unwrap_or_default(),
+                aircraft_type: Some(app_state.aircraft_type.id.clone()),
+                weighing_reference: weighing.reference.clone(),
+                weighing_date: weighing.date.clone(),
+                loads,
+                oat: app_state.oat.unwrap_or_default(),
+                pressure_altitude: app_state.pressure_altitude.unwrap_or_default(),
+                wind: app_state.wind.unwrap_or_default(),
+                gust: app_state.gust,
+                landing_oat: landing(app_state.landing_oat()),
+                landing_pressure_altitude: landing(app_state.landing_pressure_altitude()),
+                landing_wind: landing(app_state.landing_performance_wind()),
+                fuel_type: match app_state.fuel_type {
+                    Some(FuelType::Avgas) => "avgas".to_string(),
+                    Some(FuelType::Mogas) => "mogas".to_string(),
+                    None => String::new(),
+                },
+                fuel_unit: match app_state.fuel_unit {
+                    Some(VolumeType::Liter) => "liter".to_string(),
+                    Some(VolumeType::Gallon) => "gallon".to_string(),
+                    None => String::new(),
+                },
+                fuel_max: app_state.fuel_max.unwrap_or_default(),
+                trip_minutes: app_state
+                    .trip_duration
+                    .map(|d| d.as_secs() / 60)
+                    .unwrap_or_default(),
+                alternate_minutes: app_state
+                    .alternate_duration
+                    .map(|d| d.as_secs() / 60)
+                    .unwrap_or_default(),
+                destination: app_state.destination().map(str::to_uppercase),
+                alternate: app_state.alternate.clone(),
+            },
+            results: LoadsheetResults {
+                takeoff_mass,
+                takeoff_cg: plane::total_moment(plane) / takeoff_mass,
+                landing_mass,
+                landing_cg: plane::total_moment_landing(plane, &app_state.aircraft_type.fuel)
+                    / landing_mass,
+                within_limits: plane::within_limits(plane, &app_state.aircraft_type.fuel),
+                fuel_sufficient,
+                torr: performance.as_ref().map(|p| p.torr),
+                todr: performance.as_ref().map(|p| p.todr),
+                lgrr: performance.as_ref().map(|p| p.lgrr),
+                ldr: performance.as_ref().map(|p| p.ldr),
+                alternate_lgrr: performance
+                    .as_ref()
+                    .and_then(|p| p.alternate)
+                    .map(|(lgrr, _)| lgrr),
+                alternate_ldr: performance
+                    .as_ref()
+                    .and_then(|p| p.alternate)
+                    .map(|(_, ldr)| ldr),
+            },
+        }
+    }
+}
@@ -1,2 +1,7 @@
+pub use acm_weight_and_balance::aircraft;
+
+pub mod loadsheet;
 pub mod query_params;
+pub mod signoff;
 pub mod state;
+pub mod state_token;
@@ -2,43 +2,87 @@ use serde::Deserialize;
 
 #[derive(Deserialize)]
 pub struct IndexQueryParams {
+    pub state: Option<String>,
+    pub units: Option<String>,
+    pub previous_units: Option<String>,
     pub callsign: Option<String>,
-    pub pilot: Option<f64>,
+    /// Mass on each loading station, in the order of the stations of the type, and the
+    /// position of each adjustable seat, both separated by commas. Empty stations are left empty.
+    pub loads: Option<String>,
+    pub seats: Option<String>,
+    /// Pilot, passenger and baggage of links and state tokens from before the loading stations,
+    /// used for the first three stations when there are no loads.
+    pub pilot: Option<String>,
     pub pilot_seat: Option<String>,
     pub passenger: Option<String>,
     pub passenger_seat: Option<String>,
     pub baggage: Option<String>,
+    /// Identifiers of the selected optional equipment, separated by commas.
+    pub equipment: Option<String>,
     pub oat: Option<f64>,
     pub pressure_altitude: Option<f64>,
     pub wind: Option<f64>,
     pub wind_direction: Option<String>,
+    pub gust: Option<String>,
+    /// Conditions at the destination, left empty the departure conditions apply. The elevation
+    /// is in feet and the QNH in hectopascal.
+    pub landing_elevation: Option<String>,
+    pub landing_qnh: Option<String>,
+    pub landing_oat: Option<String>,
+    pub landing_wind: Option<String>,
+    pub landing_wind_direction: Option<String>,
     pub fuel_type: Option<String>,
     pub fuel_unit: Option<String>,
     pub fuel_extra: Option<String>,
     pub fuel_max: Option<String>,
+    /// Fuel on board before refuelling, in the selected fuel unit.
+    pub fuel_before: Option<String>,
     pub trip_duration: Option<String>,
+    /// Waypoints of the route separated by spaces, the trip duration is then planned from the
+    /// route instead of entered.
+    pub route: Option<String>,
+    /// True airspeed in knots and the wind along the route, from degrees true in knots.
+    pub route_tas: Option<String>,
+    pub route_wind_direction: Option<String>,
+    pub route_wind_speed: Option<String>,
+    /// Airport code of the alternate, the diversion from the destination of the route then
+    /// gives the alternate duration.
+    pub alternate: Option<String>,
     pub alternate_duration: Option<String>,
     pub reference: Option<String>,
+    pub pic_name: Option<String>,
+    pub pic_licence: Option<String>,
+    pub flight_date: Option<String>,
+    pub declaration: Option<String>,
+    pub override_reason: Option<String>,
     pub submit: Option<String>,
 }
 
-#[derive(Deserialize)]
-pub struct PerfQueryParams {
-    pub oat: f64,
-    pub pressure_altitude: f64,
-    pub mtow: f64,
-    pub wind: f64,
-    pub wind_direction: String,
-}
+pub use acm_weight_and_balance::performance::PerfQueryParams;
 
+/// Aircraft type of a performance chart, links without one are to the charts of the type of
+/// registries that predate aircraft types. The units are those of the description of the chart.
 #[derive(Deserialize)]
-pub struct FuelOptionQueryParams {
-    pub fuel_max: Option<String>,
-    pub fuel_type: Option<String>,
+pub struct ChartQueryParams {
+    #[serde(rename = "type")]
+    pub aircraft_type: Option<String>,
+    pub units: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct WindOptionQueryParams {
     pub wind: Option<f64>,
     pub wind_direction: Option<String>,
+    pub gust: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyQueryParams {
+    pub payload: Option<String>,
+}
+
+/// Theme of a chart, links without one use the theme of the cookie.
+#[derive(Deserialize)]
+pub struct ThemeQueryParams {
+    pub theme: Option<String>,
 }
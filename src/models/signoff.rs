@@ -0,0 +1,98 @@
+use acm_weight_and_balance::date::parse_date;
+use serde::{Deserialize, Serialize};
+
+use super::{loadsheet::Loadsheet, query_params::IndexQueryParams};
+
+const MAX_FIELD_LENGTH: usize = 100;
+
+/// Sign-off by the pilot in command, required before a loadsheet can be exported.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignOff {
+    pub pic_name: String,
+    pub pic_licence: String,
+    pub flight_date: String,
+    pub declaration: bool,
+    pub override_reason: Option<String>,
+}
+
+impl SignOff {
+    pub fn from_query_params(params: &IndexQueryParams) -> SignOff {
+        SignOff {
+            pic_name: trimmed(&params.pic_name),
+            pic_licence: trimmed(&params.pic_licence),
+            flight_date: trimmed(&params.flight_date),
+            declaration: params.declaration.as_deref() == Some("true"),
+            override_reason: Some(trimmed(&params.override_reason)).filter(|r| !r.is_empty()),
+        }
+    }
+
+    pub fn apply(&self, ctx: &mut tera::Context) {
+        ctx.insert("pic_name", &self.pic_name);
+        ctx.insert("pic_licence", &self.pic_licence);
+        ctx.insert("flight_date", &self.flight_date);
+        ctx.insert("declaration", &self.declaration);
+        if let Some(reason) = &self.override_reason {
+            ctx.insert("override_reason", reason);
+        }
+    }
+
+    /// Validates the sign-off against the calculated loadsheet and returns the problems found,
+    /// an empty list means the loadsheet may be exported.
+    pub fn validate(&self, loadsheet: &Loadsheet) -> Vec<String> {
+        let mut errors = vec![];
+
+        if self.pic_name.is_empty() {
+            errors.push("Vul de naam van de gezagvoerder in.".to_string());
+        } else if self.pic_name.chars().count() > MAX_FIELD_LENGTH {
+            errors.push("De naam van de gezagvoerder is te lang.".to_string());
+        }
+
+        if self.pic_licence.is_empty() {
+            errors.push("Vul het brevetnummer van de gezagvoerder in.".to_string());
+        } else if self.pic_licence.chars().count() > MAX_FIELD_LENGTH
+            || !self
+                .pic_licence
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || " .-/".contains(c))
+        {
+            errors.push("Het brevetnummer bevat ongeldige tekens.".to_string());
+        }
+
+        if parse_date(&self.flight_date).is_none() {
+            errors.push("Vul een geldige vluchtdatum in.".to_string());
+        }
+
+        if !self.declaration {
+            errors.push("Bevestig dat de belading is gecontroleerd.".to_string());
+        }
+
+        // Types without a performance model have no take-off and landing distances to sign for.
+        if loadsheet.results.torr.is_none() {
+            errors.push(
+                "Voor dit vliegtuigtype zijn geen prestatiegegevens beschikbaar, er kan geen loadsheet worden opgeslagen."
+                    .to_string(),
+            );
+        }
+
+        let negative = !loadsheet.results.within_limits || !loadsheet.results.fuel_sufficient;
+        match &self.override_reason {
+            Some(reason) if reason.chars().count() > MAX_FIELD_LENGTH * 5 => {
+                errors.push("De reden voor afwijking is te lang.".to_string());
+            }
+            None if negative => {
+                errors.push(
+                    "Gewicht en balans of brandstof voldoet niet, geef een reden op om toch op te slaan."
+                        .to_string(),
+                );
+            }
+            _ => (),
+        }
+
+        errors
+    }
+}
+
+fn trimmed(value: &Option<String>) -> String {
+    value.as_deref().unwrap_or_default().trim().to_string()
+}
@@ -1,68 +1,157 @@
-use std::{num::ParseIntError, time::Duration};
+use std::{collections::HashMap, iter, time::Duration};
 
+use acm_weight_and_balance::{
+    date::{self, Date},
+    fuel, performance, plane,
+    route::Wind,
+};
 use airplane::{
     types::{FuelType, VolumeType},
-    weight_and_balance::{LeverArm, Mass, Moment, Volume},
+    weight_and_balance::{Mass, Moment, Volume},
 };
 
-use super::query_params::IndexQueryParams;
+use crate::utils::units::{round, UnitSystem};
+
+use super::{
+    aircraft::{AircraftRegistry, AircraftType, Equipment, Tank},
+    query_params::IndexQueryParams,
+    state_token::StateToken,
+};
 
 #[derive(Clone)]
 pub struct ApplicationState {
+    pub unit_system: UnitSystem,
     pub callsign: Option<String>,
-    pub pilot_moment: Option<Moment>,
-    pub passenger_moment: Option<Moment>,
-    pub baggage_moment: Option<Moment>,
+    /// Type of the selected aircraft, or of the first aircraft while none is selected.
+    pub aircraft_type: AircraftType,
+    /// Mass in kilograms on each loading station of the type, in the order of the stations,
+    /// and the position of each adjustable seat.
+    pub loads: Vec<Option<f64>>,
+    pub seats: Vec<String>,
+    /// Optional equipment of the selected aircraft, and the items selected for the flight.
+    pub aircraft_equipment: Vec<Equipment>,
+    pub equipment: Vec<Equipment>,
     pub oat: Option<f64>,
     pub pressure_altitude: Option<f64>,
     pub wind: Option<f64>,
+    /// Gust speed in knots, in the direction of the wind.
+    pub gust: Option<f64>,
+    /// Conditions at the destination, where they are left out the departure conditions apply.
+    /// The elevation is in feet, the QNH in hectopascal and the wind in knots, negative for a
+    /// tailwind.
+    pub landing_elevation: Option<f64>,
+    pub landing_qnh: Option<f64>,
+    pub landing_oat: Option<f64>,
+    pub landing_wind: Option<f64>,
     pub fuel_type: Option<airplane::types::FuelType>,
     pub fuel_unit: Option<airplane::types::VolumeType>,
-    pub fuel_extra: Option<Mass>,
+    /// Fuel on board per tank, in the order of the tanks of the type.
+    pub fuel_extra: Option<Vec<Mass>>,
+    /// Whether a quantity of the fuel on board was not a number, there is no fuel on board then.
+    pub fuel_extra_invalid: bool,
     pub fuel_max: Option<bool>,
+    /// Usable fuel in liters of the single tank of types without tanks.
+    pub max_fuel: f64,
+    /// Fuel on board before refuelling, in the selected fuel unit.
+    pub fuel_before: Option<f64>,
     pub trip_duration: Option<Duration>,
+    /// Waypoints of a planned route, the true airspeed in knots and the wind estimate.
+    pub route: Option<String>,
+    pub route_tas: Option<f64>,
+    pub route_wind: Wind,
+    /// Airport code of the alternate aerodrome.
+    pub alternate: Option<String>,
     pub alternate_duration: Option<Duration>,
+    /// Flight date of the sign-off, calculations before the sign-off are for the current date.
+    pub flight_date: Option<Date>,
 }
 
 impl ApplicationState {
     pub fn apply(&self, step: &str, ctx: &mut tera::Context) {
         ctx.insert("step", step);
+        ctx.insert("units", self.unit_system.as_str());
+        ctx.insert("mass_unit", self.unit_system.mass_unit());
+        ctx.insert("distance_unit", self.unit_system.distance_unit());
 
         if self.callsign.is_some() {
             ctx.insert("callsign", &self.callsign.clone().unwrap());
         }
 
-        if let Some(pm) = &self.pilot_moment {
-            let LeverArm::Meter(arm) = pm.lever_arm();
-            let arm_str = if *arm == 5.0 / 11.0 {
-                "f"
-            } else if *arm == 13.0 / 22.0 {
-                "b"
-            } else {
-                "m"
-            };
-
-            ctx.insert("pilot", &pm.mass().kilo());
-            ctx.insert("pilot_seat", &arm_str);
-        }
-
-        if let Some(pm) = &self.passenger_moment {
-            let LeverArm::Meter(arm) = pm.lever_arm();
-            let arm_str = if *arm == 5.0 / 11.0 {
-                "f"
-            } else if *arm == 13.0 / 22.0 {
-                "b"
-            } else {
-                "m"
-            };
-
-            ctx.insert("passenger", &pm.mass().kilo());
-            ctx.insert("passenger_seat", &arm_str);
+        let display = |kilo: f64| round(self.unit_system.mass_from_kilo(kilo), 2);
+        ctx.insert(
+            "stations",
+            &self
+                .aircraft_type
+                .stations
+                .iter()
+                .enumerate()
+                .map(|(i, station)| {
+                    serde_json::json!({
+                        "name": station.name,
+                        "adjustable": station.adjustable(),
+                        "max": station
+                            .max_mass
+                            .map(|m| round(self.unit_system.mass_from_kilo(m), 0)),
+                        "mass": self.loads.get(i).copied().flatten().map(display),
+                        "seat": self.seats.get(i),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        );
+        ctx.insert(
+            "loads",
+            &self
+                .aircraft_type
+                .stations
+                .iter()
+                .enumerate()
+                .map(|(i, _)| {
+                    self.loads
+                        .get(i)
+                        .copied()
+                        .flatten()
+                        .map(|m| display(m).to_string())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        ctx.insert("seats", &self.seats.join(","));
+
+        let unusable: f64 = self
+            .aircraft_type
+            .fuel
+            .tanks()
+            .iter()
+            .filter_map(|t| t.unusable)
+            .sum();
+        if unusable > 0.0 {
+            ctx.insert("fuel_unusable", &unusable.to_string().replace('.', ","));
         }
 
-        if let Some(bm) = &self.baggage_moment {
-            ctx.insert("baggage", &bm.mass().kilo());
-        }
+        ctx.insert(
+            "aircraft_equipment",
+            &self
+                .aircraft_equipment
+                .iter()
+                .map(|item| {
+                    serde_json::json!({
+                        "id": item.id,
+                        "name": item.name,
+                        "mass": round(self.unit_system.mass_from_kilo(item.mass), 1),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        );
+        ctx.insert(
+            "equipment",
+            &self
+                .equipment
+                .iter()
+                .map(|item| item.id.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
 
         if self.oat.is_some() {
             ctx.insert("oat", &self.oat);
@@ -82,6 +171,30 @@ impl ApplicationState {
             }
         }
 
+        if let Some(g) = self.gust {
+            ctx.insert("gust", &g);
+        }
+
+        if let Some(elevation) = self.landing_elevation {
+            ctx.insert("landing_elevation", &elevation);
+        }
+
+        if let Some(qnh) = self.landing_qnh {
+            ctx.insert("landing_qnh", &qnh);
+        }
+
+        if let Some(oat) = self.landing_oat {
+            ctx.insert("landing_oat", &oat);
+        }
+
+        if let Some(wind) = self.landing_wind {
+            ctx.insert("landing_wind", &wind.abs());
+            ctx.insert(
+                "landing_wind_direction",
+                if wind >= 0.0 { "headwind" } else { "tailwind" },
+            );
+        }
+
         if let Some(ft) = &self.fuel_type {
             match ft {
                 FuelType::Avgas => ctx.insert("fuel_type", "avgas"),
@@ -102,103 +215,516 @@ impl ApplicationState {
             }
         }
 
-        if let Some(fe) = &self.fuel_extra {
-            match fe {
-                Mass::Mogas(v) | Mass::Avgas(v) => {
-                    match v {
-                        Volume::Liter(q) | Volume::Gallon(q) => ctx.insert("fuel_extra", &q),
-                    }
-                },
-                _ => ()
-            }
+        let quantities: Vec<f64> = self
+            .fuel_extra
+            .iter()
+            .flatten()
+            .filter_map(|fe| match fe {
+                Mass::Mogas(Volume::Liter(q) | Volume::Gallon(q))
+                | Mass::Avgas(Volume::Liter(q) | Volume::Gallon(q)) => Some(*q),
+                _ => None,
+            })
+            .collect();
+
+        if !quantities.is_empty() {
+            ctx.insert(
+                "fuel_extra",
+                &quantities
+                    .iter()
+                    .map(f64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+
+        // Types with several tanks get a fuel quantity per tank.
+        let tanks = self.aircraft_type.fuel.tanks();
+        ctx.insert("fuel_volume_unit", self.fuel_volume_unit());
+        ctx.insert("fuel_capacity", &self.tank_capacity(&tanks[0]));
+        if tanks.len() > 1 {
+            ctx.insert(
+                "fuel_tanks",
+                &tanks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, tank)| {
+                        serde_json::json!({
+                            "name": tank.name,
+                            "capacity": self.tank_capacity(tank),
+                            "quantity": quantities.get(i),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        ctx.insert("fuel_grades", &self.aircraft_type.fuel.grades);
+
+        if let Some(fb) = self.fuel_before {
+            ctx.insert("fuel_before", &fb);
         }
 
         if let Some(d) = &self.trip_duration {
             ctx.insert("trip_duration", &duration_to_hh_mm(d));
         }
 
+        if let Some(route) = &self.route {
+            ctx.insert("route", route);
+            if let Some(tas) = self.route_tas {
+                ctx.insert("route_tas", &tas);
+            }
+            ctx.insert("route_wind_direction", &self.route_wind.direction);
+            ctx.insert("route_wind_speed", &self.route_wind.speed);
+        }
+
+        if let Some(alternate) = &self.alternate {
+            ctx.insert("alternate", alternate);
+        }
+
         if let Some(d) = &self.alternate_duration {
             ctx.insert("alternate_duration", &duration_to_hh_mm(d));
         }
+
+        ctx.insert("state", &StateToken::from_state(self, step).encode());
     }
 
-    pub fn from_query_params(params: IndexQueryParams) -> (ApplicationState, IndexQueryParams) {
-        let mut state = ApplicationState {
-            callsign: params.callsign.clone(),
-            pilot_moment: match params.pilot {
-                Some(w) => match &params.pilot_seat {
-                    Some(pos) => {
-                        let name = "Pilot".to_string();
-                        match pos.as_str() {
-                            "f" => Some(Moment::new(
-                                name,
-                                LeverArm::Meter(5.0 / 11.0),
-                                Mass::Kilo(w),
-                            )),
-                            "b" => Some(Moment::new(
-                                name,
-                                LeverArm::Meter(13.0 / 22.0),
-                                Mass::Kilo(w),
-                            )),
-                            _ => Some(Moment::new(
-                                name,
-                                LeverArm::Meter(23.0 / 44.0),
-                                Mass::Kilo(w),
-                            )),
-                        }
-                    }
-                    None => None,
-                },
-                None => None,
-            },
-            passenger_moment: match &params.passenger {
-                Some(ws) => {
-                    let w: f64 = if ws.is_empty() {
-                        0.0
-                    } else {
-                        ws.parse().expect("passenger weigth must be a number")
-                    };
+    /// Encodes the complete state as a token, used to link to the charts.
+    pub fn to_token(&self) -> String {
+        StateToken::from_state(self, "token").encode()
+    }
 
-                    let name = "Passenger".to_string();
-
-                    match &params.passenger_seat {
-                        Some(pos) => match pos.as_str() {
-                            "f" => Some(Moment::new(
-                                name,
-                                LeverArm::Meter(5.0 / 11.0),
-                                Mass::Kilo(w),
-                            )),
-                            "b" => Some(Moment::new(
-                                name,
-                                LeverArm::Meter(13.0 / 22.0),
-                                Mass::Kilo(w),
-                            )),
-                            _ => Some(Moment::new(
-                                name,
-                                LeverArm::Meter(23.0 / 44.0),
-                                Mass::Kilo(w),
-                            )),
-                        },
-                        None => None,
-                    }
-                }
-                None => None,
-            },
-            baggage_moment: match &params.baggage {
-                Some(ws) => {
-                    let w: f64 = if ws.is_empty() {
-                        0.0
-                    } else {
-                        ws.parse().expect("baggage weigth must be a number")
-                    };
-                    Some(Moment::new(
-                        "Bagage".to_string(),
-                        LeverArm::Meter(1.3),
-                        Mass::Kilo(w),
-                    ))
+    /// Values of the state as the form fields submit them, without the rounding for display.
+    pub fn form_values(&self) -> HashMap<&'static str, String> {
+        let mut values = HashMap::new();
+
+        values.insert("units", self.unit_system.as_str().to_string());
+
+        if let Some(callsign) = &self.callsign {
+            values.insert("callsign", callsign.clone());
+        }
+
+        if self.loads.iter().any(Option::is_some) {
+            values.insert(
+                "loads",
+                self.loads
+                    .iter()
+                    .map(|load| match load {
+                        Some(m) => self.unit_system.mass_from_kilo(*m).to_string(),
+                        None => String::new(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+            values.insert("seats", self.seats.join(","));
+        }
+
+        if !self.equipment.is_empty() {
+            values.insert(
+                "equipment",
+                self.equipment
+                    .iter()
+                    .map(|item| item.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+
+        let wind_direction = |wind: f64| if wind >= 0.0 { "headwind" } else { "tailwind" };
+        for (field, value) in [
+            ("oat", self.oat),
+            ("pressure_altitude", self.pressure_altitude),
+            ("gust", self.gust),
+            ("landing_elevation", self.landing_elevation),
+            ("landing_qnh", self.landing_qnh),
+            ("landing_oat", self.landing_oat),
+            ("fuel_before", self.fuel_before),
+        ] {
+            if let Some(v) = value {
+                values.insert(field, v.to_string());
+            }
+        }
+
+        if let Some(wind) = self.wind {
+            values.insert("wind", wind.abs().to_string());
+            values.insert("wind_direction", wind_direction(wind).to_string());
+        }
+
+        if let Some(wind) = self.landing_wind {
+            values.insert("landing_wind", wind.abs().to_string());
+            values.insert("landing_wind_direction", wind_direction(wind).to_string());
+        }
+
+        if let Some(ft) = &self.fuel_type {
+            values.insert("fuel_type", fuel::grade(ft).to_string());
+        }
+
+        if let Some(fu) = &self.fuel_unit {
+            let unit = match fu {
+                VolumeType::Liter => "liter",
+                VolumeType::Gallon => "gallon",
+            };
+            values.insert("fuel_unit", unit.to_string());
+        }
+
+        if self.fuel_max == Some(true) {
+            values.insert("fuel_max", "max".to_string());
+        }
+
+        let quantities: Vec<String> = self
+            .fuel_extra
+            .iter()
+            .flatten()
+            .filter_map(|fe| match fe {
+                Mass::Mogas(Volume::Liter(q) | Volume::Gallon(q))
+                | Mass::Avgas(Volume::Liter(q) | Volume::Gallon(q)) => Some(q.to_string()),
+                _ => None,
+            })
+            .collect();
+        if !quantities.is_empty() {
+            values.insert("fuel_extra", quantities.join(","));
+        }
+
+        if let Some(d) = &self.trip_duration {
+            values.insert("trip_duration", duration_to_hh_mm(d));
+        }
+
+        if let Some(route) = &self.route {
+            values.insert("route", route.clone());
+            if let Some(tas) = self.route_tas {
+                values.insert("route_tas", tas.to_string());
+            }
+            values.insert(
+                "route_wind_direction",
+                self.route_wind.direction.to_string(),
+            );
+            values.insert("route_wind_speed", self.route_wind.speed.to_string());
+        }
+
+        if let Some(alternate) = &self.alternate {
+            values.insert("alternate", alternate.clone());
+        }
+
+        if let Some(d) = &self.alternate_duration {
+            values.insert("alternate_duration", duration_to_hh_mm(d));
+        }
+
+        values
+    }
+
+    /// Whether fuel is entered in gallons, when selected or by default with imperial units.
+    fn fuel_in_gallons(&self) -> bool {
+        match &self.fuel_unit {
+            Some(VolumeType::Gallon) => true,
+            Some(VolumeType::Liter) => false,
+            None => self.unit_system == UnitSystem::Imperial,
+        }
+    }
+
+    fn fuel_volume_unit(&self) -> &'static str {
+        if self.fuel_in_gallons() {
+            "gal"
+        } else {
+            "L"
+        }
+    }
+
+    /// Usable capacity of a tank in the fuel unit, rounded down so the entered maximum fits.
+    fn tank_capacity(&self, tank: &Tank) -> f64 {
+        let liters = tank.capacity.unwrap_or(self.max_fuel);
+        let capacity = if self.fuel_in_gallons() {
+            Volume::Liter(liters).to_gallon()
+        } else {
+            liters
+        };
+
+        (capacity * 10.0).floor() / 10.0
+    }
+
+    /// Checks the entered fuel on board of each tank against its usable capacity.
+    pub fn tank_errors(&self) -> Vec<String> {
+        if self.fuel_max == Some(true) {
+            return vec![];
+        }
+        if self.fuel_extra_invalid {
+            return vec!["Vul de brandstof aan boord in als een getal.".to_string()];
+        }
+
+        let tanks = self.aircraft_type.fuel.tanks();
+        self.fuel_extra
+            .iter()
+            .flatten()
+            .zip(&tanks)
+            .filter_map(|(mass, tank)| {
+                let liters = match mass {
+                    Mass::Avgas(v) | Mass::Mogas(v) => v.to_liter(),
+                    _ => return None,
+                };
+                let capacity = tank.capacity.unwrap_or(self.max_fuel);
+                let name = match tanks.len() {
+                    1 => "De tank".to_string(),
+                    _ => format!("Tank {}", tank.name),
+                };
+
+                (liters > capacity).then(|| {
+                    format!(
+                        "{} bevat maximaal {} {} bruikbare brandstof.",
+                        name,
+                        self.tank_capacity(tank).to_string().replace('.', ","),
+                        self.fuel_volume_unit()
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Moments of the loaded stations.
+    pub fn station_moments(&self) -> Vec<Moment> {
+        plane::station_moments(&self.aircraft_type.stations, &self.loads, &self.seats)
+    }
+
+    /// Last waypoint of the route.
+    pub fn destination(&self) -> Option<&str> {
+        self.route.as_deref()?.split_whitespace().last()
+    }
+
+    /// Wind used for the performance calculations, see [`performance::performance_wind`].
+    pub fn performance_wind(&self) -> Option<f64> {
+        self.wind
+            .map(|w| performance::performance_wind(w, self.gust))
+    }
+
+    /// Whether any of the landing conditions is entered.
+    pub fn has_landing_conditions(&self) -> bool {
+        self.landing_elevation.is_some()
+            || self.landing_oat.is_some()
+            || self.landing_wind.is_some()
+    }
+
+    /// Pressure altitude at the destination in feet, from its elevation and QNH when entered.
+    pub fn landing_pressure_altitude(&self) -> Option<f64> {
+        match self.landing_elevation {
+            Some(elevation) => Some(performance::pressure_altitude(
+                elevation,
+                self.landing_qnh.unwrap_or(performance::STANDARD_QNH),
+            )),
+            None => self.pressure_altitude,
+        }
+    }
+
+    pub fn landing_oat(&self) -> Option<f64> {
+        self.landing_oat.or(self.oat)
+    }
+
+    /// Wind used for the landing performance. An entered landing wind has no gusts, without one
+    /// the departure wind applies.
+    pub fn landing_performance_wind(&self) -> Option<f64> {
+        self.landing_wind.or_else(|| self.performance_wind())
+    }
+
+    /// Checks the loads against the stations of the aircraft type, and the conditions against its
+    /// wind limits and the scales of its performance charts. Outside the charts no distances can
+    /// be given, so these errors cannot be overridden.
+    pub fn condition_errors(&self) -> Vec<String> {
+        let mut errors = vec![];
+        let wind_limits = &self.aircraft_type.wind_limits;
+        let scale = self
+            .aircraft_type
+            .performance_model()
+            .map(|model| model.scale());
+
+        if self.loads.first().copied().flatten().is_none() {
+            errors.push("Vul het gewicht van de piloot in.".to_string());
+        }
+
+        for (station, load) in self.aircraft_type.stations.iter().zip(&self.loads) {
+            if let (Some(max), Some(mass)) = (station.max_mass, load) {
+                if *mass > max {
+                    errors.push(format!(
+                        "{} mag maximaal {} {} zijn.",
+                        station.name,
+                        round(self.unit_system.mass_from_kilo(max), 0),
+                        self.unit_system.mass_unit()
+                    ));
                 }
-                None => None,
-            },
+            }
+        }
+
+        if let Some(scale) = &scale {
+            match self.oat {
+                Some(oat) if (scale.oat.0..=scale.oat.1).contains(&oat) => (),
+                _ => errors.push(format!(
+                    "De buitentemperatuur moet tussen {} en {} °C liggen.",
+                    scale.oat.0, scale.oat.1
+                )),
+            }
+
+            let (min_pa, max_pa) = scale.pressure_altitude;
+            match self.pressure_altitude {
+                Some(pa) if (min_pa..=max_pa).contains(&pa) => (),
+                _ => errors.push(format!(
+                    "De drukhoogte moet tussen {} en {} voet liggen.",
+                    min_pa, max_pa
+                )),
+            }
+        }
+
+        errors.extend(self.landing_condition_errors());
+
+        let Some(wind) = self.wind.filter(|w| w.is_finite()) else {
+            errors.push("Vul de wind in.".to_string());
+            return errors;
+        };
+
+        if let Some(gust) = self.gust {
+            if !gust.is_finite() || gust < wind.abs() {
+                errors.push("De windstoten moeten minstens zo sterk zijn als de wind.".to_string());
+            }
+        }
+
+        let wind = performance::performance_wind(wind, self.gust);
+        if let Some(max_headwind) = scale.map(|s| s.max_headwind).filter(|max| wind > *max) {
+            errors.push(format!(
+                "Een tegenwind van meer dan {} kt valt buiten de prestatiegrafieken.",
+                max_headwind
+            ));
+        } else if -wind > wind_limits.max_tailwind {
+            errors.push(format!(
+                "De rugwindcomponent van {} kt overschrijdt het maximum van {} kt uit het \
+                 vlieghandboek.",
+                -wind, wind_limits.max_tailwind
+            ));
+        }
+
+        errors
+    }
+
+    fn landing_condition_errors(&self) -> Vec<String> {
+        let mut errors = vec![];
+
+        if self.landing_qnh.is_some() && self.landing_elevation.is_none() {
+            errors.push("Vul bij de QNH ook de hoogte van de bestemming in.".to_string());
+        }
+
+        if self
+            .landing_qnh
+            .is_some_and(|qnh| !(900.0..=1100.0).contains(&qnh))
+        {
+            errors.push("De QNH moet tussen 900 en 1100 hPa liggen.".to_string());
+        }
+
+        if !self.has_landing_conditions() {
+            return errors;
+        }
+
+        if let Some(scale) = self.aircraft_type.performance_model().map(|m| m.scale()) {
+            if self
+                .landing_oat
+                .is_some_and(|oat| !(scale.oat.0..=scale.oat.1).contains(&oat))
+            {
+                errors.push(format!(
+                    "De buitentemperatuur bij de landing moet tussen {} en {} °C liggen.",
+                    scale.oat.0, scale.oat.1
+                ));
+            }
+
+            let (min_pa, max_pa) = scale.pressure_altitude;
+            let pressure_altitude = self.landing_elevation.and(self.landing_pressure_altitude());
+            if pressure_altitude.is_some_and(|pa| !(min_pa..=max_pa).contains(&pa)) {
+                errors.push(format!(
+                    "De drukhoogte van de bestemming moet tussen {} en {} voet liggen.",
+                    min_pa, max_pa
+                ));
+            }
+
+            if self.landing_wind.is_some_and(|w| w > scale.max_headwind) {
+                errors.push(format!(
+                    "Een tegenwind bij de landing van meer dan {} kt valt buiten de \
+                     prestatiegrafieken.",
+                    scale.max_headwind
+                ));
+            }
+        }
+
+        let max_tailwind = self.aircraft_type.wind_limits.max_tailwind;
+        if let Some(wind) = self.landing_wind.filter(|w| -w > max_tailwind) {
+            errors.push(format!(
+                "De rugwindcomponent bij de landing van {} kt overschrijdt het maximum van {} kt \
+                 uit het vlieghandboek.",
+                -wind, max_tailwind
+            ));
+        }
+
+        errors
+    }
+
+    pub fn from_query_params(
+        mut params: IndexQueryParams,
+        registry: &AircraftRegistry,
+    ) -> (ApplicationState, IndexQueryParams) {
+        // Explicitly submitted fields take precedence over the values in the state token, both
+        // may still have the pilot, passenger and baggage of older links.
+        legacy_loads(&mut params);
+        if let Some(token) = params.state.take() {
+            match StateToken::decode(&token) {
+                Ok(t) => t.merge_into(&mut params),
+                Err(e) => tracing::warn!(error = %e, "ignoring state token"),
+            }
+        }
+        legacy_loads(&mut params);
+
+        let unit_system = params
+            .units
+            .as_deref()
+            .and_then(UnitSystem::parse)
+            .unwrap_or_default();
+
+        let aircraft = params
+            .callsign
+            .as_deref()
+            .and_then(|c| registry.get(c))
+            .or(registry.aircraft.first())
+            .expect("registry should have aircraft");
+        let aircraft_type = registry.type_of(aircraft).clone();
+        let max_fuel = registry.fuel_policy(&aircraft_type).max_fuel;
+        let stations = &aircraft_type.stations;
+
+        // Items of another aircraft are left out when the selected aircraft changes.
+        let selected: Vec<&str> = params
+            .equipment
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .collect();
+        let equipment = aircraft
+            .equipment
+            .iter()
+            .filter(|item| selected.contains(&item.id.as_str()))
+            .cloned()
+            .collect();
+
+        let mut state = ApplicationState {
+            unit_system,
+            callsign: params.callsign.clone(),
+            loads: list(&params.loads)
+                .chain(iter::repeat(""))
+                .take(stations.len())
+                .map(|load| {
+                    load.parse()
+                        .ok()
+                        .filter(|m: &f64| m.is_finite() && *m >= 0.0)
+                        .map(|m| unit_system.mass_to_kilo(m))
+                })
+                .collect(),
+            seats: stations
+                .iter()
+                .zip(list(&params.seats).chain(iter::repeat("")))
+                .map(|(station, seat)| match station.adjustable() {
+                    true => seat.to_string(),
+                    false => String::new(),
+                })
+                .collect(),
+            aircraft_equipment: aircraft.equipment.clone(),
+            equipment,
             oat: params.oat,
             pressure_altitude: params.pressure_altitude,
             wind: match params.wind_direction.clone().unwrap_or_default().as_str() {
@@ -206,6 +732,21 @@ impl ApplicationState {
                 "tailwind" => params.wind.map(|w| -w),
                 _ => None,
             },
+            gust: params
+                .gust
+                .as_deref()
+                .map(str::trim)
+                .filter(|g| !g.is_empty())
+                .and_then(|g| g.parse().ok()),
+            landing_elevation: number(&params.landing_elevation),
+            landing_qnh: number(&params.landing_qnh),
+            landing_oat: number(&params.landing_oat),
+            landing_wind: number(&params.landing_wind).map(|w| {
+                match params.landing_wind_direction.as_deref() {
+                    Some("tailwind") => -w.abs(),
+                    _ => w.abs(),
+                }
+            }),
             fuel_type: match &params.fuel_type {
                 Some(t) => match t.as_str() {
                     "mogas" => Some(airplane::types::FuelType::Mogas),
@@ -223,38 +764,70 @@ impl ApplicationState {
                 None => None,
             },
             fuel_max: params.fuel_max.as_ref().map(|m| m == "max"),
+            max_fuel,
+            fuel_before: params
+                .fuel_before
+                .as_deref()
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .and_then(|f| f.parse().ok())
+                .filter(|f: &f64| f.is_finite() && *f >= 0.0),
             fuel_extra: None,
+            fuel_extra_invalid: false,
             trip_duration: None,
+            route: params
+                .route
+                .as_deref()
+                .map(|r| r.split_whitespace().collect::<Vec<&str>>().join(" "))
+                .filter(|r| !r.is_empty()),
+            route_tas: number(&params.route_tas),
+            route_wind: Wind {
+                direction: number(&params.route_wind_direction).unwrap_or_default(),
+                speed: number(&params.route_wind_speed).unwrap_or_default(),
+            },
+            alternate: params
+                .alternate
+                .as_deref()
+                .map(|a| a.trim().to_uppercase())
+                .filter(|a| !a.is_empty()),
             alternate_duration: None,
+            flight_date: params.flight_date.as_deref().and_then(date::parse_date),
+            aircraft_type,
         };
 
-        match &params.fuel_extra {
-            Some(fe) => {
-                let extra: f64 = fe.parse().unwrap_or_default();
-                match &state.fuel_unit {
-                    Some(fu) => match &state.fuel_type {
-                        Some(ft) => {
-                            let volume = match fu {
-                                VolumeType::Liter => Volume::Liter(extra),
-                                VolumeType::Gallon => Volume::Gallon(extra),
-                            };
-
-                            state.fuel_extra = Some(match ft {
-                                FuelType::Mogas => Mass::Mogas(volume),
-                                FuelType::Avgas => Mass::Avgas(volume),
-                            })
-                        }
-                        None => (),
-                    },
-                    None => (),
-                }
+        if let Some(fe) = &params.fuel_extra {
+            // One quantity per tank, separated by commas, a tank left empty has no fuel.
+            let quantities: Option<Vec<f64>> = fe
+                .split(',')
+                .map(|q| match q.trim() {
+                    "" => Some(0.0),
+                    q => q.parse().ok().filter(|v: &f64| v.is_finite() && *v >= 0.0),
+                })
+                .collect();
+            state.fuel_extra_invalid = quantities.is_none();
+
+            if let (Some(quantities), Some(fu), Some(ft)) =
+                (quantities, &state.fuel_unit, &state.fuel_type)
+            {
+                let mass = |extra: f64| {
+                    let volume = match fu {
+                        VolumeType::Liter => Volume::Liter(extra),
+                        VolumeType::Gallon => Volume::Gallon(extra),
+                    };
+
+                    match ft {
+                        FuelType::Mogas => Mass::Mogas(volume),
+                        FuelType::Avgas => Mass::Avgas(volume),
+                    }
+                };
+
+                state.fuel_extra = Some(quantities.into_iter().map(mass).collect());
             }
-            None => (),
         }
 
         match &params.trip_duration {
             Some(d) => {
-                if let Ok(d) = parse_time_to_duration(d.as_str()) {
+                if let Some(d) = parse_time_to_duration(d.as_str()) {
                     state.trip_duration = Some(d);
                 }
             }
@@ -263,7 +836,7 @@ impl ApplicationState {
 
         match &params.alternate_duration {
             Some(d) => {
-                if let Ok(d) = parse_time_to_duration(d.as_str()) {
+                if let Some(d) = parse_time_to_duration(d.as_str()) {
                     state.alternate_duration = Some(d);
                 }
             }
@@ -274,19 +847,58 @@ impl ApplicationState {
     }
 }
 
-fn parse_time_to_duration(time_str: &str) -> Result<Duration, ParseIntError> {
-    let parts: Vec<&str> = time_str.split(':').collect();
+/// Fills the loads and seats from the pilot, passenger and baggage of links from before the
+/// loading stations, they are the first three stations.
+fn legacy_loads(params: &mut IndexQueryParams) {
+    let legacy = params.pilot.is_some() || params.passenger.is_some() || params.baggage.is_some();
+    if params.loads.is_some() || !legacy {
+        return;
+    }
 
-    if parts.len() != 2 {
-        panic!("Verkeerd formaat, verwacht HH:mm");
+    params.loads = Some(
+        [
+            params.pilot.take(),
+            params.passenger.take(),
+            params.baggage.take(),
+        ]
+        .map(Option::unwrap_or_default)
+        .join(","),
+    );
+    if params.seats.is_none() {
+        params.seats = Some(
+            [params.pilot_seat.take(), params.passenger_seat.take(), None]
+                .map(Option::unwrap_or_default)
+                .join(","),
+        );
     }
+}
 
-    let hours: u64 = parts[0].parse()?;
-    let minutes: u64 = parts[1].parse()?;
+/// Values of an optional field separated by commas.
+fn list(value: &Option<String>) -> impl Iterator<Item = &str> {
+    value
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+}
+
+/// Parses an optional number field, left empty it has no value.
+fn number(value: &Option<String>) -> Option<f64> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .and_then(|v| v.parse().ok())
+        .filter(|v: &f64| v.is_finite())
+}
 
-    let total_seconds = (hours * 3600) + (minutes * 60);
+/// Parses a duration entered as `uu:mm`, with the minutes below 60.
+fn parse_time_to_duration(time_str: &str) -> Option<Duration> {
+    let (hours, minutes) = time_str.trim().split_once(':')?;
+    let hours: u64 = hours.parse().ok()?;
+    let minutes: u64 = minutes.parse().ok().filter(|m| *m < 60)?;
 
-    Ok(Duration::from_secs(total_seconds))
+    Some(Duration::from_secs(hours * 3600 + minutes * 60))
 }
 
 pub fn duration_to_hh_mm(duration: &Duration) -> String {
@@ -0,0 +1,172 @@
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use super::{query_params::IndexQueryParams, state::ApplicationState};
+
+const STATE_TOKEN_VERSION: char = '1';
+
+/// Largest decompressed token that is accepted, guards against decompression bombs.
+const MAX_DECODED_LENGTH: u64 = 16 * 1024;
+
+/// Fields edited on the input step.
+const INPUT_FIELDS: [&str; 15] = [
+    "units",
+    "callsign",
+    "loads",
+    "seats",
+    "equipment",
+    "oat",
+    "pressure_altitude",
+    "wind",
+    "wind_direction",
+    "gust",
+    "landing_elevation",
+    "landing_qnh",
+    "landing_oat",
+    "landing_wind",
+    "landing_wind_direction",
+];
+
+/// Fields edited on the fuel step.
+const FUEL_FIELDS: [&str; 12] = [
+    "fuel_type",
+    "fuel_unit",
+    "trip_duration",
+    "alternate_duration",
+    "fuel_max",
+    "fuel_extra",
+    "fuel_before",
+    "route",
+    "route_tas",
+    "route_wind_direction",
+    "route_wind_speed",
+    "alternate",
+];
+
+/// Wizard state carried between requests as a single query parameter.
+///
+/// The token holds the same values the form fields would submit, unrounded. It is encoded as a version
+/// character followed by the base64url encoded, deflate compressed JSON serialization.
+#[derive(Default, Serialize, Deserialize)]
+pub struct StateToken(BTreeMap<String, String>);
+
+impl StateToken {
+    /// Collects the values of the parsed state, leaving out the fields that are edited on the
+    /// given step since its form submits them itself.
+    pub fn from_state(state: &ApplicationState, step: &str) -> StateToken {
+        let edited: &[&str] = match step {
+            "input" => &INPUT_FIELDS,
+            "fuel" => &FUEL_FIELDS,
+            _ => &[],
+        };
+
+        let mut values = state.form_values();
+        let values = INPUT_FIELDS
+            .iter()
+            .chain(FUEL_FIELDS.iter())
+            .filter(|field| !edited.contains(field))
+            .filter_map(|field| Some((field.to_string(), values.remove(field)?)))
+            .collect();
+
+        StateToken(values)
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(&self.0).expect("a string map always serializes");
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&json)
+            .expect("writing to a vector does not fail");
+        let compressed = encoder.finish().expect("writing to a vector does not fail");
+
+        format!(
+            "{}{}",
+            STATE_TOKEN_VERSION,
+            URL_SAFE_NO_PAD.encode(compressed)
+        )
+    }
+
+    pub fn decode(token: &str) -> Result<StateToken, String> {
+        let mut chars = token.chars();
+        match chars.next() {
+            Some(STATE_TOKEN_VERSION) => (),
+            Some(v) => return Err(format!("unsupported state token version {}", v)),
+            None => return Err("empty state token".to_string()),
+        }
+
+        let compressed = URL_SAFE_NO_PAD
+            .decode(chars.as_str())
+            .map_err(|e| format!("invalid state token: {}", e))?;
+
+        let mut json = Vec::new();
+        DeflateDecoder::new(compressed.as_slice())
+            .take(MAX_DECODED_LENGTH)
+            .read_to_end(&mut json)
+            .map_err(|e| format!("invalid state token: {}", e))?;
+
+        serde_json::from_slice(&json)
+            .map(StateToken)
+            .map_err(|e| format!("invalid state token: {}", e))
+    }
+
+    /// Fills the parameters that were not submitted explicitly with the values of the token.
+    pub fn merge_into(self, params: &mut IndexQueryParams) {
+        let mut values = self.0;
+        let mut take = |field: &str| values.remove(field);
+
+        fill(&mut params.units, take("units"));
+        fill(&mut params.callsign, take("callsign"));
+        fill(&mut params.loads, take("loads"));
+        fill(&mut params.seats, take("seats"));
+        fill(&mut params.pilot, take("pilot"));
+        fill(&mut params.pilot_seat, take("pilot_seat"));
+        fill(&mut params.passenger, take("passenger"));
+        fill(&mut params.passenger_seat, take("passenger_seat"));
+        fill(&mut params.baggage, take("baggage"));
+        fill(&mut params.equipment, take("equipment"));
+        fill(&mut params.oat, take("oat").and_then(|v| v.parse().ok()));
+        fill(
+            &mut params.pressure_altitude,
+            take("pressure_altitude").and_then(|v| v.parse().ok()),
+        );
+        fill(&mut params.wind, take("wind").and_then(|v| v.parse().ok()));
+        fill(&mut params.wind_direction, take("wind_direction"));
+        fill(&mut params.gust, take("gust"));
+        fill(&mut params.landing_elevation, take("landing_elevation"));
+        fill(&mut params.landing_qnh, take("landing_qnh"));
+        fill(&mut params.landing_oat, take("landing_oat"));
+        fill(&mut params.landing_wind, take("landing_wind"));
+        fill(
+            &mut params.landing_wind_direction,
+            take("landing_wind_direction"),
+        );
+        fill(&mut params.fuel_type, take("fuel_type"));
+        fill(&mut params.fuel_unit, take("fuel_unit"));
+        fill(&mut params.trip_duration, take("trip_duration"));
+        fill(&mut params.alternate_duration, take("alternate_duration"));
+        fill(&mut params.fuel_max, take("fuel_max"));
+        fill(&mut params.fuel_extra, take("fuel_extra"));
+        fill(&mut params.fuel_before, take("fuel_before"));
+        fill(&mut params.route, take("route"));
+        fill(&mut params.route_tas, take("route_tas"));
+        fill(
+            &mut params.route_wind_direction,
+            take("route_wind_direction"),
+        );
+        fill(&mut params.route_wind_speed, take("route_wind_speed"));
+        fill(&mut params.alternate, take("alternate"));
+    }
+}
+
+fn fill<T>(param: &mut Option<T>, value: Option<T>) {
+    if param.is_none() {
+        *param = value;
+    }
+}
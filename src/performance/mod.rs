@@ -0,0 +1,133 @@
+//! Take-off and landing performance. Every aircraft type refers to a performance model by
+//! name, the model reads its distances from the charts of the flight manual of that type.
+
+use serde::Deserialize;
+
+pub mod aquila_at01;
+
+pub use aquila_at01::{
+    calculate_aquila_performance_ldr, calculate_aquila_performance_tod, performance_self_test,
+    CHART_MAX_DISTANCE, MASS_RANGE, MAX_HEADWIND, MAX_TAILWIND, OAT_RANGE, PRESSURE_ALTITUDE_RANGE,
+};
+
+/// Conditions for the performance charts, the mass is in kilograms and the wind in knots.
+#[derive(Clone, Deserialize)]
+pub struct PerfQueryParams {
+    pub oat: f64,
+    pub pressure_altitude: f64,
+    pub mtow: f64,
+    pub wind: f64,
+    pub wind_direction: String,
+}
+
+impl PerfQueryParams {
+    /// Checks that the conditions are covered by the charts, outside their scale the
+    /// calculations would extrapolate or fail.
+    pub fn validate(&self, scale: &ChartScale) -> Result<(), String> {
+        self.validate_conditions(scale)?;
+        check_range("mass", self.mtow, scale.mass)
+    }
+
+    /// Checks the temperature, altitude and wind against the scales of the charts.
+    pub fn validate_conditions(&self, scale: &ChartScale) -> Result<(), String> {
+        let max_wind = match self.wind_direction.as_str() {
+            "headwind" => scale.max_headwind,
+            "tailwind" => scale.max_tailwind,
+            other => return Err(format!("unknown wind direction {}", other)),
+        };
+
+        check_range("OAT", self.oat, scale.oat)?;
+        check_range(
+            "pressure altitude",
+            self.pressure_altitude,
+            scale.pressure_altitude,
+        )?;
+        check_range("wind", self.wind, (0.0, max_wind))
+    }
+}
+
+fn check_range(name: &str, value: f64, (min, max): (f64, f64)) -> Result<(), String> {
+    if value.is_finite() && (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} {} is outside the chart range {} to {}",
+            name, value, min, max
+        ))
+    }
+}
+
+/// QNH of the standard atmosphere in hectopascal.
+pub const STANDARD_QNH: f64 = 1013.25;
+
+/// Change of the pressure altitude per hectopascal near sea level, in feet.
+const FEET_PER_HPA: f64 = 27.0;
+
+/// Pressure altitude in feet of an aerodrome from its elevation in feet and the QNH.
+pub fn pressure_altitude(elevation: f64, qnh: f64) -> f64 {
+    elevation + (STANDARD_QNH - qnh) * FEET_PER_HPA
+}
+
+/// Wind in knots used for the performance calculations, negative for a tailwind. Gusts never
+/// give credit: a headwind counts at its steady speed, a tailwind at its gust speed.
+pub fn performance_wind(wind: f64, gust: Option<f64>) -> f64 {
+    match gust {
+        Some(g) if wind < 0.0 => -g.abs().max(-wind),
+        _ => wind,
+    }
+}
+
+/// Scales of a set of charts, temperatures in degrees Celsius, altitudes in feet, masses in
+/// kilograms and winds in knots.
+#[derive(Clone, Copy, Debug)]
+pub struct ChartScale {
+    pub oat: (f64, f64),
+    pub pressure_altitude: (f64, f64),
+    pub mass: (f64, f64),
+    pub max_headwind: f64,
+    pub max_tailwind: f64,
+}
+
+/// Construction line through a chart, in the coordinates of its SVG template.
+pub struct ConstructionLine {
+    pub oat_x_base: f64,
+    pub oat_y_base: f64,
+    pub tom_x: f64,
+    pub tom_y: f64,
+    pub wind_x: f64,
+    pub wind_y: f64,
+    pub ob_y: f64,
+}
+
+/// Distances in meters read from a chart, together with the construction line that was drawn
+/// to read them.
+pub struct ChartReading {
+    pub line: ConstructionLine,
+    pub ground_roll: f64,
+    pub distance: f64,
+}
+
+/// Take-off and landing performance of an aircraft type.
+pub trait PerformanceModel: Send + Sync {
+    fn scale(&self) -> ChartScale;
+
+    /// Take-off run and take-off distance over a 15 m obstacle.
+    fn takeoff(&self, params: PerfQueryParams) -> ChartReading;
+
+    /// Landing ground roll and landing distance over a 15 m obstacle.
+    fn landing(&self, params: PerfQueryParams) -> ChartReading;
+
+    /// Names of the SVG templates of the take-off and landing charts.
+    fn chart_templates(&self) -> (&'static str, &'static str);
+}
+
+/// Names of the available performance models.
+pub const MODELS: [&str; 1] = ["aquila-at01"];
+
+/// Returns the performance model with the given name.
+pub fn model(name: &str) -> Option<&'static dyn PerformanceModel> {
+    match name {
+        "aquila-at01" => Some(&aquila_at01::AquilaAt01),
+        _ => None,
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+
+use tracing::subscriber::NoSubscriber;
+
+use crate::performance::{
+    calculate_aquila_performance_ldr, calculate_aquila_performance_tod, PerfQueryParams,
+    CHART_MAX_DISTANCE,
+};
+
+const DISTANCES: [&str; 4] = ["torr", "todr", "lgrr", "ldr"];
+
+/// Largest change of each distance between neighbouring grid points that is still continuous.
+/// Distances are read at the next line of the chart scale, so a step of one scale division is
+/// expected; skipping a line is not. The lines of the scales are at most 32.4 m (take-off
+/// ground roll), 61.0 m (take-off distance), 30.5 m (landing ground roll) and 72.3 m (landing
+/// distance) apart, rounded up to 5 m.
+const CONTINUITY_TOLERANCE: [f64; 4] = [35.0, 65.0, 35.0, 75.0];
+
+/// Step sizes of the grid that is swept over the chart domain. The default steps are the
+/// coarsest at which no step of an input moves a distance by more than one scale division.
+pub struct Grid {
+    pub oat_step: f64,
+    pub pressure_altitude_step: f64,
+    pub mass_step: f64,
+    pub wind_step: f64,
+}
+
+impl Default for Grid {
+    fn default() -> Grid {
+        Grid {
+            oat_step: 5.0,
+            pressure_altitude_step: 500.0,
+            mass_step: 20.0,
+            wind_step: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViolationKind {
+    Monotonicity,
+    Continuity,
+}
+
+/// Change of a distance between a grid point and its neighbour along one input.
+#[derive(Clone, Debug)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    pub distance: &'static str,
+    pub input: &'static str,
+    pub oat: f64,
+    pub pressure_altitude: f64,
+    pub mass: f64,
+    pub wind: f64,
+    pub from: f64,
+    pub to: f64,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ViolationKind::Monotonicity => "not monotone",
+            ViolationKind::Continuity => "discontinuous",
+        };
+
+        write!(
+            f,
+            "{} {} along {} at OAT {} °C, PA {} ft, mass {} kg, wind {} kt: {:.1} -> {:.1} m",
+            self.distance,
+            kind,
+            self.input,
+            self.oat,
+            self.pressure_altitude,
+            self.mass,
+            self.wind,
+            self.from,
+            self.to
+        )
+    }
+}
+
+pub struct Sweep {
+    pub points: usize,
+    pub violations: Vec<Violation>,
+}
+
+/// Inputs along which the grid is swept, with their range and whether a higher value may
+/// only give a longer (`1.0`) or a shorter (`-1.0`) distance.
+const INPUTS: [(&str, f64, f64, f64); 4] = [
+    ("OAT", -30.0, 40.0, 1.0),
+    ("pressure altitude", 0.0, 8000.0, 1.0),
+    ("mass", 550.0, 750.0, 1.0),
+    ("wind", -10.0, 20.0, -1.0),
+];
+
+/// Sweeps the grid over the full input domain of the charts and verifies that the distances
+/// are monotone and continuous between neighbouring points. Distances that run off the chart
+/// scale are left out.
+pub fn sweep(grid: &Grid) -> Sweep {
+    // Every grid point reads both charts, their spans would flood the log.
+    tracing::subscriber::with_default(NoSubscriber::default(), || sweep_grid(grid))
+}
+
+fn sweep_grid(grid: &Grid) -> Sweep {
+    let steps = [
+        grid.oat_step,
+        grid.pressure_altitude_step,
+        grid.mass_step,
+        grid.wind_step,
+    ];
+    let counts: Vec<usize> = INPUTS
+        .iter()
+        .zip(steps)
+        .map(|((_, min, max, _), step)| ((max - min) / step).floor() as usize + 1)
+        .collect();
+    let value = |axis: usize, index: usize| INPUTS[axis].1 + index as f64 * steps[axis];
+
+    let mut violations = vec![];
+    let mut points = 0;
+
+    for i in 0..counts[0] {
+        for j in 0..counts[1] {
+            for k in 0..counts[2] {
+                for l in 0..counts[3] {
+                    let point = [value(0, i), value(1, j), value(2, k), value(3, l)];
+                    let current = distances(point);
+                    points += 1;
+
+                    for (axis, index) in [i, j, k, l].into_iter().enumerate() {
+                        if index + 1 >= counts[axis] {
+                            continue;
+                        }
+
+                        let mut next_point = point;
+                        next_point[axis] = value(axis, index + 1);
+                        let next = distances(next_point);
+
+                        for (d, name) in DISTANCES.iter().enumerate() {
+                            let (from, to) = (current[d], next[d]);
+                            if from >= CHART_MAX_DISTANCE || to >= CHART_MAX_DISTANCE {
+                                continue;
+                            }
+
+                            let kind = if (to - from) * INPUTS[axis].3 < 0.0 {
+                                ViolationKind::Monotonicity
+                            } else if (to - from).abs() > CONTINUITY_TOLERANCE[d] {
+                                ViolationKind::Continuity
+                            } else {
+                                continue;
+                            };
+
+                            violations.push(Violation {
+                                kind,
+                                distance: name,
+                                input: INPUTS[axis].0,
+                                oat: point[0],
+                                pressure_altitude: point[1],
+                                mass: point[2],
+                                wind: point[3],
+                                from,
+                                to,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Sweep { points, violations }
+}
+
+fn distances([oat, pressure_altitude, mass, wind]: [f64; 4]) -> [f64; 4] {
+    let params = || PerfQueryParams {
+        oat,
+        pressure_altitude,
+        mtow: mass,
+        wind: wind.abs(),
+        wind_direction: if wind < 0.0 { "tailwind" } else { "headwind" }.to_string(),
+    };
+
+    let (.., torr, todr) = calculate_aquila_performance_tod(params());
+    let (.., lgrr, ldr) = calculate_aquila_performance_ldr(params());
+
+    [torr, todr, lgrr, ldr]
+}
@@ -0,0 +1,322 @@
+use std::time::Duration;
+
+use airplane::{
+    types::{FuelType, VolumeType},
+    weight_and_balance::{Airplane, CenterOfGravity, LeverArm, Limits, Mass, Moment, Volume},
+};
+
+use crate::{
+    aircraft::{
+        Aircraft, AircraftType, Equipment, FuelPolicy, FuelSystem, Station, Tank, Weighing,
+    },
+    units::UnitSystem,
+};
+
+/// Name of the fuel moment of a plane, the usable fuel on board. Planes with several tanks have
+/// a fuel moment per tank, named after the tank.
+pub const FUEL: &str = "Fuel";
+
+/// Name of the moment of the unusable fuel, which never counts for the fuel planning.
+pub const UNUSABLE_FUEL: &str = "Unusable Fuel";
+
+/// Name of the moment of the usable fuel in a tank.
+pub fn fuel_moment_name(tank: &Tank, tanks: usize) -> String {
+    match tanks {
+        1 => FUEL.to_string(),
+        _ => format!("{} {}", FUEL, tank.name),
+    }
+}
+
+/// Moment of the usable fuel in a tank, found by its name and fuel mass so a load that happens
+/// to have the same name is not taken for fuel.
+fn tank_moment<'a>(plane: &'a Airplane, tank: &Tank, tanks: usize) -> Option<&'a Moment> {
+    let name = fuel_moment_name(tank, tanks);
+    plane
+        .moments()
+        .iter()
+        .find(|m| *m.name() == name && matches!(m.mass(), Mass::Avgas(_) | Mass::Mogas(_)))
+}
+
+/// Moments of the loaded stations, named after the station. The masses are in kilograms in the
+/// order of the stations, stations without a mass are left out. Adjustable seats are in the
+/// position of the same index.
+pub fn station_moments(
+    stations: &[Station],
+    loads: &[Option<f64>],
+    seats: &[String],
+) -> Vec<Moment> {
+    stations
+        .iter()
+        .enumerate()
+        .filter_map(|(i, station)| {
+            let mass = loads.get(i).copied().flatten()?;
+            let seat = seats.get(i).map(String::as_str).unwrap_or_default();
+
+            Some(Moment::new(
+                station.name.clone(),
+                LeverArm::Meter(station.lever_arm(seat)),
+                Mass::Kilo(mass),
+            ))
+        })
+        .collect()
+}
+
+fn fuel_mass(fuel_type: &FuelType, liters: f64) -> Mass {
+    match fuel_type {
+        FuelType::Avgas => Mass::Avgas(Volume::Liter(liters)),
+        FuelType::Mogas => Mass::Mogas(Volume::Liter(liters)),
+    }
+}
+
+/// Aircraft, loads and fuel of a flight, from which [`build_plane`] builds the plane.
+pub struct PlaneInput<'a> {
+    pub aircraft: &'a Aircraft,
+    pub aircraft_type: &'a AircraftType,
+    /// Weighing report with the empty mass and its arm.
+    pub weighing: &'a Weighing,
+    pub equipment: &'a [Equipment],
+    pub fuel_policy: &'a FuelPolicy,
+    /// Moments of the loaded stations, see [`station_moments`].
+    pub loads: Vec<Moment>,
+    pub fuel_type: FuelType,
+    pub fuel_unit: VolumeType,
+    /// Usable fuel on board per tank, in the order of the tanks; ignored with `fuel_max`.
+    pub fuel_extra: Option<Vec<Mass>>,
+    pub fuel_max: bool,
+    pub trip_duration: Duration,
+}
+
+#[tracing::instrument(
+    level = "info",
+    skip_all,
+    fields(callsign = %input.aircraft.callsign, fuel_max = input.fuel_max)
+)]
+pub fn build_plane(input: PlaneInput) -> Airplane {
+    let PlaneInput {
+        aircraft,
+        aircraft_type,
+        weighing,
+        equipment,
+        fuel_policy,
+        loads,
+        fuel_type,
+        fuel_unit,
+        fuel_extra,
+        fuel_max,
+        trip_duration,
+    } = input;
+
+    let mut moments = vec![Moment::new(
+        "Empty Mass".to_string(),
+        LeverArm::Meter(weighing.empty_mass_arm),
+        Mass::Kilo(weighing.empty_mass),
+    )];
+
+    moments.extend(aircraft_type.fluids.iter().map(|fluid| {
+        Moment::new(
+            fluid.name.clone(),
+            LeverArm::Meter(fluid.arm),
+            Mass::Kilo(fluid.mass),
+        )
+    }));
+
+    let tanks = aircraft_type.fuel.tanks();
+    for tank in &tanks {
+        if let Some(liters) = tank.unusable.filter(|l| *l > 0.0) {
+            moments.push(Moment::new(
+                match tanks.len() {
+                    1 => UNUSABLE_FUEL.to_string(),
+                    _ => format!("{} {}", UNUSABLE_FUEL, tank.name),
+                },
+                LeverArm::Meter(tank.lever_arm),
+                fuel_mass(&fuel_type, liters),
+            ));
+        }
+    }
+
+    moments.extend(equipment.iter().map(|item| {
+        Moment::new(
+            item.name.clone(),
+            LeverArm::Meter(item.arm),
+            Mass::Kilo(item.mass),
+        )
+    }));
+
+    moments.extend(loads);
+
+    let envelope = &aircraft_type.envelope;
+
+    let mut plane = Airplane::new(
+        aircraft.callsign.clone(),
+        moments,
+        Limits::new(
+            Mass::Kilo(envelope.min_mass),
+            Mass::Kilo(envelope.max_mass),
+            CenterOfGravity::Millimeter(envelope.forward_cg * 1000.0),
+            CenterOfGravity::Millimeter(envelope.aft_cg * 1000.0),
+        ),
+        Volume::Liter(fuel_policy.consumption_per_hour * trip_duration.as_secs_f64() / 60.0 / 60.0),
+    );
+
+    // The tanks are filled in the order they are listed, as far as the limits allow.
+    let mut fuel_extra = fuel_extra.unwrap_or_default().into_iter();
+    for tank in &tanks {
+        let fuel_name = fuel_moment_name(tank, tanks.len());
+        let fuel_lever_arm = LeverArm::Meter(tank.lever_arm);
+
+        if fuel_max {
+            plane.add_max_fuel_within_limits(
+                fuel_name,
+                fuel_lever_arm,
+                fuel_type.clone(),
+                fuel_unit.clone(),
+                Some(Volume::Liter(tank.capacity.unwrap_or(fuel_policy.max_fuel))),
+            );
+        } else {
+            plane.add_moment(Moment::new(
+                fuel_name,
+                fuel_lever_arm,
+                fuel_extra
+                    .next()
+                    .unwrap_or_else(|| fuel_mass(&fuel_type, 0.0)),
+            ));
+        }
+    }
+
+    plane
+}
+
+/// Usable fuel on board in liters, the sum of the fuel in all tanks.
+pub fn fuel_on_board(plane: &Airplane, fuel: &FuelSystem) -> f64 {
+    tank_contents(plane, fuel).iter().sum()
+}
+
+/// Usable fuel in liters in each tank, in the order the tanks are listed.
+pub fn tank_contents(plane: &Airplane, fuel: &FuelSystem) -> Vec<f64> {
+    let tanks = fuel.tanks();
+    tanks
+        .iter()
+        .map(|tank| {
+            let mass = tank_moment(plane, tank, tanks.len()).map(Moment::mass);
+            match mass {
+                Some(Mass::Avgas(v) | Mass::Mogas(v)) => v.to_liter(),
+                _ => 0.0,
+            }
+        })
+        .collect()
+}
+
+/// Total moment in kilogram meters of all loaded moments.
+pub fn total_moment(plane: &Airplane) -> f64 {
+    plane
+        .moments()
+        .iter()
+        .map(|m| {
+            let LeverArm::Meter(arm) = m.lever_arm();
+            m.mass().kilo() * *arm
+        })
+        .sum()
+}
+
+/// Total moment in kilogram meters after the trip fuel has been burned. The tanks are used in
+/// the order of their burn sequence, tanks in the same step in proportion to their contents.
+pub fn total_moment_landing(plane: &Airplane, fuel: &FuelSystem) -> f64 {
+    let mut trip_fuel = plane.total_mass().kilo() - plane.total_mass_landing().kilo();
+    let mut moment = total_moment(plane);
+
+    let tanks = fuel.tanks();
+    let mut sequence: Vec<u32> = tanks.iter().map(|t| t.sequence).collect();
+    sequence.sort_unstable();
+    sequence.dedup();
+
+    for (i, step) in sequence.iter().enumerate() {
+        // Contents in kilograms and lever arms of the tanks used in this step.
+        let contents: Vec<(f64, f64)> = tanks
+            .iter()
+            .filter(|t| t.sequence == *step)
+            .filter_map(|t| tank_moment(plane, t, tanks.len()))
+            .map(|m| {
+                let LeverArm::Meter(arm) = m.lever_arm();
+                (m.mass().kilo(), *arm)
+            })
+            .collect();
+        let total: f64 = contents.iter().map(|(mass, _)| mass).sum();
+        let count = contents.len() as f64;
+
+        // Whatever is left of the trip fuel comes out of the tanks used last.
+        let burned = if i + 1 == sequence.len() {
+            trip_fuel
+        } else {
+            trip_fuel.min(total)
+        };
+
+        for (mass, arm) in contents {
+            let share = if total > 0.0 {
+                mass / total
+            } else {
+                1.0 / count
+            };
+            moment -= burned * share * arm;
+        }
+
+        trip_fuel -= burned;
+        if trip_fuel <= 0.0 {
+            break;
+        }
+    }
+
+    moment
+}
+
+/// Whether the mass and centre of gravity after the trip fuel has been burned are within the
+/// limits of the plane.
+pub fn within_limits_landing(plane: &Airplane, fuel: &FuelSystem) -> bool {
+    let limits = plane.limits();
+    let mass = plane.total_mass_landing().kilo();
+    let cg = total_moment_landing(plane, fuel) / mass;
+
+    mass <= limits.mtow().kilo()
+        && cg >= limits.forward_cg_limit().meter()
+        && cg <= limits.rearward_cg_limit().meter()
+}
+
+/// Whether the plane is within its limits at take-off as well as at landing.
+pub fn within_limits(plane: &Airplane, fuel: &FuelSystem) -> bool {
+    plane.within_limits() && within_limits_landing(plane, fuel)
+}
+
+pub fn weight_and_balance_table(plane: &Airplane, unit_system: UnitSystem) -> Vec<Vec<String>> {
+    let mut rows = vec![vec![
+        "Name".to_string(),
+        format!("Lever Arm [{}]", unit_system.arm_unit()),
+        format!("Mass [{}]", unit_system.mass_unit()),
+        format!("Moment [{}]", unit_system.moment_unit()),
+    ]];
+
+    for moment in plane.moments().iter() {
+        let LeverArm::Meter(arm) = moment.lever_arm();
+        let mass = moment.mass().kilo();
+
+        rows.push(vec![
+            moment.name().to_string(),
+            format!("{:.3}", unit_system.arm_from_meter(*arm)),
+            format!("{:.1}", unit_system.mass_from_kilo(mass)),
+            format!("{:.1}", unit_system.moment_from_kilo_meter(mass * *arm)),
+        ]);
+    }
+
+    let total_mass = plane.total_mass().kilo();
+    let total_moment = total_moment(plane);
+
+    rows.push(vec![
+        "Total".to_string(),
+        format!(
+            "{:.3}",
+            unit_system.arm_from_meter(total_moment / total_mass)
+        ),
+        format!("{:.1}", unit_system.mass_from_kilo(total_mass)),
+        format!("{:.1}", unit_system.moment_from_kilo_meter(total_moment)),
+    ]);
+
+    rows
+}
@@ -0,0 +1,289 @@
+//! Trip planning along a route of waypoints. The great-circle distance of every leg and the
+//! ground speed from the true airspeed and a wind estimate give the leg times, which add up to
+//! the trip duration of the fuel plan.
+
+use std::{fs, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+const EMBEDDED_AIRPORTS: &str = include_str!("../data/airports.toml");
+
+/// Mean radius of the earth in nautical miles.
+const EARTH_RADIUS: f64 = 3440.065;
+
+/// Position in decimal degrees, north and east are positive.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Position {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Position {
+    pub fn new(latitude: f64, longitude: f64) -> Result<Position, String> {
+        if !(latitude.is_finite() && (-90.0..=90.0).contains(&latitude)) {
+            return Err(format!("latitude {} is outside -90 to 90", latitude));
+        }
+
+        if !(longitude.is_finite() && (-180.0..=180.0).contains(&longitude)) {
+            return Err(format!("longitude {} is outside -180 to 180", longitude));
+        }
+
+        Ok(Position {
+            latitude,
+            longitude,
+        })
+    }
+
+    /// Great-circle distance in nautical miles.
+    pub fn distance(&self, other: &Position) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+    }
+
+    /// Initial true course in degrees towards another position.
+    pub fn course(&self, other: &Position) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlon = (other.longitude - self.longitude).to_radians();
+
+        let y = dlon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Airport {
+    /// ICAO location indicator, such as `EHLE`.
+    pub code: String,
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Elevation in feet, used for the landing performance at an alternate.
+    #[serde(default)]
+    pub elevation: Option<f64>,
+}
+
+impl Airport {
+    pub fn position(&self) -> Position {
+        Position {
+            latitude: self.latitude,
+            longitude: self.longitude,
+        }
+    }
+}
+
+/// Offline airport database used to resolve the airport codes of a route.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AirportDatabase {
+    #[serde(default, rename = "airport")]
+    pub airports: Vec<Airport>,
+}
+
+impl AirportDatabase {
+    /// Loads the airport file, or the embedded database when no file is given.
+    pub fn load(path: Option<&Path>) -> Result<AirportDatabase, String> {
+        let content = match path {
+            Some(p) => fs::read_to_string(p)
+                .map_err(|e| format!("unable to read {}: {}", p.display(), e))?,
+            None => EMBEDDED_AIRPORTS.to_string(),
+        };
+
+        let database: AirportDatabase =
+            toml::from_str(&content).map_err(|e| format!("invalid airport database: {}", e))?;
+        database.validate()?;
+
+        Ok(database)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, airport) in self.airports.iter().enumerate() {
+            if airport.code.is_empty() || !airport.code.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(format!(
+                    "airport code `{}` must be letters and digits",
+                    airport.code
+                ));
+            }
+
+            if self.airports[..i]
+                .iter()
+                .any(|a| a.code.eq_ignore_ascii_case(&airport.code))
+            {
+                return Err(format!("duplicate airport {}", airport.code));
+            }
+
+            Position::new(airport.latitude, airport.longitude)
+                .map_err(|e| format!("airport {}: {}", airport.code, e))?;
+
+            if airport.elevation.is_some_and(|e| !e.is_finite()) {
+                return Err(format!("airport {}: invalid elevation", airport.code));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn find(&self, code: &str) -> Option<&Airport> {
+        self.airports
+            .iter()
+            .find(|a| a.code.eq_ignore_ascii_case(code))
+    }
+
+    /// Resolves a waypoint given as an airport code or as `latitude,longitude` in decimal
+    /// degrees.
+    pub fn waypoint(&self, value: &str) -> Result<Waypoint, String> {
+        let value = value.trim();
+
+        if let Some((latitude, longitude)) = value.split_once(',') {
+            let coordinate = |v: &str| {
+                v.trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid coordinates {}", value))
+            };
+            let position = Position::new(coordinate(latitude)?, coordinate(longitude)?)?;
+
+            return Ok(Waypoint {
+                name: value.to_string(),
+                position,
+            });
+        }
+
+        let airport = self
+            .find(value)
+            .ok_or_else(|| format!("unknown airport {}", value))?;
+
+        Ok(Waypoint::from(airport))
+    }
+
+    /// Airports ordered by their distance in nautical miles from a position, nearest first.
+    pub fn nearest(&self, position: &Position) -> Vec<(&Airport, f64)> {
+        let mut airports: Vec<(&Airport, f64)> = self
+            .airports
+            .iter()
+            .map(|a| (a, position.distance(&a.position())))
+            .collect();
+        airports.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        airports
+    }
+
+    /// Resolves the waypoints of a route, separated by whitespace.
+    pub fn route(&self, value: &str) -> Result<Vec<Waypoint>, String> {
+        value.split_whitespace().map(|w| self.waypoint(w)).collect()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Waypoint {
+    pub name: String,
+    pub position: Position,
+}
+
+impl From<&Airport> for Waypoint {
+    fn from(airport: &Airport) -> Waypoint {
+        Waypoint {
+            name: airport.code.to_uppercase(),
+            position: airport.position(),
+        }
+    }
+}
+
+/// Wind estimate for the route, the direction it blows from in degrees true and its speed in
+/// knots.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Wind {
+    pub direction: f64,
+    pub speed: f64,
+}
+
+/// Ground speed in knots on a true course, `None` when the wind does not allow the aircraft to
+/// make progress along it.
+pub fn ground_speed(tas: f64, course: f64, wind: Wind) -> Option<f64> {
+    let angle = (wind.direction - course).to_radians();
+    let crosswind = wind.speed * angle.sin();
+    let headwind = wind.speed * angle.cos();
+
+    if crosswind.abs() >= tas {
+        return None;
+    }
+
+    let wind_correction = (crosswind / tas).asin();
+    let ground_speed = tas * wind_correction.cos() - headwind;
+
+    (ground_speed > 0.0).then_some(ground_speed)
+}
+
+/// Leg between two waypoints, the distance in nautical miles, the course in degrees true and
+/// the ground speed in knots.
+#[derive(Clone, Debug, Serialize)]
+pub struct Leg {
+    pub from: String,
+    pub to: String,
+    pub distance: f64,
+    pub course: f64,
+    pub ground_speed: f64,
+    pub minutes: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RoutePlan {
+    pub legs: Vec<Leg>,
+    pub distance: f64,
+    pub minutes: f64,
+}
+
+impl RoutePlan {
+    /// Plans the legs between the waypoints at a true airspeed in knots. Every leg is flown on
+    /// its initial course, which is accurate enough for the leg lengths of a VFR route.
+    pub fn new(waypoints: &[Waypoint], tas: f64, wind: Wind) -> Result<RoutePlan, String> {
+        if waypoints.len() < 2 {
+            return Err("a route needs at least two waypoints".to_string());
+        }
+
+        if !(tas.is_finite() && tas > 0.0) {
+            return Err("true airspeed must be positive".to_string());
+        }
+
+        if !(wind.speed.is_finite() && wind.speed >= 0.0 && wind.direction.is_finite()) {
+            return Err("invalid wind".to_string());
+        }
+
+        let legs = waypoints
+            .windows(2)
+            .map(|pair| {
+                let (from, to) = (&pair[0], &pair[1]);
+                let distance = from.position.distance(&to.position);
+                let course = from.position.course(&to.position);
+                let ground_speed = ground_speed(tas, course, wind).ok_or_else(|| {
+                    format!("no progress from {} to {} in this wind", from.name, to.name)
+                })?;
+
+                Ok(Leg {
+                    from: from.name.clone(),
+                    to: to.name.clone(),
+                    distance,
+                    course,
+                    ground_speed,
+                    minutes: distance / ground_speed * 60.0,
+                })
+            })
+            .collect::<Result<Vec<Leg>, String>>()?;
+
+        Ok(RoutePlan {
+            distance: legs.iter().map(|l| l.distance).sum(),
+            minutes: legs.iter().map(|l| l.minutes).sum(),
+            legs,
+        })
+    }
+
+    /// Trip duration for the fuel plan, rounded up to whole minutes.
+    pub fn trip_duration(&self) -> Duration {
+        Duration::from_secs(self.minutes.ceil() as u64 * 60)
+    }
+}
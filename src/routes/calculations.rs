@@ -1,938 +1,1038 @@
 use core::panic;
 use std::time::Duration;
 
+use acm_weight_and_balance::{
+    date,
+    fuel::{self, FuelPlan, Refuel},
+    performance::{self as charts, ChartReading, PerformanceModel},
+    route::{Airport, RoutePlan, Waypoint},
+};
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use airplane::{
-    types::VolumeType,
-    visualizer::{WeightBalanceChartVisualization, WeightBalanceTableVisualization},
-    weight_and_balance::Volume,
+    types::{FuelType, VolumeType},
+    visualizer::WeightBalanceTableVisualization,
+    weight_and_balance::{Airplane, Volume},
 };
-use tera::Tera;
 
 use crate::{
     models::{
-        query_params::{IndexQueryParams, PerfQueryParams},
+        aircraft::{Aircraft, AircraftType, Weighing, DEFAULT_AIRCRAFT_TYPE},
+        loadsheet::{Loadsheet, Performance},
+        query_params::{ChartQueryParams, IndexQueryParams, PerfQueryParams},
         state::{duration_to_hh_mm, ApplicationState},
     },
-    utils::plane,
+    runtime::{CurrentRuntime, Runtime},
+    utils::{
+        chart,
+        plane::{self, PlaneInput},
+        theme::Theme,
+        units::{round, UnitSystem},
+    },
 };
 
+use super::render;
+
 pub async fn calculations(
     query: web::Query<IndexQueryParams>,
-    tmpl: web::Data<Tera>,
+    runtime: CurrentRuntime,
 ) -> impl Responder {
     let mut ctx = tera::Context::new();
-    let (app_state, query) = ApplicationState::from_query_params(query.into_inner());
+    let (app_state, query) =
+        ApplicationState::from_query_params(query.into_inner(), &runtime.registry);
 
     match query.submit {
         Some(s) if s == "Vorige" => {
             app_state.apply("fuel", &mut ctx);
-            let rendered = tmpl.render("fuel_form.html", &ctx).unwrap();
-            return HttpResponse::Ok().content_type("text/html").body(rendered);
+            return render(&runtime.tera, "fuel_form.html", &ctx, "text/html");
         }
         _ => (),
     }
 
-    let rendered = tmpl.render("export_form.html", &ctx).unwrap();
-    HttpResponse::Ok().content_type("text/html").body(rendered)
+    render(&runtime.tera, "export_form.html", &ctx, "text/html")
 }
 
 pub fn render_calculations(
     app_state: &ApplicationState,
     ctx: &mut tera::Context,
-    req: HttpRequest,
-    tmpl: web::Data<Tera>,
+    runtime: &Runtime,
     template: &str,
 ) -> HttpResponse {
-    let plane = plane::build_plane(
-        app_state.callsign.clone().unwrap(),
-        app_state.pilot_moment.clone().unwrap(),
-        app_state.passenger_moment.clone(),
-        app_state.baggage_moment.clone(),
-        app_state.fuel_type.clone().unwrap(),
-        app_state.fuel_unit.clone().unwrap(),
-        app_state.fuel_extra.clone(),
-        app_state.fuel_max.unwrap_or_default(),
-        app_state.trip_duration.unwrap(),
-    );
-
-    app_state.apply("calculation", ctx);
-
-    if let Some(fuel_moment) = plane.moments().last() {
-        let fuel_mass = fuel_moment.mass();
-
-        let fuel_liters = match fuel_mass {
-            airplane::weight_and_balance::Mass::Mogas(v)
-            | airplane::weight_and_balance::Mass::Avgas(v) => v.to_liter(),
-            _ => panic!("should be a fuel"),
-        };
+    match calculate(app_state, ctx, runtime) {
+        Ok(_) => render(&runtime.tera, template, ctx, "text/html"),
+        Err(response) => *response,
+    }
+}
 
-        let taxi = match app_state.fuel_unit {
-            Some(VolumeType::Liter) => Volume::Liter(2.0),
-            Some(VolumeType::Gallon) => Volume::Gallon(Volume::Liter(2.0).to_gallon()),
-            None => panic!("should never be none"),
-        };
+/// Runs the weight and balance, fuel and performance calculations, adds the results to the
+/// context and returns them as a canonical loadsheet.
+pub fn calculate(
+    app_state: &ApplicationState,
+    ctx: &mut tera::Context,
+    runtime: &Runtime,
+) -> Result<Loadsheet, Box<HttpResponse>> {
+    let aircraft = match runtime
+        .registry
+        .get(app_state.callsign.as_deref().unwrap_or_default())
+    {
+        Some(a) => a,
+        None => {
+            return Err(Box::new(
+                HttpResponse::BadRequest().body("Unknown aircraft"),
+            ))
+        }
+    };
+    let aircraft_type = runtime.registry.type_of(aircraft);
+    let policy = &runtime.registry.fuel_policy(aircraft_type);
+
+    let mut errors = app_state.condition_errors();
+    let weighing = weighing(app_state, runtime, aircraft).map_err(|e| errors.push(e));
+    if !errors.is_empty() {
+        let mut ctx = tera::Context::new();
+        app_state.apply("input", &mut ctx);
+        ctx.insert("aircraft", &runtime.registry.callsigns());
+        ctx.insert("errors", &errors);
+        return Err(Box::new(render(
+            &runtime.tera,
+            "wb_form.html",
+            &ctx,
+            "text/html",
+        )));
+    }
+    let weighing = weighing.expect("weighing errors should be reported");
+
+    let fuel_type = app_state
+        .fuel_type
+        .clone()
+        .expect("should have a fuel type");
+    let mut errors = vec![];
+    if !aircraft_type.fuel.allows(&fuel_type) {
+        errors.push(format!(
+            "De {} mag niet met {} vliegen.",
+            aircraft_type.name,
+            fuel::grade(&fuel_type)
+        ));
+    }
+    errors.extend(app_state.tank_errors());
+    if app_state.trip_duration.is_none() {
+        errors.push("Vul de reisduur in als uu:mm.".to_string());
+    }
+    if app_state.alternate_duration.is_none() {
+        errors.push("Vul de reisduur naar de alternatieve luchthaven in als uu:mm.".to_string());
+    }
+    let route = route_plan(app_state, runtime)
+        .transpose()
+        .map_err(|e| errors.push(e));
+    // Without a valid route there is no destination to divert from.
+    let diversion = match &route {
+        Ok(_) => diversion_plan(app_state, runtime)
+            .transpose()
+            .map_err(|e| errors.push(e)),
+        Err(_) => Ok(None),
+    };
+    if !errors.is_empty() {
+        return Err(Box::new(fuel_errors(app_state, runtime, &errors)));
+    }
+    let route = route.expect("route errors should be reported");
+    let diversion = diversion.expect("diversion errors should be reported");
 
-        let reserve = match app_state.fuel_unit {
-            Some(VolumeType::Liter) => Volume::Liter(17.0 * 0.75),
-            Some(VolumeType::Gallon) => Volume::Gallon(Volume::Liter(17.0 * 0.75).to_gallon()),
-            None => panic!("should never be none"),
-        };
+    let plane = build_plane(app_state, aircraft, &weighing, runtime);
 
-        let trip = match app_state.fuel_unit {
-            Some(VolumeType::Liter) => Volume::Liter(
-                17.0 * (app_state
-                    .trip_duration
-                    .expect("should have duration")
-                    .as_secs_f64()
-                    / 60.0
-                    / 60.0),
-            ),
-            Some(VolumeType::Gallon) => Volume::Gallon(
-                Volume::Liter(
-                    17.0 * (app_state
-                        .trip_duration
-                        .expect("should have duration")
-                        .as_secs_f64()
-                        / 60.0
-                        / 60.0),
-                )
-                .to_gallon(),
-            ),
-            None => panic!("should never be none"),
-        };
+    app_state.apply("calculation", ctx);
+    if let Some(reference) = &weighing.reference {
+        ctx.insert("weighing_reference", reference);
+    }
+    if let Some(date) = &weighing.date {
+        ctx.insert("weighing_date", date);
+    }
 
-        let alternate = match app_state.fuel_unit {
-            Some(VolumeType::Liter) => Volume::Liter(
-                17.0 * (app_state
-                    .alternate_duration
-                    .expect("should have duration")
-                    .as_secs_f64()
-                    / 60.0
-                    / 60.0),
-            ),
-            Some(VolumeType::Gallon) => Volume::Gallon(
-                Volume::Liter(
-                    17.0 * (app_state
-                        .alternate_duration
-                        .expect("should have duration")
-                        .as_secs_f64()
-                        / 60.0
-                        / 60.0),
-                )
-                .to_gallon(),
-            ),
-            None => panic!("should never be none"),
-        };
+    // Only the usable fuel counts for the endurance, the unusable fuel has a moment of its own.
+    let fuel_on_board = plane::fuel_on_board(&plane, &aircraft_type.fuel);
+    let fuel_plan = FuelPlan::new(
+        policy,
+        fuel_on_board,
+        app_state.trip_duration.expect("should have duration"),
+        app_state.alternate_duration.expect("should have duration"),
+    );
 
-        let contigency = match app_state.fuel_unit {
-            Some(VolumeType::Liter) => Volume::Liter(trip.to_liter() * 0.1),
-            Some(VolumeType::Gallon) => {
-                Volume::Gallon(Volume::Liter(trip.to_liter() * 0.1).to_gallon())
-            }
+    let volume = |liters: f64| {
+        match app_state.fuel_unit {
+            Some(VolumeType::Liter) => Volume::Liter(liters),
+            Some(VolumeType::Gallon) => Volume::Gallon(Volume::Liter(liters).to_gallon()),
             None => panic!("should never be none"),
-        };
+        }
+        .to_string()
+        .replace('.', ",")
+    };
 
-        let extra = match app_state.fuel_unit {
-            Some(VolumeType::Liter) => Volume::Liter(
-                fuel_liters
-                    - taxi.to_liter()
-                    - reserve.to_liter()
-                    - trip.to_liter()
-                    - alternate.to_liter()
-                    - contigency.to_liter(),
-            ),
-            Some(VolumeType::Gallon) => Volume::Gallon(
-                Volume::Liter(
-                    fuel_liters
-                        - taxi.to_liter()
-                        - reserve.to_liter()
-                        - trip.to_liter()
-                        - alternate.to_liter()
-                        - contigency.to_liter(),
-                )
-                .to_gallon(),
-            ),
-            None => panic!("should never be none"),
-        };
+    ctx.insert("fuel_taxi", &volume(fuel_plan.taxi));
+    ctx.insert("fuel_reserve", &volume(fuel_plan.reserve));
+    ctx.insert("fuel_trip", &volume(fuel_plan.trip));
+    ctx.insert("fuel_alternate", &volume(fuel_plan.alternate));
+    ctx.insert("fuel_contigency", &volume(fuel_plan.contingency));
+    ctx.insert("fuel_additional", &volume(fuel_plan.extra));
+    ctx.insert("fuel_additional_abs", &volume(fuel_plan.extra.abs()));
+    let fuel_sufficient = fuel_plan.sufficient();
+    ctx.insert("fuel_sufficient", &fuel_sufficient);
+    ctx.insert("fuel_total", &volume(fuel_plan.total));
 
-        let endurance = Duration::from_secs((fuel_liters / 17.0 * 60.0 * 60.0) as u64);
+    ctx.insert(
+        "fuel_endurance",
+        &duration_to_hh_mm(&Duration::from_secs(fuel_plan.endurance_minutes * 60)),
+    );
+    ctx.insert(
+        "fuel_consumption",
+        &policy.consumption_per_hour.to_string().replace('.', ","),
+    );
+    ctx.insert("fuel_reserve_minutes", &policy.reserve_minutes.to_string());
+    ctx.insert(
+        "fuel_contingency_percentage",
+        &policy.contingency_percentage.to_string(),
+    );
 
-        ctx.insert("fuel_taxi", &taxi.to_string().replace('.', ","));
-        ctx.insert("fuel_reserve", &reserve.to_string().replace('.', ","));
-        ctx.insert("fuel_trip", &trip.to_string().replace('.', ","));
-        ctx.insert("fuel_alternate", &alternate.to_string().replace('.', ","));
-        ctx.insert("fuel_contigency", &contigency.to_string().replace('.', ","));
-        ctx.insert("fuel_additional", &extra.to_string().replace('.', ","));
+    if let Some(route) = &route {
         ctx.insert(
-            "fuel_additional_abs",
-            &match extra {
-                Volume::Gallon(v) => Volume::Gallon(v.abs()),
-                Volume::Liter(v) => Volume::Liter(v.abs()),
-            }
-            .to_string()
-            .replace('.', ","),
+            "route_legs",
+            &route
+                .legs
+                .iter()
+                .map(|leg| {
+                    serde_json::json!({
+                        "from": leg.from,
+                        "to": leg.to,
+                        "distance": format!("{:.0} NM", leg.distance),
+                        "course": format!("{:03.0}°", leg.course.round() % 360.0),
+                        "ground_speed": format!("{:.0} kt", leg.ground_speed),
+                        "time": format!("{:.0} min", leg.minutes),
+                    })
+                })
+                .collect::<Vec<_>>(),
         );
+        ctx.insert("route_distance", &format!("{:.0} NM", route.distance));
+        ctx.insert("route_time", &duration_to_hh_mm(&route.trip_duration()));
+
+        if let Some(destination) = route.legs.last() {
+            ctx.insert("destination", &destination.to);
+        }
+    }
+
+    if let Some((alternate, diversion)) = &diversion {
+        ctx.insert("alternate_code", &alternate.code.to_uppercase());
+        ctx.insert("alternate_name", &alternate.name);
         ctx.insert(
-            "fuel_sufficient",
-            &match extra {
-                Volume::Gallon(v) | Volume::Liter(v) => v.is_sign_positive(),
-            },
+            "alternate_distance",
+            &format!("{:.0} NM", diversion.distance),
         );
-
         ctx.insert(
-            "fuel_total",
-            &match app_state.fuel_unit {
-                Some(VolumeType::Liter) => Volume::Liter(fuel_liters),
-                Some(VolumeType::Gallon) => Volume::Gallon(Volume::Liter(fuel_liters).to_gallon()),
-                _ => panic!("should always have a value"),
-            }
-            .to_string()
-            .replace('.', ","),
+            "alternate_time",
+            &duration_to_hh_mm(&diversion.trip_duration()),
         );
-
-        ctx.insert("fuel_endurance", &duration_to_hh_mm(&endurance));
     }
 
-    ctx.insert("wb_within_limits", &plane.within_limits());
+    if let Some(before) = app_state.fuel_before {
+        let before = match app_state.fuel_unit {
+            Some(VolumeType::Gallon) => Volume::Gallon(before).to_liter(),
+            _ => before,
+        };
+        let prices = &runtime.registry.fuel_prices;
+        let money = |amount: f64| format!("{} {:.2}", prices.currency, amount).replace('.', ",");
 
-    let wind = match app_state.wind {
-        Some(w) => w.abs(),
-        None => panic!("wind should be present"),
-    };
+        let refuel = Refuel::new(before, fuel_on_board, prices.price(&fuel_type));
+        ctx.insert("fuel_uplift", &volume(refuel.uplift));
+        if let Some(cost) = refuel.cost {
+            ctx.insert("fuel_cost", &money(cost));
+        }
 
-    let wind_direction = match app_state.wind {
-        Some(w) => {
-            if w.is_sign_negative() {
-                "tailwind".to_string()
-            } else {
-                "headwind".to_string()
+        // Types that may use both grades get the cost of the other grade for comparison.
+        let other = match &fuel_type {
+            FuelType::Avgas => FuelType::Mogas,
+            FuelType::Mogas => FuelType::Avgas,
+        };
+        if aircraft_type.fuel.allows(&other) {
+            if let Some(cost) = Refuel::new(before, fuel_on_board, prices.price(&other)).cost {
+                ctx.insert("fuel_cost_alternative", &money(cost));
+                ctx.insert("fuel_grade_alternative", fuel::grade(&other));
             }
         }
-        None => panic!("wind should be present"),
-    };
-
-    let pressure_altitude = match app_state.pressure_altitude {
-        Some(pa) => pa,
-        None => panic!("pressure altitude should be present"),
-    };
-
-    let oat = match app_state.oat {
-        Some(oat) => oat,
-        None => panic!("oat should be present"),
-    };
-
-    let (_, _, _, _, _, _, _, lgrr, ldr) = calculate_aquila_performance_ldr(PerfQueryParams {
-        mtow: plane.total_mass_landing().kilo(),
-        wind,
-        wind_direction: wind_direction.clone(),
-        pressure_altitude,
-        oat,
-    });
-
-    let (_, _, _, _, _, _, _, tod_gr, tod_dr) = calculate_aquila_performance_tod(PerfQueryParams {
-        mtow: plane.total_mass().kilo(),
-        wind,
-        wind_direction,
-        pressure_altitude,
-        oat,
-    });
-
-    ctx.insert("ldr", &format!("{:.0}", ldr));
-    ctx.insert("lgrr", &format!("{:.0}", lgrr));
-    ctx.insert("torr", &format!("{:.0}", tod_gr));
-    ctx.insert("todr", &format!("{:.0}", tod_dr));
+    }
 
     ctx.insert(
-        "perf_chart_tod_image_url",
-        &format!(
-            "/perf-tod?{}&mtow={}",
-            req.query_string(),
-            &plane.total_mass().kilo()
-        ),
+        "wb_within_limits",
+        &plane::within_limits(&plane, &aircraft_type.fuel),
     );
-
     ctx.insert(
-        "wb_chart_image_url",
-        &format!("/wb-chart?{}", req.query_string()),
+        "wb_landing_within_limits",
+        &plane::within_limits_landing(&plane, &aircraft_type.fuel),
     );
 
+    let chart_query = format!(
+        "state={}{}",
+        app_state.to_token(),
+        app_state
+            .flight_date
+            .map(|d| format!("&flight_date={}", date::format_date(d)))
+            .unwrap_or_default()
+    );
+    ctx.insert("wb_chart_image_url", &format!("/wb-chart?{}", chart_query));
     ctx.insert(
-        "perf_chart_ldr_image_url",
-        &format!(
-            "/perf-ldr?{}&mtow={}",
-            req.query_string(),
-            &plane.total_mass_landing().kilo()
-        ),
+        "wb_chart_description",
+        &chart::weight_and_balance_chart(
+            &plane,
+            &aircraft_type.envelope,
+            &aircraft_type.fuel,
+            app_state.unit_system,
+            aircraft_type.chart.as_ref(),
+        )
+        .description,
+    );
+    ctx.insert(
+        "wb_chart_interactive_url",
+        &format!("/wb-chart-interactive?{}", chart_query),
     );
 
     ctx.insert(
         "wb_table",
-        &airplane::visualizer::weight_and_balance_table_strings(plane),
+        &plane::weight_and_balance_table(&plane, app_state.unit_system),
     );
 
-    let rendered = tmpl.render(template, ctx).unwrap();
-    HttpResponse::Ok().content_type("text/html").body(rendered)
+    let alternate = diversion.as_ref().map(|(airport, _)| airport);
+    let performance = aircraft_type
+        .performance_model()
+        .map(|model| performance(app_state, &plane, model, aircraft_type, alternate, ctx));
+
+    Ok(Loadsheet::new(
+        app_state,
+        &plane,
+        &weighing,
+        performance,
+        fuel_sufficient,
+    ))
 }
 
-pub fn calculate_aquila_performance_ldr(
-    query_params: PerfQueryParams,
-) -> (f64, f64, f64, f64, f64, f64, f64, f64, f64) {
-    let oat = query_params.oat;
-    let pressure_altitude = query_params.pressure_altitude;
-    let mtow = query_params.mtow;
-    let wind = query_params.wind;
-    let wind_direction = query_params.wind_direction;
-
-    let wind = if wind_direction == "headwind" {
-        wind
-    } else {
-        -wind
-    };
+/// Plans the route entered on the fuel step, `None` when the trip duration is entered instead.
+pub fn route_plan(
+    app_state: &ApplicationState,
+    runtime: &Runtime,
+) -> Option<Result<RoutePlan, String>> {
+    let route = app_state.route.as_deref()?;
+
+    let waypoints = route
+        .split_whitespace()
+        .map(|w| {
+            runtime.airports.waypoint(w).map_err(|_| {
+                format!(
+                    "Routepunt {} is geen bekende luchthaven en geen positie als \
+                     breedte,lengte.",
+                    w
+                )
+            })
+        })
+        .collect::<Result<Vec<Waypoint>, String>>();
 
-    let oat_x_start = 562.923177;
-    let oat_x_end = 1870.93099;
-    let oat_x_units = 70.0;
-
-    let oat_y = [
-        (
-            0.0,
-            [
-                1902.34375,
-                1948.339844,
-                1994.335938,
-                2042.317708,
-                2090.332031,
-                2136.328125,
-                2184.342448,
-                2234.342448,
-            ],
-        ),
-        (
-            2000.0,
-            [
-                2002.34375,
-                2054.329427,
-                2104.329427,
-                2158.333333,
-                2210.31901,
-                2262.33724,
-                2316.341146,
-                2370.345052,
-            ],
-        ),
-        (
-            4000.0,
-            [
-                2114.322917,
-                2172.330729,
-                2228.320313,
-                2286.328125,
-                2344.335938,
-                2404.329427,
-                2462.33724,
-                2522.330729,
-            ],
-        ),
-        (
-            6000.0,
-            [
-                2242.317708,
-                2304.329427,
-                2368.326823,
-                2432.324219,
-                2498.339844,
-                2562.33724,
-                2628.320313,
-                2694.335938,
-            ],
-        ),
-        (
-            8000.0,
-            [
-                2384.342448,
-                2454.329427,
-                2526.334635,
-                2598.339844,
-                2670.345052,
-                2742.317708,
-                2814.322917,
-                2888.313802,
-            ],
-        ),
-    ];
+    Some(waypoints.and_then(|waypoints| {
+        if waypoints.len() < 2 {
+            return Err("Een route heeft minstens twee routepunten.".to_string());
+        }
 
-    let y_bracket = ((oat + 30.0) / 10.0) as usize;
-    let y_interpolated = interpolate_y_values(pressure_altitude, &oat_y, y_bracket);
+        let Some(tas) = app_state.route_tas.filter(|t| *t > 0.0) else {
+            return Err("Vul de kruissnelheid (TAS) in.".to_string());
+        };
 
-    let p_oat_x = (oat_x_end - oat_x_start) / oat_x_units;
-    let y_offset = (oat + 30.0) % 10.0;
-    let p_oat_y = (y_interpolated.1 - y_interpolated.0) / 10.0;
+        if app_state.route_wind.speed < 0.0 {
+            return Err("De windsnelheid mag niet negatief zijn.".to_string());
+        }
 
-    let tom_x_start = 2077.115885;
-    let tom_x_end = 3263.216146;
-    let tom_units = 750.0 - 550.0;
-    let tom_x_offset = (750.0 - mtow) * ((tom_x_end - tom_x_start) / tom_units) + tom_x_start;
+        RoutePlan::new(&waypoints, tas, app_state.route_wind)
+            .map_err(|_| "Met deze wind is de route niet te vliegen.".to_string())
+    }))
+}
 
-    let tom = if y_interpolated.0 + (p_oat_y * y_offset) <= 2002.083333 {
-        (
-            (0.0, 200.0, 1906.054688, 1796.061198),
-            (0.0, 200.0, 2002.083333, 1882.063802),
-        )
-    } else if y_interpolated.0 + (p_oat_y * y_offset) <= 2112.076823 {
-        (
-            (0.0, 200.0, 2002.083333, 1882.063802),
-            (0.0, 200.0, 2112.076823, 1978.059896),
-        )
-    } else if y_interpolated.0 + (p_oat_y * y_offset) <= 2232.096354 {
-        (
-            (0.0, 200.0, 2112.076823, 1978.059896),
-            (0.0, 200.0, 2232.096354, 2074.088542),
-        )
-    } else {
-        (
-            (0.0, 200.0, 2232.096354, 2074.088542),
-            (0.0, 200.0, 2368.098958, 2192.089844),
-        )
-    };
+/// Plans the diversion from the destination of the route to the alternate selected on the fuel
+/// step, `None` when the alternate duration is entered instead.
+pub fn diversion_plan(
+    app_state: &ApplicationState,
+    runtime: &Runtime,
+) -> Option<Result<(Airport, RoutePlan), String>> {
+    let code = app_state.alternate.as_deref()?;
 
-    let tom_y_pos = interpolate_tom_y(tom, mtow, y_interpolated, p_oat_y, y_offset);
-
-    let wind_x_start = 3439.388021;
-    let wind_x_end = 4933.561198;
-    let (wind_x_pos, wind_y_pos) =
-        calculate_wind_position_ldr(wind_x_start, wind_x_end, wind, tom_y_pos, tom_x_offset);
-
-    let obs = ((1467.545573, 1171.484375), (1631.608073, 1241.503906));
-    let gr_y_pos = interpolate_obstacle_y(wind_y_pos, obs);
-
-    let perf_y_start = 965.46224;
-    let perf_y_end = 3261.946615;
-    let perf_units = 1000.0;
-
-    let ldr_gr = [
-        1171.484375,
-        1241.503906,
-        1309.53776,
-        1379.557292,
-        1447.558594,
-        1517.578125,
-        1585.579427,
-        1653.613281,
-        1723.632813,
-        1791.634115,
-        1861.653646,
-        1929.654948,
-        1999.674479,
-    ]
-    .iter()
-    .find(|&&x| x >= gr_y_pos)
-    .unwrap_or(&perf_y_end);
-
-    let ldr_dr = [
-        1467.545573,
-        1631.608073,
-        1797.65625,
-        1961.686198,
-        2125.716146,
-        2289.746094,
-        2453.776042,
-        2617.80599,
-        2781.835938,
-        2947.884115,
-        3111.914063,
-        3275.94401,
-        3440.00651,
-    ]
-    .iter()
-    .find(|&&x| x >= wind_y_pos)
-    .unwrap_or(&perf_y_end);
-
-    let ldr_gr = (ldr_gr - perf_y_start) / (perf_y_end - perf_y_start) * perf_units;
-    let ldr_dr = (ldr_dr - perf_y_start) / (perf_y_end - perf_y_start) * perf_units;
-
-    (
-        oat_x_start + (p_oat_x * (oat + 30.0)),
-        y_interpolated.0 + (p_oat_y * y_offset),
-        tom_x_offset,
-        tom_y_pos,
-        wind_x_pos,
-        wind_y_pos,
-        gr_y_pos,
-        ldr_gr,
-        ldr_dr,
-    )
+    Some(plan_diversion(code, app_state, runtime))
 }
 
-pub fn calculate_aquila_performance_tod(
-    query_params: PerfQueryParams,
-) -> (f64, f64, f64, f64, f64, f64, f64, f64, f64) {
-    let oat = query_params.oat;
-    let pressure_altitude = query_params.pressure_altitude;
-    let mtow = query_params.mtow;
-    let wind = query_params.wind;
-    let wind_direction = query_params.wind_direction;
-
-    let wind = if wind_direction == "headwind" {
-        wind
-    } else {
-        -wind
+fn plan_diversion(
+    code: &str,
+    app_state: &ApplicationState,
+    runtime: &Runtime,
+) -> Result<(Airport, RoutePlan), String> {
+    let alternate = runtime
+        .airports
+        .find(code)
+        .ok_or_else(|| format!("Uitwijkhaven {} is geen bekende luchthaven.", code))?;
+
+    let Some(destination) = app_state.destination() else {
+        return Err("Een uitwijkhaven kan alleen samen met een route worden gekozen.".to_string());
     };
+    let destination = runtime.airports.waypoint(destination)?;
+    let alternate_waypoint = Waypoint::from(alternate);
+    if destination.name == alternate_waypoint.name {
+        return Err("De uitwijkhaven moet een ander vliegveld zijn dan de bestemming.".to_string());
+    }
 
-    let oat_x_start = 562.923177;
-    let oat_x_end = 2168.91276;
-    let oat_x_units = 70.0;
-
-    let oat_y = [
-        (
-            0.0,
-            [
-                1614.322917,
-                1656.315104,
-                1698.339844,
-                1742.317708,
-                1788.313802,
-                1834.342448,
-                1882.324219,
-                1932.324219,
-            ],
-        ),
-        (
-            2000.0,
-            [
-                1702.34375,
-                1750.325521,
-                1800.325521,
-                1850.325521,
-                1902.34375,
-                1956.315104,
-                2010.31901,
-                2066.341146,
-            ],
-        ),
-        (
-            4000.0,
-            [
-                1804.329427,
-                1860.31901,
-                1916.341146,
-                1974.316406,
-                2034.342448,
-                2096.321615,
-                2160.31901,
-                2224.316406,
-            ],
-        ),
-        (
-            6000.0,
-            [
-                1924.316406,
-                1988.313802,
-                2052.34375,
-                2120.345052,
-                2190.332031,
-                2262.33724,
-                2334.342448,
-                2410.31901,
-            ],
-        ),
-        (
-            8000.0,
-            [
-                2064.322917,
-                2138.313802,
-                2214.322917,
-                2292.317708,
-                2372.330729,
-                2456.315104,
-                2540.332031,
-                2628.320313,
-            ],
-        ),
-    ];
-
-    let y_bracket = ((oat + 30.0) / 10.0) as usize;
-    let y_interpolated = interpolate_y_values(pressure_altitude, &oat_y, y_bracket);
-
-    let p_oat_x = (oat_x_end - oat_x_start) / oat_x_units;
-    let y_offset = (oat + 30.0) % 10.0;
-    let p_oat_y = (y_interpolated.1 - y_interpolated.0) / 10.0;
-
-    let tom_x_start = 2367.122396;
-    let tom_x_end = 3777.246094;
-    let tom_units = 750.0 - 550.0;
-    let tom_x_offset = (750.0 - mtow) * ((tom_x_end - tom_x_start) / tom_units) + tom_x_start;
+    let Some(tas) = app_state.route_tas.filter(|t| *t > 0.0) else {
+        return Err("Vul de kruissnelheid (TAS) in.".to_string());
+    };
 
-    let tom = (
-        (0.0, 200.0, 1632.03125, 1400.032552),
-        (0.0, 200.0, 1718.033854, 1454.003906),
-    );
-    let tom_y_pos = interpolate_tom_y(tom, mtow, y_interpolated, p_oat_y, y_offset);
-    let wind_x_start = 3965.429687;
-    let wind_x_end = 5211.621094;
-    let (wind_x_pos, wind_y_pos) =
-        calculate_wind_position_tod(wind_x_start, wind_x_end, wind, tom_y_pos, tom_x_offset);
-
-    let obs = ((1395.703125, 1727.766927), (1491.731771, 1905.794271));
-    let obs_y_pos = interpolate_obstacle_y(wind_y_pos, obs);
-
-    let perf_y_start = 1009.635417;
-    let perf_y_end = 4222.200521;
-    let perf_units = 1000.0;
-
-    let tor_gr = [
-        1395.703125,
-        1491.731771,
-        1587.727865,
-        1683.75651,
-        1779.785156,
-        1877.799479,
-        1973.795573,
-        2069.824219,
-        2165.852865,
-        2261.848958,
-        2359.895833,
-        2455.891927,
-        2551.920573,
-        2655.924479,
-    ]
-    .iter()
-    .find(|&&x| x >= wind_y_pos)
-    .unwrap_or(&perf_y_end);
-
-    let tor_dr = [
-        1727.766927,
-        1905.794271,
-        2085.839844,
-        2265.852865,
-        2443.880208,
-        2623.925781,
-        2803.938802,
-        2983.984375,
-        3162.011719,
-        3342.057292,
-        3522.070313,
-        3700.097656,
-        3880.143229,
-        4076.171875,
-    ]
-    .iter()
-    .find(|&&x| x >= obs_y_pos)
-    .unwrap_or(&perf_y_end);
-
-    let tod_gr = (tor_gr - perf_y_start) / (perf_y_end - perf_y_start) * perf_units;
-    let tod_dr = (tor_dr - perf_y_start) / (perf_y_end - perf_y_start) * perf_units;
-
-    (
-        oat_x_start + (p_oat_x * (oat + 30.0)),
-        y_interpolated.0 + (p_oat_y * y_offset),
-        tom_x_offset,
-        tom_y_pos,
-        wind_x_pos,
-        wind_y_pos,
-        obs_y_pos,
-        tod_gr,
-        tod_dr,
+    RoutePlan::new(
+        &[destination, alternate_waypoint],
+        tas,
+        app_state.route_wind,
     )
+    .map(|plan| (alternate.clone(), plan))
+    .map_err(|_| "Met deze wind is de uitwijkhaven niet te bereiken.".to_string())
 }
 
-fn interpolate_y_values(
-    pressure_altitude: f64,
-    oat_y: &[(f64, [f64; 8])],
-    y_bracket: usize,
-) -> (f64, f64) {
-    let y = if pressure_altitude <= 2000.0 {
-        (oat_y[0], oat_y[1])
-    } else if pressure_altitude <= 4000.0 {
-        (oat_y[1], oat_y[2])
-    } else if pressure_altitude <= 6000.0 {
-        (oat_y[2], oat_y[3])
-    } else if pressure_altitude <= 8000.0 {
-        (oat_y[3], oat_y[4])
-    } else {
-        panic!("not within range");
+/// Shows the fuel step again with the errors of its input.
+pub fn fuel_errors(
+    app_state: &ApplicationState,
+    runtime: &Runtime,
+    errors: &[String],
+) -> HttpResponse {
+    let mut ctx = tera::Context::new();
+    app_state.apply("fuel", &mut ctx);
+    ctx.insert("errors", errors);
+    render(&runtime.tera, "fuel_form.html", &ctx, "text/html")
+}
+
+/// Weighing report of the aircraft valid on the flight date. Without a valid report no
+/// loadsheet can be made, the error explains why.
+fn weighing(
+    app_state: &ApplicationState,
+    runtime: &Runtime,
+    aircraft: &Aircraft,
+) -> Result<Weighing, String> {
+    // The flight date is only entered on the export step, the steps before use today.
+    let flight_date = app_state.flight_date.unwrap_or_else(date::today);
+    let on = match app_state.flight_date {
+        Some(d) => date::format_date(d),
+        None => format!("vandaag ({})", date::format_date(flight_date)),
+    };
+    let Some(weighing) = aircraft.weighing(Some(flight_date)) else {
+        return Err(format!(
+            "Er is geen weegrapport van {} geldig op {}.",
+            aircraft.callsign, on
+        ));
     };
 
-    let y_factor = (pressure_altitude - y.0 .0) / (y.1 .0 - y.0 .0);
-    (
-        interpolate(y.0 .1[y_bracket], y.1 .1[y_bracket], y_factor),
-        interpolate(
-            y.0 .1[if y_bracket + 1 < y.0 .1.len() {
-                y_bracket + 1
-            } else {
-                y_bracket
-            }],
-            y.1 .1[if y_bracket + 1 < y.1 .1.len() {
-                y_bracket + 1
-            } else {
-                y_bracket
-            }],
-            y_factor,
-        ),
-    )
+    let policy = &runtime.registry.weighing_policy;
+    if !policy.valid(&weighing, flight_date) {
+        return Err(match (&weighing.date, &weighing.reference) {
+            (Some(date), Some(reference)) => format!(
+                "Het weegrapport van {} ({}, {}) is op {} ouder dan {} maanden, er kan geen \
+                 loadsheet worden gemaakt.",
+                aircraft.callsign,
+                reference,
+                date,
+                on,
+                policy.validity_months.unwrap_or_default()
+            ),
+            _ => format!(
+                "Van {} is geen gedateerd weegrapport bekend, er kan geen loadsheet worden \
+                 gemaakt.",
+                aircraft.callsign
+            ),
+        });
+    }
+
+    Ok(weighing)
 }
 
-fn interpolate(start: f64, end: f64, factor: f64) -> f64 {
-    start + (end - start) * factor
+/// Builds the plane of the state with the empty mass of the given weighing report, the state
+/// should have passed the fuel step.
+fn build_plane(
+    app_state: &ApplicationState,
+    aircraft: &Aircraft,
+    weighing: &Weighing,
+    runtime: &Runtime,
+) -> Airplane {
+    let aircraft_type = runtime.registry.type_of(aircraft);
+
+    plane::build_plane(PlaneInput {
+        aircraft,
+        aircraft_type,
+        weighing,
+        equipment: &app_state.equipment,
+        fuel_policy: &runtime.registry.fuel_policy(aircraft_type),
+        loads: app_state.station_moments(),
+        fuel_type: app_state.fuel_type.clone().expect("should have a fuel type"),
+        fuel_unit: app_state.fuel_unit.clone().expect("should have a fuel unit"),
+        fuel_extra: app_state.fuel_extra.clone(),
+        fuel_max: app_state.fuel_max.unwrap_or_default(),
+        trip_duration: app_state.trip_duration.expect("should have duration"),
+    })
 }
 
-fn interpolate_tom_y(
-    tom: ((f64, f64, f64, f64), (f64, f64, f64, f64)),
-    mtow: f64,
-    y_interpolated: (f64, f64),
-    p_oat_y: f64,
-    y_offset: f64,
-) -> f64 {
-    let tom_y = (
-        interpolate(tom.0 .2, tom.0 .3, (750.0 - mtow) / (tom.0 .1 - tom.0 .0)),
-        interpolate(tom.1 .2, tom.1 .3, (750.0 - mtow) / (tom.1 .1 - tom.1 .0)),
+/// Reads the take-off and landing distances from the charts of the performance model and adds
+/// them and the links to the charts to the context.
+fn performance(
+    app_state: &ApplicationState,
+    plane: &Airplane,
+    model: &dyn PerformanceModel,
+    aircraft_type: &AircraftType,
+    alternate: Option<&Airport>,
+    ctx: &mut tera::Context,
+) -> Performance {
+    let (takeoff_params, landing_params) = chart_conditions(
+        app_state,
+        plane.total_mass().kilo(),
+        plane.total_mass_landing().kilo(),
     );
-    interpolate(
-        tom_y.0,
-        tom_y.1,
-        (y_interpolated.0 + (p_oat_y * y_offset) - tom.0 .2) / (tom.1 .2 - tom.0 .2),
-    )
-}
+    let wind = takeoff_params.wind;
+    let wind_direction = takeoff_params.wind_direction.clone();
+    let landing_wind = app_state
+        .landing_performance_wind()
+        .expect("wind should be present");
+    let landing_oat = landing_params.oat;
+    let landing_pressure_altitude = landing_params.pressure_altitude;
+
+    let landing = model.landing(landing_params.clone());
+    let takeoff = model.takeoff(takeoff_params.clone());
+
+    // The alternate is calculated at the landing mass at the destination, which is never
+    // lighter, at its elevation with the QNH, temperature and wind of the destination.
+    let alternate_landing = alternate.and_then(|airport| {
+        let qnh = app_state.landing_qnh.unwrap_or(charts::STANDARD_QNH);
+        let params = PerfQueryParams {
+            pressure_altitude: charts::pressure_altitude(airport.elevation?, qnh),
+            ..landing_params.clone()
+        };
+        params.validate(&model.scale()).ok()?;
+        Some(model.landing(params))
+    });
 
-fn calculate_wind_position_ldr(
-    wind_x_start: f64,
-    wind_x_end: f64,
-    wind: f64,
-    tom_y_pos: f64,
-    tom_x_offset: f64,
-) -> (f64, f64) {
-    let mut wind_x_pos = tom_x_offset;
-    let mut wind_y_pos = tom_y_pos;
-
-    if wind != 0.0 {
-        let wind_units = 20.0;
-        let wind_x_offset = wind.abs() * ((wind_x_end - wind_x_start) / wind_units) + wind_x_start;
-        let mut wind_offset = wind;
-
-        let initial_factor = (
-            (0.0, 10.0, 1787.923177, 1599.902344),
-            (0.0, 10.0, 2173.958333, 1897.916667),
+    if app_state.has_landing_conditions() {
+        ctx.insert(
+            "landing_conditions",
+            &format!(
+                "{} °C, drukhoogte {:.0} ft, {} van {} kt",
+                landing_oat,
+                landing_pressure_altitude,
+                if landing_wind < 0.0 {
+                    "rugwind"
+                } else {
+                    "tegenwind"
+                },
+                landing_wind.abs()
+            ),
         );
+    }
 
-        let wind_d = if (0.0..=10.0).contains(&wind) {
-            initial_factor
-        } else if wind > 10.0 && wind <= 15.0 {
-            wind_offset = wind % 10.0;
-            (
-                (0.0, 5.0, 1599.902344, 1527.864583),
-                (0.0, 5.0, 1897.916667, 1791.894531),
-            )
-        } else if (-10.0..0.0).contains(&wind) {
-            if tom_y_pos <= 1640.891927 {
-                (
-                    (0.0, 10.0, 1389.84375, 1525.84375),
-                    (0.0, 10.0, 1640.891927, 1867.0),
-                )
-            } else if tom_y_pos <= 1958.915365 {
-                (
-                    (0.0, 10.0, 1640.891927, 1867.0),
-                    (0.0, 10.0, 1958.915365, 2300.0),
-                )
-            } else {
-                (
-                    (0.0, 10.0, 1958.915365, 2300.0),
-                    (0.0, 10.0, 2262.979167, 2710.0),
-                )
-            }
-        } else {
-            wind_offset = wind % 15.0;
-            (
-                (0.0, 5.0, 1527.864583, 1471.875),
-                (0.0, 5.0, 1791.894531, 1709.895833),
-            )
-        };
-
-        let wind_low = wind_d.0 .2
-            - ((wind_d.0 .2 - wind_d.0 .3) / (wind_d.0 .1 - wind_d.0 .0)) * wind_offset.abs();
-        let wind_high = wind_d.1 .2
-            - ((wind_d.1 .2 - wind_d.1 .3) / (wind_d.1 .1 - wind_d.1 .0)) * wind_offset.abs();
+    if app_state.gust.is_some() {
+        ctx.insert("performance_wind", &wind);
+        ctx.insert("performance_wind_direction", &wind_direction);
+    }
 
-        let wind_factor = if wind >= 0.0 {
-            (tom_y_pos - initial_factor.0 .2) / (initial_factor.1 .2 - initial_factor.0 .2)
-        } else {
-            (tom_y_pos - wind_d.0 .2) / (wind_d.1 .2 - wind_d.0 .2)
-        };
+    let units = app_state.unit_system;
+    ctx.insert(
+        "ldr",
+        &format!("{:.0}", units.distance_from_meter(landing.distance)),
+    );
+    ctx.insert(
+        "lgrr",
+        &format!("{:.0}", units.distance_from_meter(landing.ground_roll)),
+    );
+    ctx.insert(
+        "torr",
+        &format!("{:.0}", units.distance_from_meter(takeoff.ground_roll)),
+    );
+    ctx.insert(
+        "todr",
+        &format!("{:.0}", units.distance_from_meter(takeoff.distance)),
+    );
 
-        wind_y_pos = (wind_high - wind_low) * wind_factor + wind_low;
-        wind_x_pos = wind_x_offset.abs();
+    match (alternate, &alternate_landing) {
+        (_, Some(landing)) => {
+            ctx.insert(
+                "alternate_ldr",
+                &format!("{:.0}", units.distance_from_meter(landing.distance)),
+            );
+            ctx.insert(
+                "alternate_lgrr",
+                &format!("{:.0}", units.distance_from_meter(landing.ground_roll)),
+            );
+        }
+        (Some(airport), _) => ctx.insert(
+            "alternate_performance_error",
+            &match airport.elevation {
+                Some(_) => format!(
+                    "De landing op {} valt buiten het bereik van de prestatiegrafieken.",
+                    airport.code.to_uppercase()
+                ),
+                None => format!(
+                    "Van {} is geen hoogte bekend, bepaal de landingsafstand met het \
+                     vlieghandboek.",
+                    airport.code.to_uppercase()
+                ),
+            },
+        ),
+        (None, None) => (),
     }
 
-    (wind_x_pos, wind_y_pos)
-}
+    let perf_query = |params: &PerfQueryParams| {
+        format!(
+            "type={}&units={}&oat={}&pressure_altitude={}&wind={}&wind_direction={}&mtow={}",
+            aircraft_type.id,
+            units.as_str(),
+            params.oat,
+            params.pressure_altitude,
+            params.wind,
+            params.wind_direction,
+            params.mtow
+        )
+    };
 
-fn calculate_wind_position_tod(
-    wind_x_start: f64,
-    wind_x_end: f64,
-    wind: f64,
-    tom_y_pos: f64,
-    tom_x_offset: f64,
-) -> (f64, f64) {
-    let mut wind_x_pos = tom_x_offset;
-    let mut wind_y_pos = tom_y_pos;
-
-    if wind != 0.0 {
-        let wind_units = 20.0;
-        let wind_x_offset = wind.abs() * ((wind_x_end - wind_x_start) / wind_units) + wind_x_start;
-        let mut wind_offset = wind;
-
-        let initial_factor = (
-            (0.0, 10.0, 1389.84375, 1303.841146),
-            (0.0, 10.0, 1655.891927, 1507.877604),
-        );
+    ctx.insert(
+        "perf_chart_tod_image_url",
+        &format!("/perf-tod?{}", perf_query(&takeoff_params)),
+    );
+    ctx.insert(
+        "perf_chart_tod_description",
+        &chart_description(
+            &Chart::Takeoff,
+            &takeoff_params,
+            &takeoff,
+            aircraft_type,
+            units,
+        ),
+    );
 
-        let wind_d = if (0.0..=10.0).contains(&wind) {
-            initial_factor
-        } else if wind > 10.0 && wind <= 15.0 {
-            wind_offset = wind % 10.0;
-            (
-                (0.0, 5.0, 1303.841146, 1269.856771),
-                (0.0, 5.0, 1507.877604, 1449.869792),
-            )
-        } else if (-10.0..0.0).contains(&wind) {
-            if tom_y_pos <= 1640.891927 {
-                (
-                    (0.0, 10.0, 1389.84375, 1525.84375),
-                    (0.0, 10.0, 1640.891927, 1867.0),
-                )
-            } else if tom_y_pos <= 1958.915365 {
-                (
-                    (0.0, 10.0, 1640.891927, 1867.0),
-                    (0.0, 10.0, 1958.915365, 2300.0),
-                )
-            } else {
-                (
-                    (0.0, 10.0, 1958.915365, 2300.0),
-                    (0.0, 10.0, 2262.979167, 2710.0),
-                )
-            }
-        } else {
-            wind_offset = wind % 15.0;
-            (
-                (0.0, 5.0, 1269.856771, 1243.847656),
-                (0.0, 5.0, 1449.869792, 1407.845052),
-            )
-        };
+    ctx.insert(
+        "perf_chart_ldr_image_url",
+        &format!("/perf-ldr?{}", perf_query(&landing_params)),
+    );
+    ctx.insert(
+        "perf_chart_ldr_description",
+        &chart_description(
+            &Chart::Landing,
+            &landing_params,
+            &landing,
+            aircraft_type,
+            units,
+        ),
+    );
 
-        let wind_low = wind_d.0 .2
-            - ((wind_d.0 .2 - wind_d.0 .3) / (wind_d.0 .1 - wind_d.0 .0)) * wind_offset.abs();
-        let wind_high = wind_d.1 .2
-            - ((wind_d.1 .2 - wind_d.1 .3) / (wind_d.1 .1 - wind_d.1 .0)) * wind_offset.abs();
+    Performance {
+        torr: takeoff.ground_roll,
+        todr: takeoff.distance,
+        lgrr: landing.ground_roll,
+        ldr: landing.distance,
+        alternate: alternate_landing.map(|l| (l.ground_roll, l.distance)),
+    }
+}
 
-        let wind_factor = if wind >= 0.0 {
-            (tom_y_pos - initial_factor.0 .2) / (initial_factor.1 .2 - initial_factor.0 .2)
+/// Conditions of the take-off and landing charts of the state at the given masses in kilograms,
+/// the landing conditions at the destination default to those at departure.
+fn chart_conditions(
+    app_state: &ApplicationState,
+    takeoff_mass: f64,
+    landing_mass: f64,
+) -> (PerfQueryParams, PerfQueryParams) {
+    let direction = |wind: f64| {
+        if wind.is_sign_negative() {
+            "tailwind".to_string()
         } else {
-            (tom_y_pos - wind_d.0 .2) / (wind_d.1 .2 - wind_d.0 .2)
-        };
+            "headwind".to_string()
+        }
+    };
 
-        wind_y_pos = (wind_high - wind_low) * wind_factor + wind_low;
-        wind_x_pos = wind_x_offset.abs();
-    }
+    let wind = app_state.performance_wind().expect("wind should be present");
+    let takeoff = PerfQueryParams {
+        mtow: takeoff_mass,
+        wind: wind.abs(),
+        wind_direction: direction(wind),
+        pressure_altitude: app_state
+            .pressure_altitude
+            .expect("pressure altitude should be present"),
+        oat: app_state.oat.expect("oat should be present"),
+    };
 
-    (wind_x_pos, wind_y_pos)
-}
+    let landing_wind = app_state
+        .landing_performance_wind()
+        .expect("wind should be present");
+    let landing = PerfQueryParams {
+        mtow: landing_mass,
+        wind: landing_wind.abs(),
+        wind_direction: direction(landing_wind),
+        pressure_altitude: app_state
+            .landing_pressure_altitude()
+            .expect("pressure altitude should be present"),
+        oat: app_state.landing_oat().expect("oat should be present"),
+    };
 
-fn interpolate_obstacle_y(wind_y_pos: f64, obs: ((f64, f64), (f64, f64))) -> f64 {
-    let obs_factor = (wind_y_pos - obs.0 .0) / (obs.1 .0 - obs.0 .0);
-    obs.0 .1 + (obs_factor) * (obs.1 .1 - obs.0 .1)
+    (takeoff, landing)
 }
 
 pub async fn perf_ldr(
-    query: web::Query<PerfQueryParams>,
-    _req: HttpRequest,
-    tmpl: web::Data<Tera>,
+    query: Option<web::Query<PerfQueryParams>>,
+    state: web::Query<IndexQueryParams>,
+    chart: web::Query<ChartQueryParams>,
+    req: HttpRequest,
+    runtime: CurrentRuntime,
 ) -> impl Responder {
-    let mut ctx = tera::Context::new();
+    performance_chart(
+        query.map(|q| q.into_inner()),
+        state.into_inner(),
+        &chart,
+        Theme::from_request(&req),
+        &runtime,
+        Chart::Landing,
+    )
+}
+
+pub async fn perf_tod(
+    query: Option<web::Query<PerfQueryParams>>,
+    state: web::Query<IndexQueryParams>,
+    chart: web::Query<ChartQueryParams>,
+    req: HttpRequest,
+    runtime: CurrentRuntime,
+) -> impl Responder {
+    performance_chart(
+        query.map(|q| q.into_inner()),
+        state.into_inner(),
+        &chart,
+        Theme::from_request(&req),
+        &runtime,
+        Chart::Takeoff,
+    )
+}
 
-    let (oat_x_base, oat_y_base, tom_x_offset, tom_y_pos, wind_x_pos, wind_y_pos, obs_y_pos, _, _) =
-        calculate_aquila_performance_ldr(query.into_inner());
-
-    ctx.insert("oat_x_base", &format!("{:.5}", oat_x_base));
-    ctx.insert("oat_y_base", &format!("{:.5}", oat_y_base));
-    ctx.insert("tom_x", &format!("{:.5}", tom_x_offset));
-    ctx.insert("tom_y", &format!("{:.5}", tom_y_pos,));
-    ctx.insert("wind_x", &format!("{:.5}", wind_x_pos));
-    ctx.insert("wind_y", &format!("{:.5}", wind_y_pos));
-    ctx.insert("ob_y", &format!("{:.5}", obs_y_pos));
-
-    let rendered = tmpl.render("ld.svg", &ctx).unwrap();
-    HttpResponse::Ok()
-        .content_type("image/svg+xml")
-        .body(rendered)
+enum Chart {
+    Takeoff,
+    Landing,
 }
 
-pub async fn perf_tod(query: web::Query<PerfQueryParams>, tmpl: web::Data<Tera>) -> impl Responder {
+/// Draws the construction line on the take-off or landing chart of the aircraft type. Links
+/// with a state token get the conditions and mass of the flight in the token, the other links
+/// give them in the query.
+fn performance_chart(
+    params: Option<PerfQueryParams>,
+    state: IndexQueryParams,
+    chart: &ChartQueryParams,
+    theme: Theme,
+    runtime: &Runtime,
+    kind: Chart,
+) -> HttpResponse {
+    let (params, type_id, units) = match (state.state.is_some(), params) {
+        (true, _) => match state_conditions(state, runtime, &kind) {
+            Ok(c) => c,
+            Err(e) => return HttpResponse::BadRequest().body(e),
+        },
+        (false, Some(params)) => (
+            params,
+            chart
+                .aircraft_type
+                .clone()
+                .unwrap_or_else(|| DEFAULT_AIRCRAFT_TYPE.to_string()),
+            UnitSystem::default(),
+        ),
+        (false, None) => {
+            return HttpResponse::BadRequest().body("Missing conditions for the chart");
+        }
+    };
+
+    let Some((aircraft_type, model)) = runtime
+        .registry
+        .aircraft_type(&type_id)
+        .and_then(|t| Some((t, t.performance_model()?)))
+    else {
+        return HttpResponse::BadRequest().body("No performance charts for this aircraft type");
+    };
+
+    if let Err(e) = params.validate(&model.scale()) {
+        return HttpResponse::BadRequest().body(e);
+    }
+
+    let units = chart
+        .units
+        .as_deref()
+        .and_then(UnitSystem::parse)
+        .unwrap_or(units);
+    let (takeoff_template, landing_template) = model.chart_templates();
+    let (reading, template) = match kind {
+        Chart::Takeoff => (model.takeoff(params.clone()), takeoff_template),
+        Chart::Landing => (model.landing(params.clone()), landing_template),
+    };
+    let line = &reading.line;
+
     let mut ctx = tera::Context::new();
+    ctx.insert(
+        "description",
+        &chart_description(&kind, &params, &reading, aircraft_type, units),
+    );
+    ctx.insert("chart_style", &theme.chart_style());
+    ctx.insert("oat_x_base", &format!("{:.5}", line.oat_x_base));
+    ctx.insert("oat_y_base", &format!("{:.5}", line.oat_y_base));
+    ctx.insert("tom_x", &format!("{:.5}", line.tom_x));
+    ctx.insert("tom_y", &format!("{:.5}", line.tom_y));
+    ctx.insert("wind_x", &format!("{:.5}", line.wind_x));
+    ctx.insert("wind_y", &format!("{:.5}", line.wind_y));
+    ctx.insert("ob_y", &format!("{:.5}", line.ob_y));
+
+    render(&runtime.tera, template, &ctx, "image/svg+xml")
+}
+
+/// Conditions and mass of the take-off or landing of the flight in the state token, with its
+/// aircraft type and units. The flight is calculated as on the results step, a state that does
+/// not get there has no chart.
+fn state_conditions(
+    state: IndexQueryParams,
+    runtime: &Runtime,
+    kind: &Chart,
+) -> Result<(PerfQueryParams, String, UnitSystem), String> {
+    let (app_state, _) = ApplicationState::from_query_params(state, &runtime.registry);
+    // Without the fuel step there is no flight to calculate yet.
+    if app_state.fuel_type.is_none() || app_state.fuel_unit.is_none() {
+        return Err("The state is not complete".to_string());
+    }
+    let loadsheet = calculate(&app_state, &mut tera::Context::new(), runtime)
+        .map_err(|_| "The state is not complete".to_string())?;
+
+    let (takeoff, landing) = chart_conditions(
+        &app_state,
+        loadsheet.results.takeoff_mass,
+        loadsheet.results.landing_mass,
+    );
+    let params = match kind {
+        Chart::Takeoff => takeoff,
+        Chart::Landing => landing,
+    };
+
+    Ok((
+        params,
+        app_state.aircraft_type.id.clone(),
+        app_state.unit_system,
+    ))
+}
+
+/// Text alternative of a performance chart with the conditions, the distances read from it and
+/// whether a tailwind is within the limit of the flight manual.
+fn chart_description(
+    kind: &Chart,
+    params: &PerfQueryParams,
+    reading: &ChartReading,
+    aircraft_type: &AircraftType,
+    units: UnitSystem,
+) -> String {
+    let (ground_roll, distance) = match kind {
+        Chart::Takeoff => ("startrol", "startafstand"),
+        Chart::Landing => ("landingsrol", "landingsafstand"),
+    };
+    let tailwind = params.wind_direction == "tailwind";
+
+    let mut description = format!(
+        "Bij {} °C, drukhoogte {:.0} ft, {:.0} {} en {} kt {}: {} {:.0} {}, {} over 15 m {:.0} {}.",
+        params.oat,
+        params.pressure_altitude,
+        units.mass_from_kilo(params.mtow),
+        units.mass_unit(),
+        params.wind,
+        if tailwind { "rugwind" } else { "tegenwind" },
+        ground_roll,
+        units.distance_from_meter(reading.ground_roll),
+        units.distance_unit(),
+        distance,
+        units.distance_from_meter(reading.distance),
+        units.distance_unit()
+    );
 
-    let (oat_x_base, oat_y_base, tom_x_offset, tom_y_pos, wind_x_pos, wind_y_pos, obs_y_pos, _, _) =
-        calculate_aquila_performance_tod(query.into_inner());
-
-    ctx.insert("oat_x_base", &format!("{:.5}", oat_x_base));
-    ctx.insert("oat_y_base", &format!("{:.5}", oat_y_base));
-    ctx.insert("tom_x", &format!("{:.5}", tom_x_offset));
-    ctx.insert("tom_y", &format!("{:.5}", tom_y_pos,));
-    ctx.insert("wind_x", &format!("{:.5}", wind_x_pos));
-    ctx.insert("wind_y", &format!("{:.5}", wind_y_pos));
-    ctx.insert("ob_y", &format!("{:.5}", obs_y_pos));
-
-    let rendered = tmpl.render("top.svg", &ctx).unwrap();
-    HttpResponse::Ok()
-        .content_type("image/svg+xml")
-        .body(rendered)
+    let max_tailwind = aircraft_type.wind_limits.max_tailwind;
+    if tailwind && params.wind > 0.0 {
+        description.push_str(&format!(
+            " De rugwind is {} het maximum van {} kt uit het vlieghandboek.",
+            if params.wind <= max_tailwind {
+                "binnen"
+            } else {
+                "boven"
+            },
+            max_tailwind
+        ));
+    }
+
+    description
 }
 
 pub async fn wb_table(
     query: web::Query<IndexQueryParams>,
-    _tmpl: web::Data<Tera>,
+    req: HttpRequest,
+    runtime: CurrentRuntime,
 ) -> impl Responder {
-    let (app_state, _) = ApplicationState::from_query_params(query.into_inner());
-
-    let plane = plane::build_plane(
-        app_state.callsign.unwrap(),
-        app_state.pilot_moment.unwrap(),
-        app_state.passenger_moment,
-        app_state.baggage_moment,
-        app_state.fuel_type.unwrap(),
-        app_state.fuel_unit.unwrap(),
-        app_state.fuel_extra.clone(),
-        app_state.fuel_max.unwrap_or_default(),
-        app_state.trip_duration.unwrap(),
-    );
+    let (app_state, _) = ApplicationState::from_query_params(query.into_inner(), &runtime.registry);
+
+    let aircraft = match runtime
+        .registry
+        .get(app_state.callsign.as_deref().unwrap_or_default())
+    {
+        Some(a) => a,
+        None => return HttpResponse::BadRequest().body("Unknown aircraft"),
+    };
+
+    let weighing = match weighing(&app_state, &runtime, aircraft) {
+        Ok(w) => w,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+
+    let plane = build_plane(&app_state, aircraft, &weighing, &runtime);
     match airplane::visualizer::weight_and_balance_table(
         plane,
         WeightBalanceTableVisualization::new((620, 220)),
     ) {
         airplane::visualizer::Visualization::Svg(svg) => {
-            return HttpResponse::Ok().content_type("image/svg+xml").body(svg);
+            return HttpResponse::Ok()
+                .content_type("image/svg+xml")
+                .body(Theme::from_request(&req).recolour(&svg));
         }
     };
 }
 
-pub async fn wb_chart(query: web::Query<IndexQueryParams>) -> impl Responder {
-    let (app_state, _) = ApplicationState::from_query_params(query.into_inner());
-
-    let plane = plane::build_plane(
-        app_state.callsign.unwrap(),
-        app_state.pilot_moment.unwrap(),
-        app_state.passenger_moment,
-        app_state.baggage_moment,
-        app_state.fuel_type.unwrap(),
-        app_state.fuel_unit.unwrap(),
-        app_state.fuel_extra.clone(),
-        app_state.fuel_max.unwrap_or_default(),
-        app_state.trip_duration.unwrap(),
+pub async fn wb_chart(
+    query: web::Query<IndexQueryParams>,
+    req: HttpRequest,
+    runtime: CurrentRuntime,
+) -> impl Responder {
+    let (app_state, _) = ApplicationState::from_query_params(query.into_inner(), &runtime.registry);
+
+    match wb_chart_context(&app_state, Theme::from_request(&req), &runtime) {
+        Ok((_, ctx)) => render(&runtime.tera, "wb_chart.svg", &ctx, "image/svg+xml"),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+/// Chart with the loading points inline, so hovering shows their mass, centre of gravity and
+/// moment, and with sliders to try another load on the stations after the pilot and another
+/// fuel. The sliders are sent along with the state token and take precedence over the values
+/// in it; the calculation itself does not change.
+pub async fn wb_chart_interactive(
+    query: web::Query<IndexQueryParams>,
+    req: HttpRequest,
+    runtime: CurrentRuntime,
+) -> impl Responder {
+    let (app_state, _) = ApplicationState::from_query_params(query.into_inner(), &runtime.registry);
+
+    let (plane, mut ctx) = match wb_chart_context(&app_state, Theme::from_request(&req), &runtime) {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+
+    let unit_system = app_state.unit_system;
+    let aircraft_type = &app_state.aircraft_type;
+    let envelope = &aircraft_type.envelope;
+    let mass = |kilo: f64| round(unit_system.mass_from_kilo(kilo), 0);
+
+    ctx.insert("state", &app_state.to_token());
+    if let Some(d) = app_state.flight_date {
+        ctx.insert("flight_date", &date::format_date(d));
+    }
+    ctx.insert("mass_unit", unit_system.mass_unit());
+    ctx.insert(
+        "wb_within_limits",
+        &plane::within_limits(&plane, &aircraft_type.fuel),
+    );
+    ctx.insert(
+        "wb_landing_within_limits",
+        &plane::within_limits_landing(&plane, &aircraft_type.fuel),
     );
 
-    match airplane::visualizer::weight_and_balance_chart(
-        plane,
-        WeightBalanceChartVisualization::new((500, 500), (230.0..420.0, 550.0..760.0)),
-    ) {
-        airplane::visualizer::Visualization::Svg(svg) => {
-            return HttpResponse::Ok().content_type("image/svg+xml").body(svg);
-        }
+    // The pilot stays as entered, the other stations can be loaded up to their maximum or at
+    // most the useful load of the envelope.
+    ctx.insert(
+        "what_if_loads",
+        &aircraft_type
+            .stations
+            .iter()
+            .enumerate()
+            .map(|(i, station)| {
+                let kilo = app_state
+                    .loads
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .unwrap_or_default();
+                serde_json::json!({
+                    "name": station.name,
+                    "value": match i {
+                        0 => unit_system.mass_from_kilo(kilo),
+                        _ => mass(kilo),
+                    },
+                    "max": mass(station.max_mass.unwrap_or(envelope.max_mass - envelope.min_mass)),
+                })
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    let to_fuel_unit = |liters: f64| match app_state.fuel_unit {
+        Some(VolumeType::Gallon) => Volume::Liter(liters).to_gallon(),
+        _ => liters,
     };
+    let max_fuel = runtime.registry.fuel_policy(aircraft_type).max_fuel;
+    let tanks = aircraft_type.fuel.tanks();
+    let contents = plane::tank_contents(&plane, &aircraft_type.fuel);
+
+    ctx.insert(
+        "fuel_unit",
+        match app_state.fuel_unit {
+            Some(VolumeType::Gallon) => "gal",
+            _ => "L",
+        },
+    );
+    ctx.insert(
+        "what_if_tanks",
+        &tanks
+            .iter()
+            .zip(contents)
+            .map(|(tank, liters)| {
+                serde_json::json!({
+                    "name": tank.name,
+                    "value": round(to_fuel_unit(liters), 0),
+                    "max": round(to_fuel_unit(tank.capacity.unwrap_or(max_fuel)), 0),
+                })
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    render(
+        &runtime.tera,
+        "wb_chart_interactive.html",
+        &ctx,
+        "text/html",
+    )
+}
+
+/// Plane of the state and the context with its weight and balance chart, or why there is no
+/// chart.
+fn wb_chart_context(
+    app_state: &ApplicationState,
+    theme: Theme,
+    runtime: &Runtime,
+) -> Result<(Airplane, tera::Context), String> {
+    let aircraft = runtime
+        .registry
+        .get(app_state.callsign.as_deref().unwrap_or_default())
+        .ok_or_else(|| "Unknown aircraft".to_string())?;
+
+    let weighing = weighing(app_state, runtime, aircraft)?;
+
+    let aircraft_type = runtime.registry.type_of(aircraft);
+    let plane = build_plane(app_state, aircraft, &weighing, runtime);
+
+    let mut ctx = tera::Context::new();
+    ctx.insert(
+        "chart",
+        &chart::weight_and_balance_chart(
+            &plane,
+            &aircraft_type.envelope,
+            &aircraft_type.fuel,
+            app_state.unit_system,
+            aircraft_type.chart.as_ref(),
+        ),
+    );
+    ctx.insert("chart_style", &theme.chart_style());
+
+    Ok((plane, ctx))
 }
@@ -1,36 +1,55 @@
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
-use tera::Tera;
+use actix_web::{web, HttpRequest, Responder};
 
-use crate::models::{query_params::IndexQueryParams, state::ApplicationState};
+use crate::{
+    models::{query_params::IndexQueryParams, signoff::SignOff, state::ApplicationState},
+    runtime::CurrentRuntime,
+};
 
-use super::calculations::render_calculations;
+use super::{
+    calculations::{calculate, render_calculations},
+    render,
+};
 
 pub async fn export(
     query: web::Query<IndexQueryParams>,
     req: HttpRequest,
-    tmpl: web::Data<Tera>,
+    runtime: CurrentRuntime,
 ) -> impl Responder {
     let mut ctx = tera::Context::new();
-    let (app_state, query) = ApplicationState::from_query_params(query.into_inner());
+    let params = query.into_inner();
+    let sign_off = SignOff::from_query_params(&params);
+    let (app_state, query) = ApplicationState::from_query_params(params, &runtime.registry);
 
-    match query.submit {
-        Some(s) if s == "Vorige" => {
-            return render_calculations(&app_state, &mut ctx, req, tmpl, "calculations_form.html");
-        }
-        _ => (),
+    if query.submit.as_deref() == Some("Vorige") {
+        return render_calculations(&app_state, &mut ctx, &runtime, "calculations_form.html");
     }
 
+    let loadsheet = match calculate(&app_state, &mut tera::Context::new(), &runtime) {
+        Ok(l) => l,
+        Err(response) => return *response,
+    };
+
     app_state.apply("export", &mut ctx);
 
-    ctx.insert(
-        "print_url",
-        &format!(
-            "/print?{}",
-            req.query_string(),
-        ),
-    );
+    ctx.insert("wb_within_limits", &loadsheet.results.within_limits);
+    ctx.insert("fuel_sufficient", &loadsheet.results.fuel_sufficient);
+    ctx.insert("performance_available", &loadsheet.results.torr.is_some());
+    if let Some(reference) = &query.reference {
+        ctx.insert("reference", reference);
+    }
 
-    let rendered = tmpl.render("export_form.html", &ctx).unwrap();
-    HttpResponse::Ok().content_type("text/html").body(rendered)
-}
+    // The sign-off is only validated once the pilot submits it, the print URL is handed out
+    // when it is complete.
+    if query.submit.as_deref() == Some("Opslaan") {
+        sign_off.apply(&mut ctx);
 
+        let errors = sign_off.validate(&loadsheet);
+        if errors.is_empty() {
+            ctx.insert("print_url", &format!("/print?{}", req.query_string()));
+        } else {
+            ctx.insert("errors", &errors);
+        }
+    }
+
+    render(&runtime.tera, "export_form.html", &ctx, "text/html")
+}
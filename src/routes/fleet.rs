@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use actix_web::{http::header, HttpResponse, Responder};
+use serde::Serialize;
+
+use crate::{
+    models::aircraft::{AircraftRegistry, FuelPolicy},
+    runtime::CurrentRuntime,
+};
+
+/// Aircraft registry as read by the offline calculator, with the fuel policy of each type for
+/// the fuel planning.
+#[derive(Serialize)]
+struct Fleet<'a> {
+    #[serde(flatten)]
+    registry: &'a AircraftRegistry,
+    fuel_policies: HashMap<&'a str, FuelPolicy>,
+}
+
+/// Serves the aircraft registry to the offline calculator, which keeps the last copy received.
+pub async fn fleet(runtime: CurrentRuntime) -> impl Responder {
+    let registry = &runtime.registry;
+    let fleet = Fleet {
+        registry,
+        fuel_policies: registry
+            .types
+            .iter()
+            .map(|t| (t.id.as_str(), registry.fuel_policy(t)))
+            .collect(),
+    };
+
+    HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .json(fleet)
+}
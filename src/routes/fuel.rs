@@ -1,47 +1,96 @@
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
-use tera::Tera;
+use actix_web::{web, Responder};
 
-use crate::models::{
-    query_params::{FuelOptionQueryParams, IndexQueryParams},
-    state::ApplicationState,
+use crate::{
+    models::{query_params::IndexQueryParams, state::ApplicationState},
+    runtime::CurrentRuntime,
 };
 
-use super::calculations;
+use super::{calculations, render};
 
-pub async fn fuel(
-    query: web::Query<IndexQueryParams>,
-    req: HttpRequest,
-    tmpl: web::Data<Tera>,
-) -> impl Responder {
+/// Number of alternates offered, nearest to the destination first.
+const MAX_ALTERNATES: usize = 20;
+
+pub async fn fuel(query: web::Query<IndexQueryParams>, runtime: CurrentRuntime) -> impl Responder {
     let mut ctx = tera::Context::new();
-    let (app_state, query) = ApplicationState::from_query_params(query.into_inner());
+    let (mut app_state, query) =
+        ApplicationState::from_query_params(query.into_inner(), &runtime.registry);
 
     match query.submit {
         Some(s) if s == "Vorige" => {
             app_state.apply("input", &mut ctx);
-            let rendered = tmpl.render("wb_form.html", &ctx).unwrap();
-            return HttpResponse::Ok().content_type("text/html").body(rendered);
+            ctx.insert("aircraft", &runtime.registry.callsigns());
+            return render(&runtime.tera, "wb_form.html", &ctx, "text/html");
         }
         _ => (),
     }
 
-    calculations::render_calculations(&app_state, &mut ctx, req, tmpl, "calculations_form.html")
+    // A planned route replaces the entered trip duration, the state then carries the planned one.
+    match calculations::route_plan(&app_state, &runtime) {
+        Some(Ok(route)) => app_state.trip_duration = Some(route.trip_duration()),
+        Some(Err(e)) => return calculations::fuel_errors(&app_state, &runtime, &[e]),
+        None => (),
+    }
+
+    // Likewise the diversion to a selected alternate replaces the entered alternate duration.
+    match calculations::diversion_plan(&app_state, &runtime) {
+        Some(Ok((_, diversion))) => app_state.alternate_duration = Some(diversion.trip_duration()),
+        Some(Err(e)) => return calculations::fuel_errors(&app_state, &runtime, &[e]),
+        None => (),
+    }
+
+    calculations::render_calculations(&app_state, &mut ctx, &runtime, "calculations_form.html")
 }
 
 pub async fn fuel_option(
-    query: web::Query<FuelOptionQueryParams>,
-    tmpl: web::Data<Tera>,
+    query: web::Query<IndexQueryParams>,
+    runtime: CurrentRuntime,
 ) -> impl Responder {
     let mut ctx = tera::Context::new();
-    let query_params = query.into_inner();
+    let (app_state, _) = ApplicationState::from_query_params(query.into_inner(), &runtime.registry);
+    app_state.apply("fuel", &mut ctx);
 
-    if let Some(fm) = query_params.fuel_max {
-        ctx.insert("fuel_max", &fm);
-    }
+    render(
+        &runtime.tera,
+        "fuel_max_fuel_option.html",
+        &ctx,
+        "text/html",
+    )
+}
 
-    if let Some(ft) = query_params.fuel_type {
-        ctx.insert("fuel_type", &ft);
+/// Alternates near the destination of the route, rendered again when the route changes.
+pub async fn alternate_option(
+    query: web::Query<IndexQueryParams>,
+    runtime: CurrentRuntime,
+) -> impl Responder {
+    let mut ctx = tera::Context::new();
+    let (app_state, _) = ApplicationState::from_query_params(query.into_inner(), &runtime.registry);
+    app_state.apply("fuel", &mut ctx);
+
+    let destination = app_state
+        .destination()
+        .and_then(|d| runtime.airports.waypoint(d).ok());
+    if let Some(destination) = destination {
+        let alternates: Vec<_> = runtime
+            .airports
+            .nearest(&destination.position)
+            .into_iter()
+            .filter(|(airport, _)| airport.code.to_uppercase() != destination.name)
+            .take(MAX_ALTERNATES)
+            .map(|(airport, distance)| {
+                serde_json::json!({
+                    "code": airport.code.to_uppercase(),
+                    "name": airport.name,
+                    "distance": format!("{:.0} NM", distance),
+                })
+            })
+            .collect();
+        ctx.insert("alternates", &alternates);
     }
-    let rendered = tmpl.render("fuel_max_fuel_option.html", &ctx).unwrap();
-    HttpResponse::Ok().content_type("text/html").body(rendered)
+
+    render(
+        &runtime.tera,
+        "fuel_alternate_option.html",
+        &ctx,
+        "text/html",
+    )
 }
@@ -1,9 +1,153 @@
-use actix_web::{HttpResponse, Responder};
+use std::{
+    sync::{Arc, OnceLock},
+    thread,
+};
+
+use acm_weight_and_balance::{
+    performance,
+    performance_check::{self, Grid},
+};
+use actix_web::{web, HttpResponse, Responder};
+use serde::Serialize;
+
+use crate::{
+    runtime::{check_templates, CurrentRuntime},
+    storage::Storage,
+};
+
+const MAX_LOGGED_VIOLATIONS: usize = 20;
+
+#[derive(Serialize)]
+struct Check {
+    name: &'static str,
+    ok: bool,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, result: Result<String, String>) -> Check {
+        match result {
+            Ok(detail) => Check {
+                name,
+                ok: true,
+                detail,
+            },
+            Err(detail) => Check {
+                name,
+                ok: false,
+                detail,
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ReadinessReport {
+    ready: bool,
+    checks: Vec<Check>,
+}
+
+/// Result of the sweep of the performance charts, run once in the background at startup since
+/// the charts are compiled in.
+pub struct PerformanceCheck {
+    result: Arc<OnceLock<(usize, usize)>>,
+    strict: bool,
+}
+
+impl PerformanceCheck {
+    /// Starts the sweep on a thread of its own, so the server does not wait for it.
+    pub fn spawn(strict: bool) -> PerformanceCheck {
+        let check = PerformanceCheck {
+            result: Arc::new(OnceLock::new()),
+            strict,
+        };
+
+        let result = check.result.clone();
+        thread::spawn(move || {
+            let sweep = performance_check::sweep(&Grid::default());
+
+            for violation in sweep.violations.iter().take(MAX_LOGGED_VIOLATIONS) {
+                tracing::warn!(%violation, "performance self-check violation");
+            }
+
+            if sweep.violations.is_empty() {
+                tracing::info!(points = sweep.points, "performance self-check passed");
+            } else {
+                tracing::warn!(
+                    points = sweep.points,
+                    violations = sweep.violations.len(),
+                    strict,
+                    "performance self-check found violations"
+                );
+            }
+
+            let _ = result.set((sweep.points, sweep.violations.len()));
+        });
+
+        check
+    }
+
+    /// Not ready in strict mode until the sweep has finished without violations.
+    fn check(&self) -> Result<String, String> {
+        let Some((points, violations)) = self.result.get() else {
+            let detail = "sweep still running".to_string();
+            return if self.strict { Err(detail) } else { Ok(detail) };
+        };
+        let detail = format!("{} violations in {} points", violations, points);
+
+        if *violations > 0 && self.strict {
+            Err(detail)
+        } else {
+            Ok(detail)
+        }
+    }
+}
 
 pub async fn health_check() -> impl Responder {
     HttpResponse::Ok().body("OK")
 }
 
-pub async fn readiness_check() -> impl Responder {
-    HttpResponse::Ok().body("Ready")
+pub async fn readiness_check(
+    runtime: CurrentRuntime,
+    storage: web::Data<Storage>,
+    performance_check: web::Data<PerformanceCheck>,
+) -> impl Responder {
+    let checks = vec![
+        Check::new("templates", check_templates(&runtime.tera)),
+        Check::new(
+            "aircraft",
+            runtime.registry.validate().map(|_| {
+                format!(
+                    "{} aircraft of {} types",
+                    runtime.registry.aircraft.len(),
+                    runtime.registry.types.len()
+                )
+            }),
+        ),
+        Check::new(
+            "airports",
+            runtime
+                .airports
+                .validate()
+                .map(|_| format!("{} airports", runtime.airports.airports.len())),
+        ),
+        Check::new(
+            "performance",
+            performance::performance_self_test().map(|_| "self-test passed".to_string()),
+        ),
+        Check::new("performance-sweep", performance_check.check()),
+        Check::new("storage", storage.check()),
+        Check::new("signing", runtime.signer.check()),
+    ];
+
+    let report = ReadinessReport {
+        ready: checks.iter().all(|c| c.ok),
+        checks,
+    };
+
+    if report.ready {
+        HttpResponse::Ok().json(report)
+    } else {
+        HttpResponse::ServiceUnavailable().json(report)
+    }
 }
@@ -1,38 +1,85 @@
 use crate::models::query_params::{IndexQueryParams, WindOptionQueryParams};
 use crate::models::state::ApplicationState;
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
-use tera::Tera;
+use crate::runtime::CurrentRuntime;
+use crate::utils::{theme::Theme, units::UnitSystem};
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+
+use super::render;
 
 pub async fn index(
     query: web::Query<IndexQueryParams>,
     req: HttpRequest,
-    tmpl: web::Data<Tera>,
+    runtime: CurrentRuntime,
 ) -> impl Responder {
     let mut ctx = tera::Context::new();
 
     let headers = req.headers();
 
-    let (app_state, _) = ApplicationState::from_query_params(query.into_inner());
+    let (app_state, _) = ApplicationState::from_query_params(query.into_inner(), &runtime.registry);
     let mut step = "input";
 
     let template = if headers.get("HX-Request").is_some() {
-        step = "fuel";
-        "fuel_form.html"
+        let errors = app_state.condition_errors();
+        if errors.is_empty() {
+            step = "fuel";
+            "fuel_form.html"
+        } else {
+            ctx.insert("errors", &errors);
+            "wb_form.html"
+        }
     } else {
         "index.html"
     };
 
     app_state.apply(step, &mut ctx);
+    ctx.insert("aircraft", &runtime.registry.callsigns());
+    ctx.insert("theme", Theme::from_request(&req).as_str());
+
+    let mut response = render(&runtime.tera, template, &ctx, "text/html");
+    response.headers_mut().insert(
+        header::LINK,
+        header::HeaderValue::from_static("</assets/htmx.min.js>; rel=preload; as=script"),
+    );
+    response
+}
+
+/// Renders the input form again in other units, or for another aircraft with its stations and
+/// optional equipment.
+pub async fn unit_option(req: HttpRequest, runtime: CurrentRuntime) -> impl Responder {
+    let mut ctx = tera::Context::new();
+
+    // The form may still be incomplete, so skip the fields that are left empty.
+    let query_string = req
+        .query_string()
+        .split('&')
+        .filter(|pair| !pair.ends_with('='))
+        .collect::<Vec<&str>>()
+        .join("&");
 
-    let rendered = tmpl.render(template, &ctx).unwrap();
-    HttpResponse::Ok()
-        .insert_header(("Link", "</assets/htmx.min.js>; rel=preload; as=script"))
-        .body(rendered)
+    let mut query_params = match web::Query::<IndexQueryParams>::from_query(&query_string) {
+        Ok(q) => q.into_inner(),
+        Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+    };
+
+    // The entered values are still expressed in the previously selected units.
+    let units = query_params.units.take();
+    query_params.units = query_params.previous_units.take();
+
+    let (mut app_state, _) = ApplicationState::from_query_params(query_params, &runtime.registry);
+    app_state.unit_system = units
+        .as_deref()
+        .and_then(UnitSystem::parse)
+        .unwrap_or_default();
+
+    app_state.apply("input", &mut ctx);
+    ctx.insert("aircraft", &runtime.registry.callsigns());
+
+    render(&runtime.tera, "wb_form.html", &ctx, "text/html")
 }
 
 pub async fn wind_option(
     query: web::Query<WindOptionQueryParams>,
-    tmpl: web::Data<Tera>,
+    runtime: CurrentRuntime,
 ) -> impl Responder {
     let mut ctx = tera::Context::new();
     let query_params = query.into_inner();
@@ -41,6 +88,10 @@ pub async fn wind_option(
         ctx.insert("wind", &query_params.wind.unwrap());
     }
 
+    if let Some(gust) = query_params.gust.as_deref().filter(|g| !g.is_empty()) {
+        ctx.insert("gust", gust);
+    }
+
     ctx.insert(
         "wind_direction",
         &query_params
@@ -48,6 +99,5 @@ pub async fn wind_option(
             .unwrap_or_else(|| "headwind".to_string()),
     );
 
-    let rendered = tmpl.render("wb_form_wind_option.html", &ctx).unwrap();
-    HttpResponse::Ok().content_type("text/html").body(rendered)
+    render(&runtime.tera, "wb_form_wind_option.html", &ctx, "text/html")
 }
@@ -1,26 +1,50 @@
-use actix_web::web;
+use actix_web::{web, HttpResponse};
+use tera::Tera;
+
+use crate::metrics;
 
 mod calculations;
 mod export;
+mod fleet;
 mod print;
 mod index;
-mod health;
+pub mod health;
 mod fuel;
+mod verify;
 
 pub fn init(cfg: &mut web::ServiceConfig) {
     cfg
         .route("/", web::get().to(index::index))
+        .route("/unit-option", web::get().to(index::unit_option))
         .route("/wind-option", web::get().to(index::wind_option))
         .route("/fuel", web::get().to(fuel::fuel))
         .route("/fuel-option", web::get().to(fuel::fuel_option))
+        .route("/alternate-option", web::get().to(fuel::alternate_option))
         .route("/calculations", web::get().to(calculations::calculations))
         .route("/perf-tod", web::get().to(calculations::perf_tod))
         .route("/perf-ldr", web::get().to(calculations::perf_ldr))
         .route("/wb-chart", web::get().to(calculations::wb_chart))
+        .route(
+            "/wb-chart-interactive",
+            web::get().to(calculations::wb_chart_interactive),
+        )
         .route("/wb-table", web::get().to(calculations::wb_table))
         .route("/export", web::get().to(export::export))
-        .route("/print", web::get().to(print::print))
+        .route("/print", web::post().to(print::print))
+        .route("/verify", web::get().to(verify::verify))
+        .route("/fleet.json", web::get().to(fleet::fleet))
         .route("/health", web::get().to(health::health_check))
         .route("/ready", web::get().to(health::readiness_check));
 }
 
+
+fn render(tmpl: &Tera, template: &str, ctx: &tera::Context, content_type: &str) -> HttpResponse {
+    match tmpl.render(template, ctx) {
+        Ok(rendered) => HttpResponse::Ok().content_type(content_type).body(rendered),
+        Err(e) => {
+            metrics::RENDER_ERRORS.with_label_values(&[template]).inc();
+            tracing::error!(template, error = ?e, "failed to render template");
+            HttpResponse::InternalServerError().body("Internal Server Error")
+        }
+    }
+}
@@ -1,20 +1,97 @@
-use actix_web::{web, HttpRequest, Responder};
-use tera::Tera;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 
-use crate::models::{query_params::IndexQueryParams, state::ApplicationState};
+use crate::{
+    metrics,
+    models::{
+        loadsheet::Loadsheet, query_params::IndexQueryParams, signoff::SignOff,
+        state::ApplicationState,
+    },
+    runtime::{CurrentRuntime, Runtime},
+    signing,
+    storage::Storage,
+};
 
-use super::calculations::render_calculations;
+use super::{calculations::calculate, render};
 
 pub async fn print(
     query: web::Query<IndexQueryParams>,
     req: HttpRequest,
-    tmpl: web::Data<Tera>,
+    runtime: CurrentRuntime,
+    storage: web::Data<Storage>,
 ) -> impl Responder {
     let mut ctx = tera::Context::new();
-    let (app_state, query) = ApplicationState::from_query_params(query.into_inner());
+    let params = query.into_inner();
+    let sign_off = SignOff::from_query_params(&params);
+    let (app_state, query) = ApplicationState::from_query_params(params, &runtime.registry);
 
     ctx.insert("print", &true);
     ctx.insert("document_reference", &query.reference);
 
-    render_calculations(&app_state, &mut ctx, req, tmpl, "print.html")
+    let mut loadsheet = match calculate(&app_state, &mut ctx, &runtime) {
+        Ok(l) => l,
+        Err(response) => return *response,
+    };
+
+    // The export step validates the sign-off as well, this guards against edited print URLs.
+    let errors = sign_off.validate(&loadsheet);
+    if !errors.is_empty() {
+        return HttpResponse::BadRequest().body(errors.join("\n"));
+    }
+
+    sign_off.apply(&mut ctx);
+    loadsheet.reference = query.reference;
+    loadsheet.sign_off = Some(sign_off);
+
+    let signature = sign_loadsheet(&mut ctx, &req, &runtime, &loadsheet);
+
+    if !loadsheet.results.within_limits {
+        metrics::OUT_OF_LIMITS_LOADSHEETS.inc();
+    }
+
+    match storage.store(&loadsheet, signature.as_deref()) {
+        Ok(Some(record)) => {
+            if record.new {
+                tracing::info!(record = %record.name, "stored loadsheet");
+            }
+            ctx.insert("record", &record.name);
+        }
+        Ok(None) => (),
+        Err(e) => {
+            tracing::error!(error = %e, "unable to store loadsheet");
+            return HttpResponse::InternalServerError().body("Unable to store loadsheet");
+        }
+    }
+
+    render(&runtime.tera, "print.html", &ctx, "text/html")
+}
+
+/// Adds the signed canonical loadsheet, its verification URL and a QR code of that URL to the
+/// printout when signing is configured.
+fn sign_loadsheet(
+    ctx: &mut tera::Context,
+    req: &HttpRequest,
+    runtime: &Runtime,
+    loadsheet: &Loadsheet,
+) -> Option<String> {
+    let token = runtime.signer.sign(loadsheet)?;
+
+    let verify_url = {
+        let conn = req.connection_info();
+        format!(
+            "{}://{}/verify?payload={}",
+            conn.scheme(),
+            conn.host(),
+            token
+        )
+    };
+
+    match signing::qr_code(&verify_url) {
+        Ok(svg) => ctx.insert("loadsheet_qr", &svg),
+        Err(e) => tracing::warn!(error = %e, "unable to render loadsheet QR code"),
+    }
+
+    ctx.insert("loadsheet_signature", &token);
+    ctx.insert("loadsheet_verify_url", &verify_url);
+
+    Some(token)
 }
@@ -0,0 +1,43 @@
+use actix_web::{web, HttpRequest, Responder};
+
+use crate::{
+    models::query_params::VerifyQueryParams, runtime::CurrentRuntime, utils::theme::Theme,
+};
+
+use super::render;
+
+pub async fn verify(
+    query: web::Query<VerifyQueryParams>,
+    req: HttpRequest,
+    runtime: CurrentRuntime,
+) -> impl Responder {
+    let mut ctx = tera::Context::new();
+    ctx.insert("theme", Theme::from_request(&req).as_str());
+
+    if let Some(payload) = query.into_inner().payload.filter(|p| !p.trim().is_empty()) {
+        // A scanned QR code contains the full verification URL, accept that as well.
+        let token = match payload.split_once("payload=") {
+            Some((_, t)) => t.to_string(),
+            None => payload.clone(),
+        };
+
+        match runtime.signer.verify(&token) {
+            Ok(loadsheet) => {
+                tracing::info!(
+                    callsign = %loadsheet.state.callsign,
+                    issued_at = loadsheet.issued_at,
+                    "verified loadsheet"
+                );
+                ctx.insert("loadsheet", &loadsheet);
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "loadsheet verification failed");
+                ctx.insert("error", &e);
+            }
+        }
+
+        ctx.insert("payload", &payload);
+    }
+
+    render(&runtime.tera, "verify.html", &ctx, "text/html")
+}
@@ -0,0 +1,229 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    future::{ready, Ready},
+    ops::Deref,
+    path::Path,
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+use actix_web::{dev::Payload, error, web, FromRequest, HttpRequest};
+use rust_embed::RustEmbed;
+use tera::Tera;
+
+use acm_weight_and_balance::route::AirportDatabase;
+
+use crate::{config::Config, models::aircraft::AircraftRegistry, signing::Signer};
+
+#[derive(RustEmbed)]
+#[folder = "templates/"]
+struct Templates;
+
+const REQUIRED_TEMPLATES: [&str; 16] = [
+    "index.html",
+    "wb_form.html",
+    "wb_form_wind_option.html",
+    "wb_form_equipment_option.html",
+    "fuel_form.html",
+    "fuel_max_fuel_option.html",
+    "fuel_alternate_option.html",
+    "calculations_form.html",
+    "export_form.html",
+    "print.html",
+    "wb_chart.svg",
+    "wb_chart_plot.svg",
+    "wb_chart_interactive.html",
+    "top.svg",
+    "ld.svg",
+    "verify.html",
+];
+
+/// Data that can be reloaded while the server is running.
+pub struct Runtime {
+    pub tera: Tera,
+    pub registry: AircraftRegistry,
+    pub airports: AirportDatabase,
+    pub signer: Signer,
+}
+
+impl Runtime {
+    /// Loads and validates the templates, the aircraft registry, the airports and the signing
+    /// key.
+    pub fn load(config: &Config) -> Result<Runtime, String> {
+        let tera = load_templates(config.template_dir.as_deref(), config.feature_names())?;
+        check_templates(&tera)?;
+
+        let registry = AircraftRegistry::load(config.aircraft_file.as_deref())?;
+        let airports = AirportDatabase::load(config.airports_file.as_deref())?;
+        let signer = Signer::load(config.signing_key_file.as_deref())?;
+
+        Ok(Runtime {
+            tera,
+            registry,
+            airports,
+            signer,
+        })
+    }
+}
+
+/// Holds the current runtime. Requests keep the snapshot they started with, so a reload never
+/// affects requests in flight.
+#[derive(Clone)]
+pub struct SharedRuntime(Arc<RwLock<Arc<Runtime>>>);
+
+impl SharedRuntime {
+    pub fn new(runtime: Runtime) -> SharedRuntime {
+        SharedRuntime(Arc::new(RwLock::new(Arc::new(runtime))))
+    }
+
+    pub fn current(&self) -> Arc<Runtime> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Loads a new runtime and swaps it in, keeping the current one if loading fails.
+    pub fn reload(&self, config: &Config) -> Result<(), String> {
+        let runtime = Runtime::load(config)?;
+        *self.0.write().unwrap() = Arc::new(runtime);
+        Ok(())
+    }
+}
+
+/// Extracts the runtime that is current when the request arrives.
+pub struct CurrentRuntime(Arc<Runtime>);
+
+impl Deref for CurrentRuntime {
+    type Target = Runtime;
+
+    fn deref(&self) -> &Runtime {
+        &self.0
+    }
+}
+
+impl FromRequest for CurrentRuntime {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<CurrentRuntime, actix_web::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(match req.app_data::<web::Data<SharedRuntime>>() {
+            Some(shared) => Ok(CurrentRuntime(shared.current())),
+            None => Err(error::ErrorInternalServerError("runtime is not configured")),
+        })
+    }
+}
+
+/// Templates are parsed when they are loaded, so verify that all templates the routes depend on
+/// are present.
+pub fn check_templates(tera: &Tera) -> Result<String, String> {
+    let names: Vec<&str> = tera.get_template_names().collect();
+
+    let missing: Vec<&str> = REQUIRED_TEMPLATES
+        .iter()
+        .filter(|t| !names.contains(*t))
+        .copied()
+        .collect();
+
+    if missing.is_empty() {
+        Ok(format!("{} templates", names.len()))
+    } else {
+        Err(format!("missing templates: {}", missing.join(", ")))
+    }
+}
+
+/// Returns the latest modification time of the external data and key files, used to detect
+/// changes in watch mode.
+pub fn last_modified(config: &Config) -> Option<SystemTime> {
+    let mut paths = vec![];
+
+    if let Some(file) = &config.aircraft_file {
+        paths.push(file.to_path_buf());
+    }
+
+    if let Some(file) = &config.airports_file {
+        paths.push(file.to_path_buf());
+    }
+
+    if let Some(file) = &config.signing_key_file {
+        paths.push(file.to_path_buf());
+    }
+
+    if let Some(dir) = &config.template_dir {
+        let mut templates = BTreeMap::new();
+        if read_template_dir(dir, dir, &mut templates).is_ok() {
+            paths.extend(templates.keys().map(|name| dir.join(name)));
+        }
+        paths.push(dir.to_path_buf());
+    }
+
+    paths
+        .iter()
+        .filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+fn load_templates(
+    template_dir: Option<&Path>,
+    features: Vec<&'static str>,
+) -> Result<Tera, String> {
+    let mut templates = BTreeMap::new();
+
+    for file in Templates::iter() {
+        if let Some(content) = Templates::get(file.as_ref()) {
+            let content_str = std::str::from_utf8(&content.data)
+                .map_err(|e| format!("template {} is not valid UTF-8: {}", file, e))?;
+            templates.insert(file.to_string(), content_str.to_string());
+        }
+    }
+
+    if let Some(dir) = template_dir {
+        read_template_dir(dir, dir, &mut templates)?;
+    }
+
+    let mut tera = Tera::default();
+    tera.add_raw_templates(templates)
+        .map_err(|e| format!("invalid templates: {:?}", e))?;
+
+    let feature = move |args: &HashMap<String, tera::Value>| -> tera::Result<tera::Value> {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| tera::Error::msg("feature requires a `name` argument"))?;
+        Ok(tera::Value::Bool(features.contains(&name)))
+    };
+    tera.register_function("feature", feature);
+
+    Ok(tera)
+}
+
+/// Collects all templates below `dir`, named by their path relative to `root`.
+fn read_template_dir(
+    root: &Path,
+    dir: &Path,
+    templates: &mut BTreeMap<String, String>,
+) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("unable to read {}: {}", dir.display(), e))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("unable to read {}: {}", dir.display(), e))?
+            .path();
+
+        if path.is_dir() {
+            read_template_dir(root, &path, templates)?;
+            continue;
+        }
+
+        let name = path
+            .strip_prefix(root)
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+
+        templates.insert(name, content);
+    }
+
+    Ok(())
+}
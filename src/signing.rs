@@ -0,0 +1,125 @@
+use std::{fs, path::Path};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use qrcode::{render::svg, QrCode};
+use sha2::Sha256;
+
+use crate::models::loadsheet::{Loadsheet, LOADSHEET_VERSION};
+
+const MIN_KEY_LENGTH: usize = 32;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs and verifies loadsheets with a server-held HMAC-SHA256 key.
+///
+/// A signed loadsheet is encoded as `v<version>.<payload>.<signature>`, where the payload is the
+/// base64url encoded JSON serialization of the loadsheet and the signature covers the version
+/// prefix and the encoded payload.
+#[derive(Clone)]
+pub struct Signer {
+    key: Option<Vec<u8>>,
+}
+
+impl Signer {
+    /// Reads the key from the given file, signing is disabled when no file is given.
+    pub fn load(path: Option<&Path>) -> Result<Signer, String> {
+        let key = match path {
+            Some(p) => {
+                let content =
+                    fs::read(p).map_err(|e| format!("unable to read {}: {}", p.display(), e))?;
+                let key = content.trim_ascii().to_vec();
+
+                if key.len() < MIN_KEY_LENGTH {
+                    return Err(format!(
+                        "signing key {} must be at least {} bytes",
+                        p.display(),
+                        MIN_KEY_LENGTH
+                    ));
+                }
+
+                Some(key)
+            }
+            None => None,
+        };
+
+        Ok(Signer { key })
+    }
+
+    pub fn check(&self) -> Result<String, String> {
+        match &self.key {
+            Some(_) => Ok("HMAC-SHA256".to_string()),
+            None => Ok("not configured".to_string()),
+        }
+    }
+
+    /// Returns the signed token, or `None` when signing is not configured.
+    pub fn sign(&self, loadsheet: &Loadsheet) -> Option<String> {
+        let key = self.key.as_ref()?;
+
+        let json = match serde_json::to_vec(loadsheet) {
+            Ok(j) => j,
+            Err(e) => {
+                tracing::error!(error = %e, "unable to serialize loadsheet");
+                return None;
+            }
+        };
+        let signed = format!("v{}.{}", loadsheet.version, URL_SAFE_NO_PAD.encode(json));
+
+        let signature = mac(key, &signed).finalize().into_bytes();
+
+        Some(format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+    /// Verifies the signature of a token and returns the loadsheet it contains.
+    pub fn verify(&self, token: &str) -> Result<Loadsheet, String> {
+        let key = self
+            .key
+            .as_ref()
+            .ok_or_else(|| "signing is not configured on this server".to_string())?;
+
+        let (signed, signature) = token
+            .trim()
+            .rsplit_once('.')
+            .ok_or_else(|| "malformed loadsheet code".to_string())?;
+
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| "malformed signature".to_string())?;
+
+        mac(key, signed)
+            .verify_slice(&signature)
+            .map_err(|_| "signature does not match, the loadsheet has been altered".to_string())?;
+
+        let (version, payload) = signed
+            .split_once('.')
+            .ok_or_else(|| "malformed loadsheet code".to_string())?;
+
+        if version != format!("v{}", LOADSHEET_VERSION) {
+            return Err(format!("unsupported loadsheet version {}", version));
+        }
+
+        let json = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| "malformed payload".to_string())?;
+
+        serde_json::from_slice(&json).map_err(|e| format!("invalid loadsheet: {}", e))
+    }
+}
+
+fn mac(key: &[u8], message: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    mac
+}
+
+/// Renders the given data as an SVG QR code.
+pub fn qr_code(data: &str) -> Result<String, String> {
+    let code = QrCode::new(data.as_bytes()).map_err(|e| e.to_string())?;
+
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(160, 160)
+        .quiet_zone(true)
+        .build())
+}
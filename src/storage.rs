@@ -0,0 +1,103 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::models::loadsheet::Loadsheet;
+
+/// Location where finalized loadsheets are stored, if storage is configured.
+#[derive(Clone, Debug)]
+pub struct Storage {
+    dir: Option<PathBuf>,
+}
+
+impl Storage {
+    pub fn new(dir: Option<PathBuf>) -> Storage {
+        Storage { dir }
+    }
+
+    /// Verifies that the storage directory exists and is writable.
+    pub fn check(&self) -> Result<String, String> {
+        let dir = match &self.dir {
+            Some(d) => d,
+            None => return Ok("not configured".to_string()),
+        };
+
+        if !dir.is_dir() {
+            return Err(format!("{} is not a directory", dir.display()));
+        }
+
+        let probe = dir.join(".readiness-probe");
+        fs::write(&probe, b"ok")
+            .map_err(|e| format!("{} is not writable: {}", dir.display(), e))?;
+        fs::remove_file(&probe).map_err(|e| format!("unable to clean up probe: {}", e))?;
+
+        Ok(dir.display().to_string())
+    }
+
+    /// Stores a finalized loadsheet together with its signature, returns the record or `None`
+    /// when storage is not configured. Printing the same loadsheet again does not add a record,
+    /// the record is named after a digest of the loadsheet without its time of issue.
+    pub fn store(
+        &self,
+        loadsheet: &Loadsheet,
+        signature: Option<&str>,
+    ) -> Result<Option<Record>, String> {
+        let dir = match &self.dir {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        let unissued = Loadsheet {
+            issued_at: 0,
+            ..loadsheet.clone()
+        };
+        let digest = Sha256::digest(serde_json::to_vec(&unissued).map_err(|e| e.to_string())?);
+        let callsign: String = loadsheet
+            .state
+            .callsign
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect();
+        let name = format!(
+            "{}-{}.json",
+            callsign,
+            digest[..8]
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        );
+
+        let record = serde_json::json!({
+            "loadsheet": loadsheet,
+            "signature": signature,
+        });
+        let content = serde_json::to_vec_pretty(&record).map_err(|e| e.to_string())?;
+
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(&name));
+        match file {
+            Ok(mut f) => f
+                .write_all(&content)
+                .map_err(|e| format!("unable to store {}: {}", name, e))?,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                return Ok(Some(Record { name, new: false }))
+            }
+            Err(e) => return Err(format!("unable to store {}: {}", name, e)),
+        }
+
+        Ok(Some(Record { name, new: true }))
+    }
+}
+
+/// Stored loadsheet, `new` is false when the loadsheet had been stored before.
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub name: String,
+    pub new: bool,
+}
@@ -0,0 +1,113 @@
+const POUNDS_PER_KILO: f64 = 2.204_622_621_8;
+const INCHES_PER_METER: f64 = 39.370_078_740_2;
+const FEET_PER_METER: f64 = 3.280_839_895;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl UnitSystem {
+    pub fn parse(value: &str) -> Option<UnitSystem> {
+        match value {
+            "metric" => Some(UnitSystem::Metric),
+            "imperial" => Some(UnitSystem::Imperial),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "metric",
+            UnitSystem::Imperial => "imperial",
+        }
+    }
+
+    pub fn mass_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "kg",
+            UnitSystem::Imperial => "lb",
+        }
+    }
+
+    pub fn arm_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "m",
+            UnitSystem::Imperial => "in",
+        }
+    }
+
+    pub fn cg_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "mm",
+            UnitSystem::Imperial => "in",
+        }
+    }
+
+    pub fn moment_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "kg m",
+            UnitSystem::Imperial => "lb in",
+        }
+    }
+
+    pub fn distance_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "m",
+            UnitSystem::Imperial => "ft",
+        }
+    }
+
+    /// Converts a user entered mass to kilograms.
+    pub fn mass_to_kilo(&self, mass: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => mass,
+            UnitSystem::Imperial => mass / POUNDS_PER_KILO,
+        }
+    }
+
+    /// Converts a mass in kilograms to the display unit.
+    pub fn mass_from_kilo(&self, kilo: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => kilo,
+            UnitSystem::Imperial => kilo * POUNDS_PER_KILO,
+        }
+    }
+
+    /// Converts a lever arm in meters to the display unit.
+    pub fn arm_from_meter(&self, meter: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => meter,
+            UnitSystem::Imperial => meter * INCHES_PER_METER,
+        }
+    }
+
+    /// Converts a center of gravity in meters to the display unit.
+    pub fn cg_from_meter(&self, meter: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => meter * 1000.0,
+            UnitSystem::Imperial => meter * INCHES_PER_METER,
+        }
+    }
+
+    /// Converts a moment in kilogram meters to the display unit.
+    pub fn moment_from_kilo_meter(&self, moment: f64) -> f64 {
+        self.arm_from_meter(self.mass_from_kilo(moment))
+    }
+
+    /// Converts a distance in meters to the display unit.
+    pub fn distance_from_meter(&self, meter: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => meter,
+            UnitSystem::Imperial => meter * FEET_PER_METER,
+        }
+    }
+}
+
+/// Rounds a converted value so that round trips through the forms stay stable.
+pub fn round(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
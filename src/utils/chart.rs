@@ -0,0 +1,236 @@
+use std::ops::Range;
+
+use airplane::weight_and_balance::Airplane;
+use serde::Serialize;
+
+use crate::models::aircraft::{ChartRanges, Envelope, FuelSystem};
+
+use super::{
+    plane,
+    units::{round, UnitSystem},
+};
+
+const WIDTH: f64 = 500.0;
+const HEIGHT: f64 = 500.0;
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 60.0;
+
+/// Space around the envelope and the loading on axes derived from the envelope, as a fraction
+/// of their span.
+const PADDING: f64 = 0.1;
+
+#[derive(Serialize)]
+pub struct Tick {
+    pub position: f64,
+    pub label: String,
+}
+
+/// Point of the loading on the chart, with its mass, centre of gravity and moment in the units
+/// of the chart.
+#[derive(Serialize)]
+pub struct ChartPoint {
+    pub x: f64,
+    pub y: f64,
+    pub mass: f64,
+    pub cg: f64,
+    pub moment: f64,
+    pub within_limits: bool,
+}
+
+#[derive(Serialize)]
+pub struct WeightBalanceChart {
+    pub width: f64,
+    pub height: f64,
+    pub left: f64,
+    pub right: f64,
+    pub top: f64,
+    pub bottom: f64,
+    pub x_label: String,
+    pub y_label: String,
+    pub mass_unit: &'static str,
+    pub cg_unit: &'static str,
+    pub moment_unit: &'static str,
+    pub x_ticks: Vec<Tick>,
+    pub y_ticks: Vec<Tick>,
+    pub envelope: String,
+    pub takeoff: ChartPoint,
+    pub landing: ChartPoint,
+    /// Text alternative of the chart with the plotted values and whether they are within the
+    /// limits, for screen readers and printouts.
+    pub description: String,
+}
+
+/// Builds a mass versus moment chart. Without fixed ranges the axes span the envelope and the
+/// take-off and landing points.
+pub fn weight_and_balance_chart(
+    plane: &Airplane,
+    envelope: &Envelope,
+    fuel: &FuelSystem,
+    unit_system: UnitSystem,
+    ranges: Option<&ChartRanges>,
+) -> WeightBalanceChart {
+    let Envelope {
+        min_mass,
+        max_mass,
+        forward_cg,
+        aft_cg,
+    } = *envelope;
+
+    let corners = [
+        (min_mass * forward_cg, min_mass),
+        (max_mass * forward_cg, max_mass),
+        (max_mass * aft_cg, max_mass),
+        (min_mass * aft_cg, min_mass),
+    ];
+    let takeoff = (plane::total_moment(plane), plane.total_mass().kilo());
+    let landing = (
+        plane::total_moment_landing(plane, fuel),
+        plane.total_mass_landing().kilo(),
+    );
+
+    let (moment_range, mass_range) = match ranges {
+        Some(r) => (r.moment.0..r.moment.1, r.mass.0..r.mass.1),
+        None => derived_ranges(corners.iter().chain([&takeoff, &landing])),
+    };
+
+    let x_range = unit_system.moment_from_kilo_meter(moment_range.start)
+        ..unit_system.moment_from_kilo_meter(moment_range.end);
+    let y_range =
+        unit_system.mass_from_kilo(mass_range.start)..unit_system.mass_from_kilo(mass_range.end);
+
+    let right = WIDTH - MARGIN_RIGHT;
+    let bottom = HEIGHT - MARGIN_BOTTOM;
+
+    let to_x = |moment: f64| {
+        MARGIN_LEFT
+            + (unit_system.moment_from_kilo_meter(moment) - x_range.start)
+                / (x_range.end - x_range.start)
+                * (right - MARGIN_LEFT)
+    };
+    let to_y = |mass: f64| {
+        bottom
+            - (unit_system.mass_from_kilo(mass) - y_range.start) / (y_range.end - y_range.start)
+                * (bottom - MARGIN_TOP)
+    };
+
+    let x_ticks = ticks(&x_range)
+        .into_iter()
+        .map(|v| Tick {
+            position: MARGIN_LEFT
+                + (v - x_range.start) / (x_range.end - x_range.start) * (right - MARGIN_LEFT),
+            label: format!("{:.0}", v),
+        })
+        .collect();
+
+    let y_ticks = ticks(&y_range)
+        .into_iter()
+        .map(|v| Tick {
+            position: bottom
+                - (v - y_range.start) / (y_range.end - y_range.start) * (bottom - MARGIN_TOP),
+            label: format!("{:.0}", v),
+        })
+        .collect();
+
+    let point = |(moment, mass): (f64, f64)| ChartPoint {
+        x: to_x(moment),
+        y: to_y(mass),
+        mass: round(unit_system.mass_from_kilo(mass), 1),
+        cg: round(unit_system.cg_from_meter(moment / mass), 1),
+        moment: round(unit_system.moment_from_kilo_meter(moment), 1),
+        within_limits: (min_mass..=max_mass).contains(&mass)
+            && (forward_cg..=aft_cg).contains(&(moment / mass)),
+    };
+
+    let envelope = corners
+        .iter()
+        .map(|(moment, mass)| format!("{:.2},{:.2}", to_x(*moment), to_y(*mass)))
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let (takeoff, landing) = (point(takeoff), point(landing));
+    let describe = |name: &str, p: &ChartPoint| {
+        format!(
+            "{}: {} {}, zwaartepunt {} {}, moment {} {}, {} de limieten.",
+            name,
+            p.mass,
+            unit_system.mass_unit(),
+            p.cg,
+            unit_system.cg_unit(),
+            p.moment,
+            unit_system.moment_unit(),
+            if p.within_limits { "binnen" } else { "buiten" }
+        )
+    };
+    let description = format!(
+        "{} {} Toegestaan is {} tot {} {} met het zwaartepunt van {} tot {} {}.",
+        describe("Start", &takeoff),
+        describe("Landing", &landing),
+        round(unit_system.mass_from_kilo(min_mass), 1),
+        round(unit_system.mass_from_kilo(max_mass), 1),
+        unit_system.mass_unit(),
+        round(unit_system.cg_from_meter(forward_cg), 1),
+        round(unit_system.cg_from_meter(aft_cg), 1),
+        unit_system.cg_unit()
+    );
+
+    WeightBalanceChart {
+        width: WIDTH,
+        height: HEIGHT,
+        left: MARGIN_LEFT,
+        right,
+        top: MARGIN_TOP,
+        bottom,
+        x_label: format!("Moment [{}]", unit_system.moment_unit()),
+        y_label: format!("Mass [{}]", unit_system.mass_unit()),
+        mass_unit: unit_system.mass_unit(),
+        cg_unit: unit_system.cg_unit(),
+        moment_unit: unit_system.moment_unit(),
+        x_ticks,
+        y_ticks,
+        envelope,
+        takeoff,
+        landing,
+        description,
+    }
+}
+
+/// Moment and mass ranges that hold all points, in kilogram meters and kilograms, with some
+/// padding so no point lies on the border of the chart.
+fn derived_ranges<'a>(points: impl Iterator<Item = &'a (f64, f64)>) -> (Range<f64>, Range<f64>) {
+    let mut moment = f64::INFINITY..f64::NEG_INFINITY;
+    let mut mass = f64::INFINITY..f64::NEG_INFINITY;
+
+    for (m, kg) in points.filter(|(m, kg)| m.is_finite() && kg.is_finite()) {
+        moment = moment.start.min(*m)..moment.end.max(*m);
+        mass = mass.start.min(*kg)..mass.end.max(*kg);
+    }
+
+    let pad = |range: Range<f64>| {
+        let margin = (range.end - range.start) * PADDING;
+        range.start - margin..range.end + margin
+    };
+
+    (pad(moment), pad(mass))
+}
+
+/// Returns round tick values within the range, aiming for about eight ticks.
+fn ticks(range: &Range<f64>) -> Vec<f64> {
+    let raw_step = (range.end - range.start) / 8.0;
+    let magnitude = 10f64.powf(raw_step.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .iter()
+        .map(|f| f * magnitude)
+        .find(|s| *s >= raw_step)
+        .unwrap_or(10.0 * magnitude);
+
+    let mut values = vec![];
+    let mut value = (range.start / step).ceil() * step;
+    while value <= range.end {
+        values.push(value);
+        value += step;
+    }
+
+    values
+}
@@ -1 +1,4 @@
-pub mod plane;
+pub mod chart;
+pub mod theme;
+
+pub use acm_weight_and_balance::{plane, units};
@@ -0,0 +1,162 @@
+use actix_web::{web, HttpRequest};
+
+use crate::models::query_params::ThemeQueryParams;
+
+/// Cookie that keeps the theme chosen by the pilot.
+pub const COOKIE: &str = "theme";
+
+/// Colour theme of the pages and the charts. The system theme follows the colour scheme of the
+/// device, the red night theme keeps the eyes adapted to the dark in the cockpit.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+    RedNight,
+}
+
+/// Colours of the weight and balance chart, and the filter that recolours the performance
+/// charts, which are drawings from the flight manual with fixed colours.
+struct ChartPalette {
+    background: &'static str,
+    ink: &'static str,
+    grid: &'static str,
+    envelope_fill: &'static str,
+    envelope_stroke: &'static str,
+    load: &'static str,
+    filter: &'static str,
+}
+
+const LIGHT: ChartPalette = ChartPalette {
+    background: "#ffffff",
+    ink: "#111827",
+    grid: "#e5e7eb",
+    envelope_fill: "#c7d2fe",
+    envelope_stroke: "#4f46e5",
+    load: "#dc2626",
+    filter: "none",
+};
+
+const DARK: ChartPalette = ChartPalette {
+    background: "#111827",
+    ink: "#e5e7eb",
+    grid: "#374151",
+    envelope_fill: "#3730a3",
+    envelope_stroke: "#a5b4fc",
+    load: "#f87171",
+    filter: "url(#chart-dark)",
+};
+
+const RED_NIGHT: ChartPalette = ChartPalette {
+    background: "#000000",
+    ink: "#b91c1c",
+    grid: "#450a0a",
+    envelope_fill: "#450a0a",
+    envelope_stroke: "#dc2626",
+    load: "#ef4444",
+    filter: "url(#chart-red-night)",
+};
+
+/// Filters for the performance charts: the dark one inverts the lightness and keeps the hues,
+/// the red night one draws the lightness inverted in red only.
+const CHART_FILTERS: &str = r#"<defs>
+<filter id="chart-dark" color-interpolation-filters="sRGB">
+<feColorMatrix type="matrix" values="-1 0 0 0 1 0 -1 0 0 1 0 0 -1 0 1 0 0 0 1 0"/>
+<feColorMatrix type="hueRotate" values="180"/>
+</filter>
+<filter id="chart-red-night" color-interpolation-filters="sRGB">
+<feColorMatrix type="matrix" values="-0.2126 -0.7152 -0.0722 0 1 0 0 0 0 0 0 0 0 0 0 0 0 0 1 0"/>
+</filter>
+</defs>"#;
+
+impl Theme {
+    pub fn parse(value: &str) -> Option<Theme> {
+        match value {
+            "system" => Some(Theme::System),
+            "light" => Some(Theme::Light),
+            "dark" => Some(Theme::Dark),
+            "red-night" => Some(Theme::RedNight),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Theme::System => "system",
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::RedNight => "red-night",
+        }
+    }
+
+    /// Theme of a request, a `theme` in the query takes precedence over the cookie so the
+    /// printout can ask for light charts.
+    pub fn from_request(req: &HttpRequest) -> Theme {
+        web::Query::<ThemeQueryParams>::from_query(req.query_string())
+            .ok()
+            .and_then(|q| q.into_inner().theme)
+            .or_else(|| req.cookie(COOKIE).map(|c| c.value().to_string()))
+            .and_then(|t| Theme::parse(&t))
+            .unwrap_or_default()
+    }
+
+    /// Filters and style sheet of the server rendered charts, to be placed inside their `svg`
+    /// element.
+    pub fn chart_style(&self) -> String {
+        let rules = match self {
+            Theme::System => format!(
+                "{}@media (prefers-color-scheme: dark) {{\n{}}}\n",
+                chart_rules(&LIGHT),
+                chart_rules(&DARK)
+            ),
+            Theme::Light => chart_rules(&LIGHT),
+            Theme::Dark => chart_rules(&DARK),
+            Theme::RedNight => chart_rules(&RED_NIGHT),
+        };
+
+        format!("{}\n<style>\n{}</style>", CHART_FILTERS, rules)
+    }
+
+    /// Recolours an SVG drawn by another library, such as the weight and balance table of the
+    /// visualizer, with the filter of the performance charts.
+    pub fn recolour(&self, svg: &str) -> String {
+        let start = svg
+            .find("<svg")
+            .and_then(|i| svg[i..].find('>').map(|j| i + j + 1));
+        let end = svg.rfind("</svg>");
+
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => format!(
+                "{}\n{}\n<g class=\"chart-recolour\">{}</g>{}",
+                &svg[..start],
+                self.chart_style(),
+                &svg[start..end],
+                &svg[end..]
+            ),
+            _ => svg.to_string(),
+        }
+    }
+}
+
+fn chart_rules(palette: &ChartPalette) -> String {
+    format!(
+        ".chart-background {{ fill: {}; }}\n\
+         .chart-ink {{ fill: {}; }}\n\
+         .chart-frame {{ stroke: {}; }}\n\
+         .chart-grid {{ stroke: {}; }}\n\
+         .chart-envelope {{ fill: {}; stroke: {}; }}\n\
+         .chart-load {{ fill: {}; }}\n\
+         .chart-load-line {{ stroke: {}; }}\n\
+         .chart-recolour {{ filter: {}; }}\n",
+        palette.background,
+        palette.ink,
+        palette.ink,
+        palette.grid,
+        palette.envelope_fill,
+        palette.envelope_stroke,
+        palette.load,
+        palette.load,
+        palette.filter
+    )
+}
@@ -0,0 +1,474 @@
+//! JavaScript bindings of the calculation core. Every function takes a JSON document and returns
+//! one, masses are in kilograms, lever arms in meters, volumes in liters and wind in knots with
+//! a negative value for a tailwind. Invalid input is reported as a thrown error.
+
+use std::{mem, time::Duration};
+
+use airplane::{
+    types::{FuelType, VolumeType},
+    weight_and_balance::{LeverArm, Mass, Volume},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aircraft::{
+        Aircraft, AircraftRegistry, AircraftType, FuelPolicy, FuelPrices, WeighingPolicy,
+        DEFAULT_AIRCRAFT_TYPE,
+    },
+    date,
+    fuel::{FuelPlan, Refuel},
+    performance::{performance_wind, PerfQueryParams},
+    plane::{self, PlaneInput},
+    route::{AirportDatabase, RoutePlan, Wind},
+};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Load {
+    mass: f64,
+    /// Position of an adjustable seat, `f` (forward), `m` (middle) or `b` (backward).
+    seat: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MassAndBalanceInput {
+    aircraft: Aircraft,
+    /// Type of the aircraft, a type of the embedded registry when it is left out.
+    aircraft_type: Option<AircraftType>,
+    #[serde(default)]
+    fuel_policy: FuelPolicy,
+    /// Validity of the weighing reports, checked on the flight date when it is given.
+    #[serde(default)]
+    weighing_policy: WeighingPolicy,
+    /// Load on each station of the type in the order of its stations, the first is the pilot.
+    /// Stations that are left out or `null` are empty.
+    #[serde(default)]
+    loads: Vec<Option<Load>>,
+    /// Pilot, passenger and baggage of the input from before the loading stations, they load
+    /// the first three stations. Only accepted without `loads`.
+    pilot: Option<Load>,
+    passenger: Option<Load>,
+    baggage: Option<f64>,
+    /// Identifiers of the optional equipment of the aircraft carried on the flight.
+    #[serde(default)]
+    equipment: Vec<String>,
+    fuel_type: String,
+    /// Fuel on board, ignored when `fuel_max` is set.
+    fuel: Option<f64>,
+    /// Fuel on board per tank for types with several tanks, in the order of the tanks.
+    fuel_tanks: Option<Vec<f64>>,
+    #[serde(default)]
+    fuel_max: bool,
+    trip_minutes: u64,
+    /// Flight date as `YYYY-MM-DD` selecting the weighing report, the latest report when it is
+    /// left out.
+    flight_date: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LoadItem {
+    name: String,
+    mass: f64,
+    lever_arm: f64,
+}
+
+#[derive(Serialize)]
+struct MassAndBalanceOutput {
+    loads: Vec<LoadItem>,
+    takeoff_mass: f64,
+    takeoff_cg: f64,
+    landing_mass: f64,
+    landing_cg: f64,
+    /// Usable fuel on board in liters.
+    fuel_on_board: f64,
+    within_limits: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FuelPlanInput {
+    #[serde(default)]
+    fuel_policy: FuelPolicy,
+    fuel: f64,
+    trip_minutes: u64,
+    alternate_minutes: u64,
+    /// Fuel on board before refuelling, in liters.
+    fuel_before: Option<f64>,
+    /// Price per liter of the fuel grade.
+    fuel_price: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct FuelPlanOutput {
+    #[serde(flatten)]
+    plan: FuelPlan,
+    sufficient: bool,
+    refuel: Option<Refuel>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RoutePlanInput {
+    /// Airport codes of the embedded database or `latitude,longitude` positions.
+    waypoints: Vec<String>,
+    /// True airspeed in knots.
+    tas: f64,
+    #[serde(default)]
+    wind: Wind,
+}
+
+#[derive(Serialize)]
+struct RoutePlanOutput {
+    #[serde(flatten)]
+    plan: RoutePlan,
+    trip_minutes: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PerformanceInput {
+    /// Aircraft type, the id of a type of the embedded registry or a type as in
+    /// `data/aircraft.toml`.
+    #[serde(default = "default_aircraft_type")]
+    aircraft_type: TypeInput,
+    oat: f64,
+    pressure_altitude: f64,
+    wind: f64,
+    /// Gust speed in knots, in the direction of the wind.
+    #[serde(default)]
+    gust: Option<f64>,
+    /// Conditions at the destination, the departure conditions by default.
+    #[serde(default)]
+    landing_oat: Option<f64>,
+    #[serde(default)]
+    landing_pressure_altitude: Option<f64>,
+    #[serde(default)]
+    landing_wind: Option<f64>,
+    takeoff_mass: f64,
+    landing_mass: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TypeInput {
+    Id(String),
+    Type(Box<AircraftType>),
+}
+
+#[derive(Serialize)]
+struct PerformanceOutput {
+    torr: f64,
+    todr: f64,
+    lgrr: f64,
+    ldr: f64,
+}
+
+#[wasm_bindgen(js_name = massAndBalance)]
+pub fn mass_and_balance(input: &str) -> Result<String, JsError> {
+    let mut input: MassAndBalanceInput = parse(input)?;
+    let station_loads = station_loads(&mut input)?;
+    let aircraft_type = match input.aircraft_type {
+        Some(t) => t,
+        None => embedded_type(&input.aircraft.aircraft_type)?,
+    };
+    let registry = AircraftRegistry {
+        fuel_policy: input.fuel_policy,
+        fuel_prices: FuelPrices::default(),
+        weighing_policy: input.weighing_policy,
+        types: vec![aircraft_type],
+        aircraft: vec![input.aircraft],
+    };
+    registry.validate().map_err(|e| JsError::new(&e))?;
+    let aircraft = &registry.aircraft[0];
+    let aircraft_type = registry.type_of(aircraft);
+    let stations = &aircraft_type.stations;
+
+    if station_loads.len() > stations.len() {
+        return Err(JsError::new(&format!(
+            "loads has more than the {} stations of the type",
+            stations.len()
+        )));
+    }
+
+    if station_loads.first().is_none_or(Option::is_none) {
+        return Err(JsError::new("the load of the pilot is required"));
+    }
+
+    for (station, load) in stations.iter().zip(&station_loads) {
+        if let Some(load) = load {
+            if !(load.mass.is_finite()
+                && load.mass >= 0.0
+                && station.max_mass.is_none_or(|max| load.mass <= max))
+            {
+                return Err(JsError::new(&format!("invalid load of {}", station.name)));
+            }
+        }
+    }
+
+    let flight_date = match input.flight_date.as_deref() {
+        Some(d) => Some(date::parse_date(d).ok_or_else(|| JsError::new("invalid flight_date"))?),
+        None => None,
+    };
+    let weighing = aircraft
+        .weighing(flight_date)
+        .ok_or_else(|| JsError::new("no weighing report valid on the flight date"))?;
+    if let Some(on) = flight_date {
+        if !registry.weighing_policy.valid(&weighing, on) {
+            return Err(JsError::new(
+                "the weighing report has expired on the flight date",
+            ));
+        }
+    }
+
+    let equipment = input
+        .equipment
+        .iter()
+        .map(|id| {
+            aircraft
+                .equipment
+                .iter()
+                .find(|item| item.id == *id)
+                .cloned()
+                .ok_or_else(|| JsError::new(&format!("unknown equipment {}", id)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let fuel_type = match input.fuel_type.as_str() {
+        "avgas" => FuelType::Avgas,
+        "mogas" => FuelType::Mogas,
+        other => return Err(JsError::new(&format!("unknown fuel type {}", other))),
+    };
+
+    let fuel_extra = if input.fuel_max {
+        None
+    } else {
+        let tanks = aircraft_type.fuel.tanks().len();
+        let liters = match (input.fuel_tanks, input.fuel) {
+            (Some(quantities), _) => quantities,
+            (None, Some(fuel)) if tanks == 1 => vec![fuel],
+            _ => return Err(JsError::new("fuel is required unless fuel_max is set")),
+        };
+
+        if liters.len() != tanks || !liters.iter().all(|f| f.is_finite() && *f >= 0.0) {
+            return Err(JsError::new(&format!(
+                "fuel_tanks needs a quantity for each of the {} tanks",
+                tanks
+            )));
+        }
+
+        let max_fuel = registry.fuel_policy(aircraft_type).max_fuel;
+        for (tank, liters) in aircraft_type.fuel.tanks().iter().zip(&liters) {
+            let capacity = tank.capacity.unwrap_or(max_fuel);
+            if *liters > capacity {
+                return Err(JsError::new(&format!(
+                    "{} holds at most {} liters of usable fuel",
+                    tank.name, capacity
+                )));
+            }
+        }
+
+        Some(
+            liters
+                .into_iter()
+                .map(|liters| match fuel_type {
+                    FuelType::Avgas => Mass::Avgas(Volume::Liter(liters)),
+                    FuelType::Mogas => Mass::Mogas(Volume::Liter(liters)),
+                })
+                .collect(),
+        )
+    };
+
+    let loads = plane::station_moments(
+        stations,
+        &station_loads
+            .iter()
+            .map(|l| l.as_ref().map(|l| l.mass))
+            .collect::<Vec<_>>(),
+        &station_loads
+            .iter()
+            .map(|l| l.as_ref().and_then(|l| l.seat.clone()).unwrap_or_default())
+            .collect::<Vec<_>>(),
+    );
+
+    let airplane = plane::build_plane(PlaneInput {
+        aircraft,
+        aircraft_type,
+        weighing: &weighing,
+        equipment: &equipment,
+        fuel_policy: &registry.fuel_policy(aircraft_type),
+        loads,
+        fuel_type,
+        fuel_unit: VolumeType::Liter,
+        fuel_extra,
+        fuel_max: input.fuel_max,
+        trip_duration: Duration::from_secs(input.trip_minutes * 60),
+    });
+
+    let takeoff_mass = airplane.total_mass().kilo();
+    let landing_mass = airplane.total_mass_landing().kilo();
+
+    to_json(&MassAndBalanceOutput {
+        loads: airplane
+            .moments()
+            .iter()
+            .map(|m| {
+                let LeverArm::Meter(arm) = m.lever_arm();
+                LoadItem {
+                    name: m.name().to_string(),
+                    mass: m.mass().kilo(),
+                    lever_arm: *arm,
+                }
+            })
+            .collect(),
+        takeoff_mass,
+        takeoff_cg: plane::total_moment(&airplane) / takeoff_mass,
+        landing_mass,
+        landing_cg: plane::total_moment_landing(&airplane, &aircraft_type.fuel) / landing_mass,
+        fuel_on_board: plane::fuel_on_board(&airplane, &aircraft_type.fuel),
+        within_limits: plane::within_limits(&airplane, &aircraft_type.fuel),
+    })
+}
+
+#[wasm_bindgen(js_name = fuelPlan)]
+pub fn fuel_plan(input: &str) -> Result<String, JsError> {
+    let input: FuelPlanInput = parse(input)?;
+    input.fuel_policy.validate().map_err(|e| JsError::new(&e))?;
+
+    let plan = FuelPlan::new(
+        &input.fuel_policy,
+        input.fuel,
+        Duration::from_secs(input.trip_minutes * 60),
+        Duration::from_secs(input.alternate_minutes * 60),
+    );
+
+    to_json(&FuelPlanOutput {
+        sufficient: plan.sufficient(),
+        plan,
+        refuel: input
+            .fuel_before
+            .map(|before| Refuel::new(before, input.fuel, input.fuel_price)),
+    })
+}
+
+#[wasm_bindgen(js_name = routePlan)]
+pub fn route_plan(input: &str) -> Result<String, JsError> {
+    let input: RoutePlanInput = parse(input)?;
+    let airports = AirportDatabase::load(None).map_err(|e| JsError::new(&e))?;
+    let waypoints = input
+        .waypoints
+        .iter()
+        .map(|w| airports.waypoint(w))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| JsError::new(&e))?;
+
+    let plan = RoutePlan::new(&waypoints, input.tas, input.wind).map_err(|e| JsError::new(&e))?;
+
+    to_json(&RoutePlanOutput {
+        trip_minutes: plan.trip_duration().as_secs() / 60,
+        plan,
+    })
+}
+
+#[wasm_bindgen]
+pub fn performance(input: &str) -> Result<String, JsError> {
+    let input: PerformanceInput = parse(input)?;
+    let aircraft_type = match input.aircraft_type {
+        TypeInput::Id(id) => embedded_type(&id)?,
+        TypeInput::Type(t) => {
+            t.validate().map_err(|e| JsError::new(&e))?;
+            *t
+        }
+    };
+    let model = aircraft_type
+        .performance_model()
+        .ok_or_else(|| JsError::new("no performance model for this aircraft type"))?;
+    let wind = performance_wind(input.wind, input.gust);
+    let max_tailwind = aircraft_type.wind_limits.max_tailwind;
+    if [Some(wind), input.landing_wind]
+        .into_iter()
+        .flatten()
+        .any(|w| -w > max_tailwind)
+    {
+        return Err(JsError::new(&format!(
+            "the tailwind exceeds the limit of {} kt of the type",
+            max_tailwind
+        )));
+    }
+
+    let params = |mtow: f64, oat: f64, pressure_altitude: f64, wind: f64| PerfQueryParams {
+        oat,
+        pressure_altitude,
+        mtow,
+        wind: wind.abs(),
+        wind_direction: if wind < 0.0 { "tailwind" } else { "headwind" }.to_string(),
+    };
+
+    let takeoff = params(input.takeoff_mass, input.oat, input.pressure_altitude, wind);
+    let landing = params(
+        input.landing_mass,
+        input.landing_oat.unwrap_or(input.oat),
+        input
+            .landing_pressure_altitude
+            .unwrap_or(input.pressure_altitude),
+        input.landing_wind.unwrap_or(wind),
+    );
+    for p in [&takeoff, &landing] {
+        p.validate(&model.scale()).map_err(|e| JsError::new(&e))?;
+    }
+
+    let takeoff = model.takeoff(takeoff);
+    let landing = model.landing(landing);
+
+    to_json(&PerformanceOutput {
+        torr: takeoff.ground_roll,
+        todr: takeoff.distance,
+        lgrr: landing.ground_roll,
+        ldr: landing.distance,
+    })
+}
+
+fn default_aircraft_type() -> TypeInput {
+    TypeInput::Id(DEFAULT_AIRCRAFT_TYPE.to_string())
+}
+
+fn embedded_type(id: &str) -> Result<AircraftType, JsError> {
+    AircraftRegistry::load(None)
+        .map_err(|e| JsError::new(&e))?
+        .aircraft_type(id)
+        .cloned()
+        .ok_or_else(|| JsError::new(&format!("unknown aircraft type {}", id)))
+}
+
+/// Loads of the stations, from `loads` or from the pilot, passenger and baggage of the input
+/// from before the loading stations.
+fn station_loads(input: &mut MassAndBalanceInput) -> Result<Vec<Option<Load>>, JsError> {
+    let legacy = [
+        input.pilot.take(),
+        input.passenger.take(),
+        input.baggage.take().map(|mass| Load { mass, seat: None }),
+    ];
+    if legacy.iter().all(Option::is_none) {
+        return Ok(mem::take(&mut input.loads));
+    }
+    if !input.loads.is_empty() {
+        return Err(JsError::new(
+            "give either loads or the pilot, passenger and baggage",
+        ));
+    }
+
+    let mut loads = Vec::from(legacy);
+    while loads.last().is_some_and(Option::is_none) {
+        loads.pop();
+    }
+    Ok(loads)
+}
+
+fn parse<T: DeserializeOwned>(input: &str) -> Result<T, JsError> {
+    serde_json::from_str(input).map_err(|e| JsError::new(&format!("invalid input: {}", e)))
+}
+
+fn to_json<T: Serialize>(output: &T) -> Result<String, JsError> {
+    serde_json::to_string(output).map_err(|e| JsError::new(&e.to_string()))
+}
@@ -0,0 +1,207 @@
+//! Aircraft types of the registry: validation, the type of each aircraft and its loading
+//! stations.
+
+mod common;
+
+use std::fs;
+
+use acm_weight_and_balance::{
+    aircraft::{AircraftRegistry, DEFAULT_AIRCRAFT_TYPE},
+    plane::station_moments,
+};
+use airplane::weight_and_balance::LeverArm;
+use common::{registry, Server, FLIGHT};
+
+const TYPE: &str = r#"
+[[aircraft_type]]
+id = "TST"
+name = "Test"
+performance = "aquila-at01"
+
+[aircraft_type.envelope]
+min_mass = 500.0
+max_mass = 800.0
+forward_cg = 0.4
+aft_cg = 0.5
+
+[[aircraft_type.station]]
+name = "Pilot"
+positions = { forward = 0.4, middle = 0.5, backward = 0.6 }
+
+[[aircraft_type.station]]
+name = "Baggage"
+arm = 1.2
+max_mass = 20.0
+
+[aircraft_type.fuel]
+lever_arm = 0.3
+"#;
+
+const AIRCRAFT: &str = r#"
+[[aircraft]]
+callsign = "PHTST"
+type = "TST"
+empty_mass = 500.0
+empty_mass_arm = 0.4
+"#;
+
+fn registry_with_type(aircraft_type: &str) -> Result<AircraftRegistry, String> {
+    registry(&format!("{}{}", aircraft_type, AIRCRAFT))
+}
+
+#[test]
+fn embedded_registry_is_valid() {
+    let registry = AircraftRegistry::load(None).unwrap();
+    let aircraft_type = registry.aircraft_type(DEFAULT_AIRCRAFT_TYPE).unwrap();
+
+    assert!(aircraft_type.performance_model().is_some());
+    assert_eq!(aircraft_type.stations[0].name, "Piloot");
+    for aircraft in &registry.aircraft {
+        assert_eq!(registry.type_of(aircraft).id, aircraft.aircraft_type);
+    }
+}
+
+#[test]
+fn registry_without_types_uses_the_embedded_types() {
+    let path = format!("{}/legacy-aircraft.toml", env!("CARGO_TARGET_TMPDIR"));
+    fs::write(
+        &path,
+        "[[aircraft]]\ncallsign = \"PHOLD\"\nempty_mass = 517.0\nempty_mass_arm = 0.43\n",
+    )
+    .unwrap();
+
+    let registry = AircraftRegistry::load(Some(path.as_ref())).unwrap();
+    let aircraft = registry.get("PHOLD").unwrap();
+    assert_eq!(aircraft.aircraft_type, DEFAULT_AIRCRAFT_TYPE);
+    assert_eq!(registry.type_of(aircraft).id, DEFAULT_AIRCRAFT_TYPE);
+}
+
+#[test]
+fn type_of_an_aircraft_must_exist() {
+    assert!(registry_with_type(TYPE).is_ok());
+    assert!(registry_with_type(&TYPE.replace("\"TST\"", "\"OTHER\"")).is_err());
+    assert!(registry(&format!("{}{}{}", TYPE, TYPE, AIRCRAFT)).is_err());
+}
+
+#[test]
+fn performance_model_must_be_known() {
+    assert!(registry_with_type(&TYPE.replace("aquila-at01", "unknown")).is_err());
+
+    let registry =
+        registry_with_type(&TYPE.replace("performance = \"aquila-at01\"\n", "")).unwrap();
+    assert!(registry
+        .aircraft_type("TST")
+        .unwrap()
+        .performance_model()
+        .is_none());
+}
+
+#[test]
+fn envelope_must_not_be_empty() {
+    assert!(registry_with_type(&TYPE.replace("max_mass = 800.0", "max_mass = 500.0")).is_err());
+    assert!(registry_with_type(&TYPE.replace("aft_cg = 0.5", "aft_cg = 0.4")).is_err());
+    assert!(registry_with_type(&TYPE.replace("forward_cg = 0.4", "forward_cg = -0.4")).is_err());
+}
+
+#[test]
+fn stations_need_a_single_kind_of_arm() {
+    let both = TYPE.replace(
+        "arm = 1.2",
+        "arm = 1.2\npositions = { forward = 1.0, middle = 1.1, backward = 1.2 }",
+    );
+    assert!(registry_with_type(&both).is_err());
+    assert!(registry_with_type(&TYPE.replace("arm = 1.2\n", "")).is_err());
+    assert!(registry_with_type(&TYPE.replace("arm = 1.2", "arm = 0.0")).is_err());
+    assert!(registry_with_type(&TYPE.replace("max_mass = 20.0", "max_mass = -1.0")).is_err());
+}
+
+#[test]
+fn stations_have_unique_names() {
+    assert!(registry_with_type(&TYPE.replace("\"Baggage\"", "\"Pilot\"")).is_err());
+    assert!(registry_with_type(&TYPE.replace("\"Baggage\"", "\" \"")).is_err());
+}
+
+#[test]
+fn adjustable_seats_have_an_arm_per_position() {
+    let registry = registry_with_type(TYPE).unwrap();
+    let stations = &registry.aircraft_type("TST").unwrap().stations;
+
+    assert!(stations[0].adjustable());
+    assert_eq!(stations[0].lever_arm("f"), 0.4);
+    assert_eq!(stations[0].lever_arm("m"), 0.5);
+    assert_eq!(stations[0].lever_arm("b"), 0.6);
+    assert_eq!(stations[0].lever_arm(""), 0.5);
+
+    assert!(!stations[1].adjustable());
+    assert_eq!(stations[1].lever_arm("f"), 1.2);
+}
+
+#[test]
+fn moments_of_the_loaded_stations() {
+    let registry = registry_with_type(TYPE).unwrap();
+    let stations = &registry.aircraft_type("TST").unwrap().stations;
+
+    let moments = station_moments(
+        stations,
+        &[Some(80.0), Some(15.0), Some(5.0)],
+        &["b".to_string()],
+    );
+    assert_eq!(moments.len(), 2);
+    assert_eq!(moments[0].name(), "Pilot");
+    assert_eq!(moments[0].mass().kilo(), 80.0);
+    let LeverArm::Meter(arm) = moments[0].lever_arm();
+    assert_eq!(*arm, 0.6);
+
+    // Empty stations have no moment.
+    let moments = station_moments(stations, &[Some(80.0), None], &[]);
+    assert_eq!(moments.len(), 1);
+    let LeverArm::Meter(arm) = moments[0].lever_arm();
+    assert_eq!(*arm, 0.5);
+}
+
+#[test]
+fn loadsheets_need_a_performance_model() {
+    let registry = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/data/aircraft.toml"))
+        .unwrap()
+        .replace("performance = \"aquila-at01\"\n", "");
+    let path = format!(
+        "{}/aircraft-without-performance.toml",
+        env!("CARGO_TARGET_TMPDIR")
+    );
+    fs::write(&path, registry).unwrap();
+    let server = Server::start_with(&["--aircraft-file", &path]);
+    let sign_off = "pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-10-19&declaration=true";
+
+    let body = server.get(&format!("/export?{}", FLIGHT)).body;
+    assert!(body.contains("Voor dit vliegtuigtype zijn geen prestatiegegevens beschikbaar."));
+
+    let body = server
+        .get(&format!("/export?{}&{}&submit=Opslaan", FLIGHT, sign_off))
+        .body;
+    assert!(body.contains("er kan geen loadsheet worden opgeslagen."));
+    assert!(!body.contains("form.action = '/print?"));
+
+    let response = server.post(&format!("/print?{}&{}", FLIGHT, sign_off), &[]);
+    assert_eq!(response.status, 400);
+}
+
+#[test]
+fn landing_outside_the_limits_is_reported() {
+    // The take-off at 434 mm is within this aft limit, the landing at 436.1 mm is not.
+    let registry = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/data/aircraft.toml"))
+        .unwrap()
+        .replace("aft_cg = 0.523", "aft_cg = 0.435");
+    let path = format!("{}/aircraft-landing-aft.toml", env!("CARGO_TARGET_TMPDIR"));
+    fs::write(&path, registry).unwrap();
+    let server = Server::start_with(&["--aircraft-file", &path]);
+
+    let body = server.get(&format!("/fuel?{}", FLIGHT)).body;
+    assert!(body.contains("valt het zwaartepunt bij de landing buiten"));
+
+    let body = server.get(&format!("/export?{}", FLIGHT)).body;
+    assert!(body.contains("Gewicht en balans vallen buiten de limieten."));
+
+    let sign_off = "pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-10-19&declaration=true";
+    let response = server.post(&format!("/print?{}&{}", FLIGHT, sign_off), &[]);
+    assert_eq!(response.status, 400);
+}
@@ -0,0 +1,93 @@
+//! Weight and balance chart: axes from the envelope of the type and the interactive chart.
+
+mod common;
+
+use std::fs;
+
+use common::{Server, FLIGHT};
+
+/// Labels of the ticks and the legend of a chart, in the order they are drawn.
+fn labels(svg: &str) -> Vec<&str> {
+    svg.split("class=\"chart-ink\">")
+        .skip(1)
+        .filter_map(|s| s.split_once("</text>"))
+        .map(|(label, _)| label)
+        .collect()
+}
+
+#[test]
+fn axes_span_the_envelope() {
+    let server = Server::start();
+    let response = server.get(&format!("/wb-chart?{}", FLIGHT));
+
+    assert_eq!(response.status, 200);
+    // The envelope of 558 to 750 kg between 427 and 523 mm, with some space around it.
+    assert_eq!(
+        labels(&response.body)[..13],
+        [
+            "225", "250", "275", "300", "325", "350", "375", "400", "550", "600", "650", "700",
+            "750"
+        ]
+    );
+}
+
+#[test]
+fn axes_with_fixed_ranges_of_the_type() {
+    let registry = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/data/aircraft.toml"))
+        .unwrap()
+        .replace(
+            "# chart = { moment = [230.0, 420.0], mass = [550.0, 760.0] }",
+            "chart = { moment = [100.0, 500.0], mass = [400.0, 900.0] }",
+        );
+    let path = format!("{}/chart-aircraft.toml", env!("CARGO_TARGET_TMPDIR"));
+    fs::write(&path, registry).unwrap();
+
+    let server = Server::start_with(&["--aircraft-file", &path]);
+    let response = server.get(&format!("/wb-chart?{}", FLIGHT));
+
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        labels(&response.body)[..15],
+        [
+            "100", "150", "200", "250", "300", "350", "400", "450", "500", "400", "500", "600",
+            "700", "800", "900"
+        ]
+    );
+}
+
+#[test]
+fn interactive_chart_shows_the_points_and_loads() {
+    let server = Server::start();
+    let response = server.get(&format!("/wb-chart-interactive?{}", FLIGHT));
+
+    assert_eq!(response.status, 200);
+    assert!(response
+        .body
+        .contains("<title>Take-off: 640.2 kg, CG 434 mm, moment 277.9 kg m</title>"));
+    assert!(response
+        .body
+        .contains("<title>Landing: 628 kg, CG 436.1 mm, moment 273.9 kg m</title>"));
+    // Sliders for the stations after the pilot and for the fuel.
+    assert!(response
+        .body
+        .contains(r#"type="range" id="what-if-load-1" min="0" max="192""#));
+    assert!(response
+        .body
+        .contains(r#"type="range" id="what-if-load-2" min="0" max="40""#));
+    assert!(response
+        .body
+        .contains(r#"type="range" id="what-if-tank-0" min="0" max="110""#));
+}
+
+#[test]
+fn what_if_loads_move_the_points() {
+    let server = Server::start();
+    let response = server.get(&format!(
+        "/wb-chart-interactive?{}",
+        FLIGHT.replace("loads=80", "loads=80,80,20")
+    ));
+
+    assert_eq!(response.status, 200);
+    assert!(response.body.contains("<title>Take-off: 740.2 kg,"));
+    assert!(response.body.contains("<title>Landing: 728 kg,"));
+}
@@ -0,0 +1,233 @@
+//! Fixtures shared by the tests: registries, planes and performance parameters for the tests
+//! of the calculation core, and the application server for the tests that go through its HTTP
+//! interface.
+
+#![allow(dead_code)]
+
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use acm_weight_and_balance::{
+    aircraft::{AircraftRegistry, AircraftType, Equipment, DEFAULT_AIRCRAFT_TYPE},
+    performance::PerfQueryParams,
+    plane::{build_plane, station_moments, PlaneInput},
+};
+use airplane::{
+    types::{FuelType, VolumeType},
+    weight_and_balance::{Airplane, Mass, Volume},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use flate2::{write::DeflateEncoder, Compression};
+
+/// Query of a flight of the embedded fleet with the pilot alone, up to the calculations.
+pub const FLIGHT: &str = "callsign=PHDHA&loads=80&oat=15&pressure_altitude=0&wind=5\
+                          &wind_direction=headwind&fuel_type=avgas&fuel_unit=liter\
+                          &fuel_extra=60&trip_duration=01:00&alternate_duration=00:30";
+
+/// Parses and validates a registry, one without aircraft types gets the embedded types.
+pub fn registry(content: &str) -> Result<AircraftRegistry, String> {
+    let mut registry: AircraftRegistry = toml::from_str(content).map_err(|e| e.to_string())?;
+    if registry.types.is_empty() {
+        registry.types = AircraftRegistry::load(None).unwrap().types;
+    }
+    registry.validate()?;
+    Ok(registry)
+}
+
+/// Aircraft type of the embedded registry.
+pub fn embedded_type() -> AircraftType {
+    AircraftRegistry::load(None)
+        .unwrap()
+        .aircraft_type(DEFAULT_AIRCRAFT_TYPE)
+        .cloned()
+        .unwrap()
+}
+
+/// Plane of the first aircraft of the registry with a pilot of 80 kg on the first station and
+/// the given equipment, for a trip of an hour. The fuel is avgas in liters per tank, without
+/// fuel the tanks are filled up to the maximum.
+pub fn plane(
+    registry: &AircraftRegistry,
+    equipment: &[Equipment],
+    fuel: Option<&[f64]>,
+) -> Airplane {
+    let aircraft = &registry.aircraft[0];
+    let aircraft_type = registry.type_of(aircraft);
+
+    build_plane(PlaneInput {
+        aircraft,
+        aircraft_type,
+        weighing: &aircraft.weighing(None).unwrap(),
+        equipment,
+        fuel_policy: &registry.fuel_policy(aircraft_type),
+        loads: station_moments(&aircraft_type.stations, &[Some(80.0)], &[]),
+        fuel_type: FuelType::Avgas,
+        fuel_unit: VolumeType::Liter,
+        fuel_extra: fuel.map(|tanks| {
+            tanks
+                .iter()
+                .map(|liters| Mass::Avgas(Volume::Liter(*liters)))
+                .collect()
+        }),
+        fuel_max: fuel.is_none(),
+        trip_duration: Duration::from_secs(3600),
+    })
+}
+
+/// Performance parameters with the wind in knots, negative for a tailwind.
+pub fn perf_params(oat: f64, pressure_altitude: f64, mass: f64, wind: f64) -> PerfQueryParams {
+    PerfQueryParams {
+        oat,
+        pressure_altitude,
+        mtow: mass,
+        wind: wind.abs(),
+        wind_direction: if wind < 0.0 { "tailwind" } else { "headwind" }.to_string(),
+    }
+}
+
+/// Encodes form values in a state token of the given version, as the application does.
+pub fn encode_state(version: char, values: &[(&str, &str)]) -> String {
+    let values: serde_json::Map<String, serde_json::Value> = values
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string().into()))
+        .collect();
+
+    encode_json(version, &serde_json::to_vec(&values).unwrap())
+}
+
+/// Encodes any JSON in a state token of the given version.
+pub fn encode_json(version: char, json: &[u8]) -> String {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(json).unwrap();
+    format!(
+        "{}{}",
+        version,
+        URL_SAFE_NO_PAD.encode(encoder.finish().unwrap())
+    )
+}
+
+/// Server running the built binary with the embedded fleet, stopped when dropped.
+pub struct Server {
+    process: Child,
+    address: String,
+    health_address: String,
+}
+
+/// Response with its status code and its body.
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+pub fn free_address() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+/// Command that runs the built binary, without its output.
+pub fn command() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_acm_weight_and_balance"));
+    command.stdout(Stdio::null()).stderr(Stdio::null());
+    command
+}
+
+impl Server {
+    pub fn start() -> Server {
+        Server::start_with(&[])
+    }
+
+    /// Starts the server with extra command line flags, such as another aircraft file.
+    pub fn start_with(args: &[&str]) -> Server {
+        let (address, health_address) = (free_address(), free_address());
+        let mut command = command();
+        command
+            .args(["--listen", &address, "--health-listen", &health_address])
+            .args(args);
+
+        Server::spawn(command, address, health_address)
+    }
+
+    /// Runs the command and waits until the server accepts connections on both addresses.
+    pub fn spawn(mut command: Command, address: String, health_address: String) -> Server {
+        let process = command.spawn().unwrap();
+
+        let started = Instant::now();
+        for address in [&address, &health_address] {
+            while TcpStream::connect(address).is_err() {
+                assert!(
+                    started.elapsed() < Duration::from_secs(10),
+                    "server did not start"
+                );
+                thread::sleep(Duration::from_millis(20));
+            }
+        }
+
+        Server {
+            process,
+            address,
+            health_address,
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Response {
+        self.request(path, &[])
+    }
+
+    /// Sends a GET request with the given headers, such as a cookie.
+    pub fn request(&self, path: &str, headers: &[(&str, &str)]) -> Response {
+        send(&self.address, "GET", path, headers)
+    }
+
+    /// Sends a POST request without a body, the form is in the query.
+    pub fn post(&self, path: &str, headers: &[(&str, &str)]) -> Response {
+        let mut headers = headers.to_vec();
+        headers.push(("Content-Length", "0"));
+        send(&self.address, "POST", path, &headers)
+    }
+
+    /// Process id of the server, to send it signals.
+    pub fn pid(&self) -> u32 {
+        self.process.id()
+    }
+
+    /// Sends a GET request to the health and metrics server.
+    pub fn health(&self, path: &str) -> Response {
+        send(&self.health_address, "GET", path, &[])
+    }
+}
+
+fn send(address: &str, method: &str, path: &str, headers: &[(&str, &str)]) -> Response {
+    let mut stream = TcpStream::connect(address).unwrap();
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+        method, path, address
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut response = vec![];
+    stream.read_to_end(&mut response).unwrap();
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap();
+
+    Response {
+        status: status.parse().unwrap(),
+        body: body.to_string(),
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,98 @@
+//! Configuration from command line flags, environment variables and the configuration file.
+
+mod common;
+
+use std::{fs, process::Stdio};
+
+use common::{command, free_address, Server};
+
+/// Writes a configuration file to the temporary directory of the tests.
+fn config_file(name: &str, content: &str) -> String {
+    let path = format!("{}/{}", env!("CARGO_TARGET_TMPDIR"), name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+/// Starts the server on the addresses picked from the given sources, the flag, the
+/// environment variable and the file, and checks that it listens on the one that takes
+/// precedence.
+fn listen(flag: bool, env: bool, file: bool) {
+    let addresses = [free_address(), free_address(), free_address()];
+    let health_address = free_address();
+    let mut command = command();
+    command.args(["--health-listen", &health_address]);
+    if flag {
+        command.args(["--listen", &addresses[0]]);
+    }
+    if env {
+        command.env("ACM_LISTEN", &addresses[1]);
+    }
+    if file {
+        let name = format!("config-{}-{}.toml", flag, env);
+        let path = config_file(&name, &format!("listen = \"{}\"\n", addresses[2]));
+        command.args(["--config", &path]);
+    }
+
+    let address = match (flag, env) {
+        (true, _) => &addresses[0],
+        (false, true) => &addresses[1],
+        (false, false) => &addresses[2],
+    };
+    let server = Server::spawn(command, address.clone(), health_address);
+    assert_eq!(server.get("/").status, 200);
+}
+
+#[test]
+fn flags_take_precedence_over_the_environment_and_the_file() {
+    listen(true, true, true);
+}
+
+#[test]
+fn environment_takes_precedence_over_the_file() {
+    listen(false, true, true);
+}
+
+#[test]
+fn file_is_used_without_flags_and_environment() {
+    listen(false, false, true);
+}
+
+#[test]
+fn zero_workers_are_rejected() {
+    let path = config_file("config-workers.toml", "workers = 0\n");
+    let output = command()
+        .args([
+            "--listen",
+            &free_address(),
+            "--health-listen",
+            &free_address(),
+        ])
+        .args(["--config", &path])
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("workers must be at least 1"));
+
+    let output = command()
+        .args(["--workers", "0"])
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("workers must be at least 1"));
+}
+
+#[test]
+fn unknown_settings_in_the_file_are_rejected() {
+    let path = config_file("config-unknown.toml", "listen_address = \"127.0.0.1:80\"\n");
+    let output = command()
+        .args(["--config", &path])
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unable to parse"));
+}
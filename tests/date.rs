@@ -0,0 +1,96 @@
+//! Calendar arithmetic of the weighing validity period.
+
+use acm_weight_and_balance::{
+    aircraft::{Weighing, WeighingPolicy},
+    date::{add_months, parse_date},
+};
+
+fn weighing(date: &str) -> Weighing {
+    Weighing {
+        date: Some(date.to_string()),
+        reference: Some("WR-1".to_string()),
+        empty_mass: 517.0,
+        empty_mass_arm: 0.4294,
+    }
+}
+
+#[test]
+fn month_end_is_clamped() {
+    assert_eq!(add_months((2023, 1, 31), 1), (2023, 2, 28));
+    assert_eq!(add_months((2023, 3, 31), 1), (2023, 4, 30));
+    assert_eq!(add_months((2023, 8, 31), 4), (2023, 12, 31));
+    assert_eq!(add_months((2023, 11, 30), 3), (2024, 2, 29));
+}
+
+#[test]
+fn months_roll_over_the_year() {
+    assert_eq!(add_months((2023, 12, 15), 1), (2024, 1, 15));
+    assert_eq!(add_months((2020, 6, 1), 48), (2024, 6, 1));
+    assert_eq!(add_months((2020, 6, 1), 0), (2020, 6, 1));
+}
+
+#[test]
+fn leap_years() {
+    assert_eq!(parse_date("2024-02-29"), Some((2024, 2, 29)));
+    assert_eq!(parse_date("2000-02-29"), Some((2000, 2, 29)));
+    assert_eq!(parse_date("2023-02-29"), None);
+    assert_eq!(parse_date("1900-02-29"), None);
+
+    assert_eq!(add_months((2024, 2, 29), 12), (2025, 2, 28));
+    assert_eq!(add_months((2024, 2, 29), 48), (2028, 2, 29));
+    assert_eq!(add_months((2099, 2, 28), 12), (2100, 2, 28));
+}
+
+#[test]
+fn invalid_dates_are_rejected() {
+    for value in [
+        "",
+        "2024",
+        "2024-13-01",
+        "2024-00-10",
+        "2024-04-31",
+        "2024-04-00",
+        "2024-04-10-01",
+        "24-04-10",
+        "2024/04/10",
+    ] {
+        assert_eq!(parse_date(value), None, "{}", value);
+    }
+}
+
+#[test]
+fn report_is_valid_up_to_and_including_the_last_day() {
+    let policy = WeighingPolicy {
+        validity_months: Some(48),
+    };
+    let report = weighing("2020-03-15");
+
+    assert!(policy.valid(&report, (2020, 3, 15)));
+    assert!(policy.valid(&report, (2024, 3, 15)));
+    assert!(!policy.valid(&report, (2024, 3, 16)));
+}
+
+#[test]
+fn boundary_of_a_report_at_the_end_of_february() {
+    let policy = WeighingPolicy {
+        validity_months: Some(12),
+    };
+    let report = weighing("2024-02-29");
+
+    assert!(policy.valid(&report, (2025, 2, 28)));
+    assert!(!policy.valid(&report, (2025, 3, 1)));
+}
+
+#[test]
+fn reports_without_a_date_need_a_policy_without_validity() {
+    let report = Weighing {
+        date: None,
+        ..weighing("2020-03-15")
+    };
+
+    assert!(WeighingPolicy::default().valid(&report, (2030, 1, 1)));
+    assert!(!WeighingPolicy {
+        validity_months: Some(48)
+    }
+    .valid(&report, (2020, 3, 15)));
+}
@@ -0,0 +1,128 @@
+//! Text alternatives of the charts with the plotted values and verdicts.
+
+mod common;
+
+use std::fs;
+
+use common::{Server, FLIGHT};
+
+const PERF: &str = "type=AT01&units=metric&oat=15&pressure_altitude=0&mtow=700";
+
+const SIGN_OFF: &str = "pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-10-19&declaration=true";
+
+#[test]
+fn weight_and_balance_chart_describes_its_points() {
+    let server = Server::start();
+    let response = server.get(&format!("/wb-chart?{}", FLIGHT));
+
+    assert_eq!(response.status, 200);
+    assert!(response
+        .body
+        .contains(r#"role="img" aria-labelledby="wb-chart-title wb-chart-desc""#));
+    assert!(response
+        .body
+        .contains(r#"<title id="wb-chart-title">Massa en zwaartepunt</title>"#));
+    assert!(response.body.contains(
+        "<desc id=\"wb-chart-desc\">Start: 640.2 kg, zwaartepunt 434 mm, moment 277.9 kg m, \
+         binnen de limieten. Landing: 628 kg, zwaartepunt 436.1 mm, moment 273.9 kg m, binnen \
+         de limieten. Toegestaan is 558 tot 750 kg met het zwaartepunt van 427 tot 523 mm.</desc>"
+    ));
+}
+
+#[test]
+fn weight_and_balance_chart_describes_an_overload() {
+    let server = Server::start();
+    let response = server.get(&format!(
+        "/wb-chart?{}",
+        FLIGHT.replace("loads=80", "loads=80,150,40")
+    ));
+
+    assert_eq!(response.status, 200);
+    assert!(response.body.contains("Start: 830.2 kg,"));
+    assert!(response.body.contains("buiten de limieten."));
+}
+
+#[test]
+fn performance_charts_describe_their_distances() {
+    let server = Server::start();
+
+    let response = server.get(&format!(
+        "/perf-tod?{}&wind=5&wind_direction=headwind",
+        PERF
+    ));
+    assert_eq!(response.status, 200);
+    assert!(response
+        .body
+        .contains(r#"role="img" aria-labelledby="perf-tod-title perf-tod-desc""#));
+    assert!(response
+        .body
+        .contains(r#"<title id="perf-tod-title">Startafstand"#));
+    assert!(response
+        .body
+        .contains("Bij 15 °C, drukhoogte 0 ft, 700 kg en 5 kt tegenwind: startrol"));
+
+    let response = server.get(&format!(
+        "/perf-ldr?{}&wind=10&wind_direction=tailwind",
+        PERF
+    ));
+    assert_eq!(response.status, 200);
+    assert!(response
+        .body
+        .contains(r#"<title id="perf-ldr-title">Landingsafstand"#));
+    assert!(response.body.contains(
+        "Bij 15 °C, drukhoogte 0 ft, 700 kg en 10 kt rugwind: landingsrol 300 m, \
+         landingsafstand over 15 m 720 m. De rugwind is binnen het maximum van 10 kt uit het \
+         vlieghandboek."
+    ));
+}
+
+#[test]
+fn tailwind_above_the_limit_of_the_type_is_described() {
+    let registry = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/data/aircraft.toml"))
+        .unwrap()
+        .replace("max_tailwind = 10.0", "max_tailwind = 5.0");
+    let path = format!("{}/description-aircraft.toml", env!("CARGO_TARGET_TMPDIR"));
+    fs::write(&path, registry).unwrap();
+
+    let server = Server::start_with(&["--aircraft-file", &path]);
+    let response = server.get(&format!(
+        "/perf-ldr?{}&wind=8&wind_direction=tailwind",
+        PERF
+    ));
+
+    assert_eq!(response.status, 200);
+    assert!(response
+        .body
+        .contains("De rugwind is boven het maximum van 5 kt uit het vlieghandboek."));
+}
+
+#[test]
+fn pages_expose_the_descriptions() {
+    let server = Server::start();
+
+    let response = server.get(&format!("/fuel?{}", FLIGHT));
+    assert!(response
+        .body
+        .contains(r#"alt="Massa en zwaartepunt. Start: 640.2 kg,"#));
+    assert!(response
+        .body
+        .contains(r#"alt="Startafstand. Bij 15 °C, drukhoogte 0 ft, 640 kg"#));
+    assert!(response
+        .body
+        .contains(r#"alt="Landingsafstand. Bij 15 °C, drukhoogte 0 ft, 628 kg"#));
+
+    // The printout shows them as captions next to the charts.
+    let response = server.post(&format!("/print?{}&{}", FLIGHT, SIGN_OFF), &[]);
+    assert_eq!(response.status, 200);
+    for caption in ["wb-chart-caption", "perf-tod-caption", "perf-ldr-caption"] {
+        assert!(response
+            .body
+            .contains(&format!(r#"aria-describedby="{}""#, caption)));
+    }
+    assert!(response
+        .body
+        .contains(r#"<figcaption id="wb-chart-caption" class="text-xs">Start: 640.2 kg,"#));
+    assert!(response.body.contains(
+        r#"<figcaption id="perf-ldr-caption" class="text-xs">Landingsafstand. Bij 15 °C,"#
+    ));
+}
@@ -0,0 +1,69 @@
+//! Optional equipment selected per flight, loaded on top of the empty mass.
+
+mod common;
+
+use acm_weight_and_balance::plane;
+use airplane::weight_and_balance::LeverArm;
+use common::{plane, registry};
+
+const AIRCRAFT: &str = r#"
+[[aircraft]]
+callsign = "PHTST"
+empty_mass = 517.0
+empty_mass_arm = 0.43
+
+[[aircraft.equipment]]
+id = "tow-hook"
+name = "Sleephaak"
+mass = 2.5
+arm = 2.1
+
+[[aircraft.equipment]]
+id = "spinner-cover"
+name = "Spinnerhoes"
+mass = 0.4
+arm = -0.6
+"#;
+
+#[test]
+fn selected_items_are_loaded_at_their_arm() {
+    let registry = registry(AIRCRAFT).unwrap();
+    let equipment = &registry.aircraft[0].equipment;
+    let without = plane(&registry, &[], Some(&[60.0]));
+    let with = plane(&registry, equipment, Some(&[60.0]));
+
+    let item = with
+        .moments()
+        .iter()
+        .find(|m| m.name() == "Sleephaak")
+        .unwrap();
+    assert_eq!(item.mass().kilo(), 2.5);
+    let LeverArm::Meter(arm) = item.lever_arm();
+    assert_eq!(*arm, 2.1);
+
+    let mass = with.total_mass().kilo() - without.total_mass().kilo();
+    assert!((mass - 2.9).abs() < 1e-9);
+    let moment = plane::total_moment(&with) - plane::total_moment(&without);
+    assert!((moment - (2.5 * 2.1 - 0.4 * 0.6)).abs() < 1e-9);
+}
+
+#[test]
+fn items_that_are_not_selected_are_left_out() {
+    let registry = registry(AIRCRAFT).unwrap();
+    let tow_hook = registry.aircraft[0].equipment[..1].to_vec();
+    let plane = plane(&registry, &tow_hook, Some(&[60.0]));
+
+    assert!(plane.moments().iter().any(|m| m.name() == "Sleephaak"));
+    assert!(!plane.moments().iter().any(|m| m.name() == "Spinnerhoes"));
+}
+
+#[test]
+fn items_are_validated() {
+    assert!(registry(AIRCRAFT).is_ok());
+    assert!(registry(&AIRCRAFT.replace("\"spinner-cover\"", "\"tow-hook\"")).is_err());
+    assert!(registry(&AIRCRAFT.replace("\"tow-hook\"", "\"tow hook\"")).is_err());
+    assert!(registry(&AIRCRAFT.replace("\"tow-hook\"", "\"\"")).is_err());
+    assert!(registry(&AIRCRAFT.replace("\"Sleephaak\"", "\" \"")).is_err());
+    assert!(registry(&AIRCRAFT.replace("mass = 2.5", "mass = 0.0")).is_err());
+    assert!(registry(&AIRCRAFT.replace("arm = 2.1", "arm = nan")).is_err());
+}
@@ -0,0 +1,128 @@
+//! Unusable fuel, oil and other fluids that the empty mass of the flight manual excludes.
+
+mod common;
+
+use std::time::Duration;
+
+use acm_weight_and_balance::{
+    fuel::FuelPlan,
+    plane::{self, UNUSABLE_FUEL},
+};
+use airplane::weight_and_balance::{LeverArm, Mass, Volume};
+use common::{plane, registry};
+
+const TYPE: &str = r#"
+[[aircraft_type]]
+id = "TST"
+name = "Test"
+
+[aircraft_type.envelope]
+min_mass = 500.0
+max_mass = 800.0
+forward_cg = 0.35
+aft_cg = 0.5
+
+[[aircraft_type.station]]
+name = "Pilot"
+arm = 0.5
+
+[aircraft_type.fuel]
+lever_arm = 0.3
+max_fuel = 100.0
+unusable = 4.0
+
+[[aircraft_type.fluids]]
+name = "Oil"
+mass = 2.5
+arm = -0.5
+
+[[aircraft]]
+callsign = "PHTST"
+type = "TST"
+empty_mass = 500.0
+empty_mass_arm = 0.4
+"#;
+
+#[test]
+fn fluids_and_unusable_fuel_are_loaded_after_the_empty_mass() {
+    let registry = registry(TYPE).unwrap();
+    let plane = plane(&registry, &[], Some(&[50.0]));
+    let names: Vec<&str> = plane.moments().iter().map(|m| m.name().as_str()).collect();
+
+    assert_eq!(names, ["Empty Mass", "Oil", UNUSABLE_FUEL, "Pilot", "Fuel"]);
+
+    let oil = &plane.moments()[1];
+    assert_eq!(oil.mass().kilo(), 2.5);
+    let LeverArm::Meter(arm) = oil.lever_arm();
+    assert_eq!(*arm, -0.5);
+
+    let unusable = &plane.moments()[2];
+    assert_eq!(
+        unusable.mass().kilo(),
+        Mass::Avgas(Volume::Liter(4.0)).kilo()
+    );
+    let LeverArm::Meter(arm) = unusable.lever_arm();
+    assert_eq!(*arm, 0.3);
+}
+
+#[test]
+fn unusable_fuel_is_not_on_board_for_the_planning() {
+    let registry = registry(TYPE).unwrap();
+    let aircraft_type = registry.aircraft_type("TST").unwrap();
+    let plane = plane(&registry, &[], Some(&[50.0]));
+
+    let on_board = plane::fuel_on_board(&plane, &aircraft_type.fuel);
+    assert_eq!(on_board, 50.0);
+
+    let policy = registry.fuel_policy(aircraft_type);
+    let fuel_plan = FuelPlan::new(
+        &policy,
+        on_board,
+        Duration::from_secs(3600),
+        Duration::from_secs(1800),
+    );
+    assert_eq!(fuel_plan.total, 50.0);
+    assert_eq!(
+        fuel_plan.endurance_minutes,
+        (50.0 / policy.consumption_per_hour * 60.0) as u64
+    );
+}
+
+#[test]
+fn maximum_fuel_is_the_usable_fuel() {
+    let fleet = registry(TYPE).unwrap();
+    let aircraft_type = fleet.aircraft_type("TST").unwrap();
+    assert_eq!(fleet.fuel_policy(aircraft_type).max_fuel, 96.0);
+
+    let plane = plane(&fleet, &[], None);
+    assert_eq!(plane::fuel_on_board(&plane, &aircraft_type.fuel), 96.0);
+
+    assert!(registry(&TYPE.replace("unusable = 4.0", "unusable = 100.0")).is_err());
+}
+
+#[test]
+fn types_without_unusable_fuel_or_fluids_load_the_empty_mass_alone() {
+    let plain = TYPE.replace("unusable = 4.0\n", "").replace(
+        "[[aircraft_type.fluids]]\nname = \"Oil\"\nmass = 2.5\narm = -0.5\n",
+        "",
+    );
+    let registry = registry(&plain).unwrap();
+    let plane = plane(&registry, &[], Some(&[50.0]));
+    let names: Vec<&str> = plane.moments().iter().map(|m| m.name().as_str()).collect();
+
+    assert_eq!(names, ["Empty Mass", "Pilot", "Fuel"]);
+    assert_eq!(
+        registry
+            .fuel_policy(registry.aircraft_type("TST").unwrap())
+            .max_fuel,
+        100.0
+    );
+}
+
+#[test]
+fn fluids_and_unusable_fuel_are_validated() {
+    assert!(registry(&TYPE.replace("unusable = 4.0", "unusable = -1.0")).is_err());
+    assert!(registry(&TYPE.replace("name = \"Oil\"", "name = \" \"")).is_err());
+    assert!(registry(&TYPE.replace("mass = 2.5", "mass = 0.0")).is_err());
+    assert!(registry(&TYPE.replace("arm = -0.5", "arm = nan")).is_err());
+}
@@ -0,0 +1,95 @@
+//! Landing performance at the destination, with conditions that default to those at departure.
+
+mod common;
+
+use acm_weight_and_balance::performance::{self, STANDARD_QNH};
+use common::{perf_params, Server, FLIGHT};
+
+const DEPARTURE: &str = "oat=15&pressure_altitude=0&wind=5&wind_direction=headwind";
+
+fn landing(oat: f64, pressure_altitude: f64, wind: f64) -> f64 {
+    performance::model("aquila-at01")
+        .unwrap()
+        .landing(perf_params(oat, pressure_altitude, 700.0, wind))
+        .distance
+}
+
+#[test]
+fn pressure_altitude_from_the_elevation_and_qnh() {
+    assert_eq!(performance::pressure_altitude(1500.0, STANDARD_QNH), 1500.0);
+    assert_eq!(performance::pressure_altitude(1500.0, 1003.25), 1770.0);
+    assert_eq!(performance::pressure_altitude(0.0, 1023.25), -270.0);
+}
+
+#[test]
+fn landing_distance_follows_the_conditions() {
+    let sea_level = landing(15.0, 0.0, 0.0);
+
+    assert!(landing(15.0, 2000.0, 0.0) > sea_level);
+    assert!(landing(30.0, 0.0, 0.0) > sea_level);
+    assert!(landing(15.0, 0.0, -5.0) > sea_level);
+    assert!(landing(15.0, 0.0, 10.0) < sea_level);
+}
+
+#[test]
+fn landing_conditions_default_to_the_departure() {
+    let server = Server::start();
+    let response = server.get(&format!("/fuel?{}", FLIGHT));
+
+    assert_eq!(response.status, 200);
+    assert!(response
+        .body
+        .contains(&format!("/perf-ldr?type=AT01&units=metric&{}", DEPARTURE)));
+    assert!(response
+        .body
+        .contains(&format!("/perf-tod?type=AT01&units=metric&{}", DEPARTURE)));
+}
+
+#[test]
+fn landing_conditions_at_the_destination() {
+    let server = Server::start();
+    let response = server.get(&format!(
+        "/fuel?{}&landing_elevation=1500&landing_qnh=1003.25&landing_oat=25&landing_wind=3\
+         &landing_wind_direction=tailwind",
+        FLIGHT
+    ));
+
+    assert_eq!(response.status, 200);
+    assert!(response.body.contains(
+        "/perf-ldr?type=AT01&units=metric&oat=25&pressure_altitude=1770&wind=3\
+         &wind_direction=tailwind"
+    ));
+    // The take-off is still calculated at the departure.
+    assert!(response
+        .body
+        .contains(&format!("/perf-tod?type=AT01&units=metric&{}", DEPARTURE)));
+    assert!(response
+        .body
+        .contains("drukhoogte 1770 ft, rugwind van 3 kt"));
+}
+
+#[test]
+fn landing_conditions_are_validated() {
+    let server = Server::start();
+
+    let response = server.get(&format!("/fuel?{}&landing_qnh=1003", FLIGHT));
+    assert!(response
+        .body
+        .contains("Vul bij de QNH ook de hoogte van de bestemming in."));
+
+    let response = server.get(&format!(
+        "/fuel?{}&landing_elevation=0&landing_qnh=1200",
+        FLIGHT
+    ));
+    assert!(response
+        .body
+        .contains("De QNH moet tussen 900 en 1100 hPa liggen."));
+
+    let response = server.get(&format!(
+        "/fuel?{}&landing_wind=15&landing_wind_direction=tailwind",
+        FLIGHT
+    ));
+    assert!(response
+        .body
+        .contains("overschrijdt het maximum van 10 kt uit het vlieghandboek."));
+}
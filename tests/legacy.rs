@@ -0,0 +1,109 @@
+//! Links and state tokens from before the loading stations, with a pilot, a passenger and
+//! baggage instead of the loads of the stations.
+
+mod common;
+
+use common::{encode_state, Server, FLIGHT};
+
+const CONDITIONS: &str = "oat=15&pressure_altitude=0&wind=5&wind_direction=headwind\
+                          &fuel_type=avgas&fuel_unit=liter&fuel_extra=60&trip_duration=01:00\
+                          &alternate_duration=00:30";
+
+#[test]
+fn old_links_load_the_first_stations() {
+    let server = Server::start();
+    let expected = server
+        .get(&format!(
+            "/wb-chart?{}",
+            FLIGHT.replace("loads=80", "loads=80,70,20&seats=m,b")
+        ))
+        .body;
+    assert!(expected.contains("Start: 730.2 kg,"));
+
+    let response = server.get(&format!(
+        "/wb-chart?callsign=PHDHA&pilot=80&pilot_seat=m&passenger=70&passenger_seat=b\
+         &baggage=20&{}",
+        CONDITIONS
+    ));
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, expected);
+
+    // The form of the old wizard submitted empty fields for the passenger and baggage.
+    let response = server.get(&format!(
+        "/fuel?callsign=PHDHA&pilot=80&pilot_seat=m&passenger=&passenger_seat=m&baggage=&{}",
+        CONDITIONS
+    ));
+    assert!(response
+        .body
+        .contains("Start: 640.2 kg, zwaartepunt 434 mm"));
+}
+
+#[test]
+fn loads_take_precedence_over_the_old_fields() {
+    let server = Server::start();
+    let body = server
+        .get(&format!("/wb-chart?{}&pilot=100&baggage=40", FLIGHT))
+        .body;
+
+    assert!(body.contains("Start: 640.2 kg,"));
+}
+
+#[test]
+fn old_tokens_keep_their_masses() {
+    let server = Server::start();
+    let expected = server
+        .get(&format!(
+            "/wb-chart?{}",
+            FLIGHT.replace("loads=80", "loads=80,70,20&seats=m,b")
+        ))
+        .body;
+
+    // A version 1 token of the old wizard, with the fields it submitted.
+    let token = encode_state(
+        '1',
+        &[
+            ("units", "metric"),
+            ("callsign", "PHDHA"),
+            ("pilot", "80"),
+            ("pilot_seat", "m"),
+            ("passenger", "70"),
+            ("passenger_seat", "b"),
+            ("baggage", "20"),
+            ("oat", "15"),
+            ("pressure_altitude", "0"),
+            ("wind", "5"),
+            ("wind_direction", "headwind"),
+            ("fuel_type", "avgas"),
+            ("fuel_unit", "liter"),
+            ("trip_duration", "01:00"),
+            ("alternate_duration", "00:30"),
+            ("fuel_extra", "60"),
+        ],
+    );
+    let response = server.get(&format!("/wb-chart?state={}", token));
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, expected);
+
+    // The old input form submitted the pilot next to a token without it.
+    let token = encode_state(
+        '1',
+        &[
+            ("callsign", "PHDHA"),
+            ("pilot", "100"),
+            ("oat", "15"),
+            ("pressure_altitude", "0"),
+            ("wind", "5"),
+            ("wind_direction", "headwind"),
+            ("fuel_type", "avgas"),
+            ("fuel_unit", "liter"),
+            ("trip_duration", "01:00"),
+            ("alternate_duration", "00:30"),
+            ("fuel_extra", "60"),
+        ],
+    );
+    let response = server.get(&format!(
+        "/wb-chart?state={}&pilot=80&pilot_seat=m&passenger=70&passenger_seat=b&baggage=20",
+        token
+    ));
+    assert_eq!(response.body, expected);
+}
@@ -0,0 +1,79 @@
+//! Prometheus metrics on the health server: handled requests and printed loadsheets outside
+//! the limits.
+
+mod common;
+
+use std::fs;
+
+use common::{Server, FLIGHT};
+
+const SIGN_OFF: &str = "pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-10-19&declaration=true";
+
+/// Value of a metric line, zero when the line is not there yet.
+fn value(metrics: &str, name: &str) -> u64 {
+    metrics
+        .lines()
+        .find_map(|l| l.strip_prefix(name)?.strip_prefix(' '))
+        .map(|v| v.parse().unwrap())
+        .unwrap_or_default()
+}
+
+#[test]
+fn printed_loadsheets_outside_the_limits_are_counted() {
+    let storage = format!("{}/metrics-storage", env!("CARGO_TARGET_TMPDIR"));
+    fs::create_dir_all(&storage).unwrap();
+    let server = Server::start_with(&["--storage-dir", &storage]);
+    let overload = format!(
+        "/print?{}&{}&override_reason=Test",
+        FLIGHT.replace("loads=80", "loads=80,150,40"),
+        SIGN_OFF
+    );
+
+    assert_eq!(server.post(&overload, &[]).status, 200);
+    assert_eq!(
+        server
+            .post(&format!("/print?{}&{}", FLIGHT, SIGN_OFF), &[])
+            .status,
+        200
+    );
+    let metrics = server.health("/metrics").body;
+    assert_eq!(value(&metrics, "loadsheets_out_of_limits_total"), 1);
+
+    // Printing the same loadsheet again stores nothing new, but it is printed again.
+    assert_eq!(server.post(&overload, &[]).status, 200);
+    let metrics = server.health("/metrics").body;
+    assert_eq!(value(&metrics, "loadsheets_out_of_limits_total"), 2);
+}
+
+#[test]
+fn failed_requests_are_counted_with_their_status() {
+    let server = Server::start();
+
+    // A repeated parameter is rejected by the query extractor.
+    let chart = format!("/wb-chart?{}", FLIGHT);
+    assert_eq!(server.get(&format!("{}&loads=80", chart)).status, 400);
+    assert_eq!(server.get(&chart).status, 200);
+
+    let metrics = server.health("/metrics").body;
+    assert_eq!(
+        value(
+            &metrics,
+            r#"http_requests_total{method="GET",route="/wb-chart",status="400"}"#
+        ),
+        1
+    );
+    assert_eq!(
+        value(
+            &metrics,
+            r#"http_requests_total{method="GET",route="/wb-chart",status="200"}"#
+        ),
+        1
+    );
+}
+
+#[test]
+fn metrics_can_be_disabled() {
+    let server = Server::start_with(&["--features", "imperial-units"]);
+
+    assert_eq!(server.health("/metrics").status, 404);
+}
@@ -0,0 +1,74 @@
+//! Progressive web app: the service worker, the self-hosted assets it caches, the fleet of the
+//! offline calculator and the duration inputs that replaced the pickers from a CDN.
+
+mod common;
+
+use common::{Server, FLIGHT};
+
+#[test]
+fn the_service_worker_is_tied_to_the_release() {
+    let server = Server::start();
+
+    let worker = server.get("/sw.js");
+    assert_eq!(worker.status, 200);
+    assert!(worker
+        .body
+        .contains(&format!("const VERSION = '{}';", env!("CARGO_PKG_VERSION"))));
+    assert!(!worker.body.contains("__VERSION__"));
+}
+
+#[test]
+fn all_assets_are_served_by_the_application() {
+    let server = Server::start();
+
+    for asset in [
+        "/assets/app.css",
+        "/assets/icons.svg",
+        "/assets/offline.html",
+        "/assets/manifest.webmanifest",
+        "/assets/icon.svg",
+    ] {
+        assert_eq!(server.get(asset).status, 200, "{}", asset);
+    }
+    let page = server.get("/").body;
+    assert!(page.contains("/assets/app.css"));
+    assert!(!page.contains("https://cdn"));
+    assert!(!page.contains("fonts.googleapis.com"));
+}
+
+#[test]
+fn durations_must_be_hours_and_minutes() {
+    let server = Server::start();
+
+    for duration in ["01:75", "een uur", "60"] {
+        let flight = FLIGHT.replace(
+            "trip_duration=01:00",
+            &format!("trip_duration={}", duration),
+        );
+        let response = server.get(&format!("/fuel?{}", flight.replace(' ', "%20")));
+        assert_eq!(response.status, 200, "{}", duration);
+        assert!(
+            response.body.contains("Vul de reisduur in als uu:mm."),
+            "{}",
+            duration
+        );
+    }
+
+    let flight = FLIGHT.replace("alternate_duration=00:30", "alternate_duration=0:3x");
+    let response = server.get(&format!("/fuel?{}", flight));
+    assert!(response
+        .body
+        .contains("Vul de reisduur naar de alternatieve luchthaven in als uu:mm."));
+}
+
+#[test]
+fn the_offline_calculator_gets_the_fleet() {
+    let server = Server::start();
+
+    assert_eq!(server.get("/assets/offline.js").status, 200);
+    let fleet = server.get("/fleet.json");
+    assert_eq!(fleet.status, 200);
+    let fleet: serde_json::Value = serde_json::from_str(&fleet.body).unwrap();
+    assert_eq!(fleet["aircraft"][0]["callsign"], "PHDHA");
+    assert!(fleet["fuel_policy"]["max_fuel"].as_f64().unwrap() > 0.0);
+}
@@ -0,0 +1,277 @@
+//! Regression suite for the performance chart calculations.
+//!
+//! The cases live in `tests/golden/performance.json` and are shared with the WebAssembly tests.
+//! They record the output of a reviewed version of the calculations, so they detect changes in
+//! the calculations, not errors in them.
+//!
+//! Construction line snapshots are stored in `tests/golden/construction_lines.json`, run the
+//! tests with `UPDATE_SNAPSHOTS=1` to rewrite them after an intended change.
+
+mod common;
+
+use std::{collections::BTreeMap, env, fmt::Write, fs, path::PathBuf};
+
+use acm_weight_and_balance::{
+    performance::{
+        calculate_aquila_performance_ldr, calculate_aquila_performance_tod, CHART_MAX_DISTANCE,
+    },
+    performance_check::{sweep, Grid},
+};
+use common::perf_params;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+struct Regression {
+    tolerance: f64,
+    cases: Vec<Case>,
+}
+
+#[derive(Deserialize)]
+struct Case {
+    name: String,
+    oat: f64,
+    pressure_altitude: f64,
+    mass: f64,
+    wind: f64,
+    expected: Distances,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+struct Distances {
+    torr: f64,
+    todr: f64,
+    lgrr: f64,
+    ldr: f64,
+}
+
+impl Distances {
+    fn named(&self) -> [(&'static str, f64); 4] {
+        [
+            ("torr", self.torr),
+            ("todr", self.todr),
+            ("lgrr", self.lgrr),
+            ("ldr", self.ldr),
+        ]
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ConstructionLines {
+    tod: String,
+    ldr: String,
+}
+
+fn distances(oat: f64, pressure_altitude: f64, mass: f64, wind: f64) -> Distances {
+    let (.., torr, todr) =
+        calculate_aquila_performance_tod(perf_params(oat, pressure_altitude, mass, wind));
+    let (.., lgrr, ldr) =
+        calculate_aquila_performance_ldr(perf_params(oat, pressure_altitude, mass, wind));
+
+    Distances {
+        torr,
+        todr,
+        lgrr,
+        ldr,
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name)
+}
+
+fn load_cases() -> Regression {
+    let content = fs::read_to_string(golden_path("performance.json")).unwrap();
+    serde_json::from_str(&content).expect("regression cases should be valid")
+}
+
+#[test]
+fn regression_cases() {
+    let regression = load_cases();
+    let mut report = String::new();
+    let mut failures = 0;
+
+    writeln!(
+        report,
+        "| case | distance | expected | actual | deviation |"
+    )
+    .unwrap();
+    writeln!(report, "|---|---|---:|---:|---:|").unwrap();
+
+    for case in &regression.cases {
+        let actual = distances(case.oat, case.pressure_altitude, case.mass, case.wind);
+
+        for ((name, expected), (_, value)) in case.expected.named().iter().zip(actual.named()) {
+            let deviation = value - expected;
+            if deviation.abs() <= f64::EPSILON {
+                continue;
+            }
+
+            let marker = if deviation.abs() > regression.tolerance {
+                failures += 1;
+                " (exceeds tolerance)"
+            } else {
+                ""
+            };
+
+            writeln!(
+                report,
+                "| {} | {} | {:.1} | {:.1} | {:+.1}{} |",
+                case.name, name, expected, value, deviation, marker
+            )
+            .unwrap();
+        }
+    }
+
+    let report_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("performance-deviations.md");
+    fs::write(&report_path, &report).unwrap();
+    println!("{}", report);
+
+    assert_eq!(
+        failures,
+        0,
+        "{} distances deviate more than their tolerance from the regression cases, see {}",
+        failures,
+        report_path.display()
+    );
+}
+
+/// Renders the chart templates the way the `/perf-tod` and `/perf-ldr` routes do and returns
+/// the path of the construction line.
+fn construction_line(template: &str, coordinates: (f64, f64, f64, f64, f64, f64, f64)) -> String {
+    let (oat_x_base, oat_y_base, tom_x, tom_y, wind_x, wind_y, ob_y) = coordinates;
+
+    let mut ctx = tera::Context::new();
+    ctx.insert("oat_x_base", &format!("{:.5}", oat_x_base));
+    ctx.insert("oat_y_base", &format!("{:.5}", oat_y_base));
+    ctx.insert("tom_x", &format!("{:.5}", tom_x));
+    ctx.insert("tom_y", &format!("{:.5}", tom_y));
+    ctx.insert("wind_x", &format!("{:.5}", wind_x));
+    ctx.insert("wind_y", &format!("{:.5}", wind_y));
+    ctx.insert("ob_y", &format!("{:.5}", ob_y));
+    ctx.insert("description", "");
+    ctx.insert("chart_style", "");
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("templates")
+        .join(template);
+    let content = fs::read_to_string(path).unwrap();
+    let rendered = tera::Tera::one_off(&content, &ctx, false).unwrap();
+
+    let start = rendered
+        .find("<path id=\"oat-line\"")
+        .expect("chart should have a construction line");
+    let element = &rendered[start..];
+    let d = &element[element.find(" d=\"").unwrap() + 4..];
+    d[..d.find('"').unwrap()].to_string()
+}
+
+#[test]
+fn construction_line_snapshots() {
+    let regression = load_cases();
+
+    let actual: BTreeMap<String, ConstructionLines> = regression
+        .cases
+        .iter()
+        .map(|case| {
+            let p = || perf_params(case.oat, case.pressure_altitude, case.mass, case.wind);
+            let (a, b, c, d, e, f, g, ..) = calculate_aquila_performance_tod(p());
+            let tod = construction_line("top.svg", (a, b, c, d, e, f, g));
+            let (a, b, c, d, e, f, g, ..) = calculate_aquila_performance_ldr(p());
+            let ldr = construction_line("ld.svg", (a, b, c, d, e, f, g));
+
+            (case.name.clone(), ConstructionLines { tod, ldr })
+        })
+        .collect();
+
+    let path = golden_path("construction_lines.json");
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+        return;
+    }
+
+    let expected: BTreeMap<String, ConstructionLines> =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+    for (name, lines) in &actual {
+        assert_eq!(
+            Some(lines),
+            expected.get(name),
+            "construction lines changed for {}",
+            name
+        );
+    }
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "snapshots without a regression case"
+    );
+}
+
+/// Sweeps a grid over the chart domain and checks that increasing one input never gives a
+/// shorter distance. Distances that run off the chart scale are left out.
+fn assert_monotone(input: &str, step: impl Fn(f64, f64, f64) -> Option<(f64, f64, f64)>) {
+    let mut violations = vec![];
+
+    for oat in (0..=28).map(|i| -30.0 + i as f64 * 2.5) {
+        for pressure_altitude in (0..=32).map(|i| i as f64 * 250.0) {
+            for mass in (0..=20).map(|i| 550.0 + i as f64 * 10.0) {
+                let Some((next_oat, next_pa, next_mass)) = step(oat, pressure_altitude, mass)
+                else {
+                    continue;
+                };
+
+                for wind in [-10.0, -5.0, 0.0, 5.0, 10.0, 15.0, 20.0] {
+                    let before = distances(oat, pressure_altitude, mass, wind);
+                    let after = distances(next_oat, next_pa, next_mass, wind);
+
+                    for ((name, a), (_, b)) in before.named().iter().zip(after.named()) {
+                        if *a < CHART_MAX_DISTANCE && b < CHART_MAX_DISTANCE && b < *a {
+                            violations.push(format!(
+                                "{} {:.1} -> {:.1} m at OAT {}, PA {}, mass {}, wind {}",
+                                name, a, b, oat, pressure_altitude, mass, wind
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    assert!(
+        violations.is_empty(),
+        "a higher {} gives a shorter distance:\n{}",
+        input,
+        violations.join("\n")
+    );
+}
+
+#[test]
+fn hotter_is_never_shorter() {
+    assert_monotone("OAT", |oat, pa, mass| {
+        (oat < 40.0).then_some((oat + 2.5, pa, mass))
+    });
+}
+
+#[test]
+fn higher_is_never_shorter() {
+    assert_monotone("pressure altitude", |oat, pa, mass| {
+        (pa < 8000.0).then_some((oat, pa + 250.0, mass))
+    });
+}
+
+#[test]
+fn heavier_is_never_shorter() {
+    assert_monotone("mass", |oat, pa, mass| {
+        (mass < 750.0).then_some((oat, pa, mass + 10.0))
+    });
+}
+
+#[test]
+fn self_check_finds_no_violations() {
+    let sweep = sweep(&Grid::default());
+
+    let report: Vec<String> = sweep.violations.iter().map(|v| v.to_string()).collect();
+    assert!(report.is_empty(), "{}", report.join("\n"));
+}
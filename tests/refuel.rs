@@ -0,0 +1,98 @@
+//! Fuel to add at the pump, its estimated cost and the fuel grades a type may use.
+
+mod common;
+
+use acm_weight_and_balance::{
+    aircraft::{AircraftType, FuelPrices},
+    fuel::{self, Refuel},
+};
+use airplane::types::FuelType;
+use common::{embedded_type, registry};
+
+const PRICES: &str = r#"
+[fuel_prices]
+currency = "EUR"
+avgas = 2.95
+mogas = 2.10
+
+[[aircraft]]
+callsign = "PHTST"
+empty_mass = 517.0
+empty_mass_arm = 0.43
+"#;
+
+fn aircraft_type(grades: &[&str]) -> AircraftType {
+    let mut aircraft_type = embedded_type();
+    aircraft_type.fuel.grades = grades.iter().map(|g| g.to_string()).collect();
+    aircraft_type
+}
+
+#[test]
+fn uplift_is_the_fuel_missing_on_board() {
+    let refuel = Refuel::new(20.0, 80.0, Some(2.5));
+    assert_eq!(refuel.uplift, 60.0);
+    assert_eq!(refuel.cost, Some(150.0));
+
+    let refuel = Refuel::new(20.0, 80.0, None);
+    assert_eq!(refuel.uplift, 60.0);
+    assert_eq!(refuel.cost, None);
+}
+
+#[test]
+fn nothing_to_add_with_enough_fuel_on_board() {
+    let refuel = Refuel::new(90.0, 80.0, Some(2.5));
+    assert_eq!(refuel.uplift, 0.0);
+    assert_eq!(refuel.cost, Some(0.0));
+}
+
+#[test]
+fn prices_per_grade() {
+    let prices = registry(PRICES).unwrap().fuel_prices;
+    assert_eq!(prices.currency, "EUR");
+    assert_eq!(prices.price(&FuelType::Avgas), Some(2.95));
+    assert_eq!(prices.price(&FuelType::Mogas), Some(2.10));
+
+    let prices = registry(&PRICES.replace("mogas = 2.10\n", ""))
+        .unwrap()
+        .fuel_prices;
+    assert_eq!(prices.price(&FuelType::Mogas), None);
+
+    let prices = FuelPrices::default();
+    assert!(prices.validate().is_ok());
+    assert_eq!(prices.price(&FuelType::Avgas), None);
+}
+
+#[test]
+fn prices_are_validated() {
+    assert!(registry(&PRICES.replace("avgas = 2.95", "avgas = -2.95")).is_err());
+    assert!(registry(&PRICES.replace("mogas = 2.10", "mogas = nan")).is_err());
+    assert!(registry(&PRICES.replace("\"EUR\"", "\" \"")).is_err());
+}
+
+#[test]
+fn types_use_both_grades_unless_limited() {
+    let both = aircraft_type(&["avgas", "mogas"]);
+    assert!(both.validate().is_ok());
+    assert!(both.fuel.allows(&FuelType::Avgas));
+    assert!(both.fuel.allows(&FuelType::Mogas));
+
+    let avgas = aircraft_type(&["avgas"]);
+    assert!(avgas.validate().is_ok());
+    assert!(avgas.fuel.allows(&FuelType::Avgas));
+    assert!(!avgas.fuel.allows(&FuelType::Mogas));
+}
+
+#[test]
+fn grades_are_validated() {
+    assert!(aircraft_type(&[]).validate().is_err());
+    assert!(aircraft_type(&["jet-a1"]).validate().is_err());
+    assert!(aircraft_type(&["avgas", "avgas"]).validate().is_err());
+
+    for fuel_type in [FuelType::Avgas, FuelType::Mogas] {
+        let grade = fuel::grade(&fuel_type);
+        assert_eq!(
+            fuel::parse_grade(grade).map(|f| fuel::grade(&f)),
+            Some(grade)
+        );
+    }
+}
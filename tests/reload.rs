@@ -0,0 +1,102 @@
+//! Reloading the aircraft registry on SIGHUP and on file changes, and the enabled features.
+
+mod common;
+
+use std::{
+    fs,
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::Server;
+
+const AIRCRAFT: &str = include_str!("../data/aircraft.toml");
+
+/// Writes the aircraft file of a test, with the second aircraft under another callsign.
+fn write_fleet(path: &str, callsign: &str) {
+    fs::write(path, AIRCRAFT.replace("PHDHB", callsign)).unwrap();
+}
+
+fn aircraft_file(name: &str, callsign: &str) -> String {
+    let path = format!("{}/{}", env!("CARGO_TARGET_TMPDIR"), name);
+    write_fleet(&path, callsign);
+    path
+}
+
+fn hang_up(server: &Server) {
+    let status = Command::new("kill")
+        .args(["-HUP", &server.pid().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn serves(server: &Server, callsign: &str) -> bool {
+    server
+        .get("/")
+        .body
+        .contains(&format!(r#"<option value="{}""#, callsign))
+}
+
+/// Waits until the fleet of the server has the aircraft.
+fn wait_for(server: &Server, callsign: &str) {
+    let started = Instant::now();
+    while !serves(server, callsign) {
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "{} was not loaded",
+            callsign
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn hangup_reloads_the_aircraft_file() {
+    let path = aircraft_file("reload-hangup.toml", "PHDHX");
+    let server = Server::start_with(&["--aircraft-file", &path]);
+    assert!(serves(&server, "PHDHX"));
+
+    write_fleet(&path, "PHDHY");
+    hang_up(&server);
+    wait_for(&server, "PHDHY");
+    assert!(!serves(&server, "PHDHX"));
+}
+
+#[test]
+fn failed_reload_keeps_the_current_fleet() {
+    let path = aircraft_file("reload-failed.toml", "PHDHX");
+    let server = Server::start_with(&["--aircraft-file", &path]);
+
+    fs::write(&path, "[[aircraft]]\ncallsign = ").unwrap();
+    hang_up(&server);
+    thread::sleep(Duration::from_millis(500));
+    assert!(serves(&server, "PHDHX"));
+    assert_eq!(server.health("/healthz").status, 200);
+
+    // The signals are handled in order, the next good file is loaded after the failed one.
+    write_fleet(&path, "PHDHY");
+    hang_up(&server);
+    wait_for(&server, "PHDHY");
+}
+
+#[test]
+fn changed_aircraft_file_is_reloaded() {
+    let path = aircraft_file("reload-poll.toml", "PHDHX");
+    let server = Server::start_with(&["--aircraft-file", &path, "--reload-interval", "1"]);
+    assert!(serves(&server, "PHDHX"));
+
+    // Polling notices the change without a signal.
+    write_fleet(&path, "PHDHY");
+    wait_for(&server, "PHDHY");
+}
+
+#[test]
+fn unit_selection_follows_the_enabled_features() {
+    let server = Server::start();
+    assert!(server.get("/").body.contains(r#"<select id="units""#));
+
+    let server = Server::start_with(&["--features", "metrics"]);
+    assert!(!server.get("/").body.contains(r#"<select id="units""#));
+}
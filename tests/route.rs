@@ -0,0 +1,287 @@
+//! Route planning: great-circle geometry, the wind triangle and the leg times of a route.
+
+use acm_weight_and_balance::route::{
+    ground_speed, AirportDatabase, Position, RoutePlan, Waypoint, Wind,
+};
+
+/// Nautical miles in one degree of a great circle, from the mean radius of the earth.
+const DEGREE: f64 = 3440.065 * std::f64::consts::PI / 180.0;
+
+fn position(latitude: f64, longitude: f64) -> Position {
+    Position::new(latitude, longitude).unwrap()
+}
+
+fn waypoint(name: &str, latitude: f64, longitude: f64) -> Waypoint {
+    Waypoint {
+        name: name.to_string(),
+        position: position(latitude, longitude),
+    }
+}
+
+fn wind(direction: f64, speed: f64) -> Wind {
+    Wind { direction, speed }
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{} is not within {} of {}",
+        actual,
+        tolerance,
+        expected
+    );
+}
+
+#[test]
+fn distance_along_a_meridian_and_the_equator() {
+    assert_close(
+        position(52.0, 5.0).distance(&position(53.0, 5.0)),
+        DEGREE,
+        1e-9,
+    );
+    assert_close(
+        position(0.0, 5.0).distance(&position(0.0, 6.0)),
+        DEGREE,
+        1e-9,
+    );
+    assert_close(
+        position(52.0, 5.0).distance(&position(52.0, 5.0)),
+        0.0,
+        1e-12,
+    );
+}
+
+#[test]
+fn distance_along_a_parallel_is_shorter_than_at_the_equator() {
+    // One degree of longitude at 60° north is about half a degree of a great circle.
+    let distance = position(60.0, 5.0).distance(&position(60.0, 6.0));
+    assert_close(distance, DEGREE / 2.0, 0.01);
+}
+
+#[test]
+fn distance_is_symmetric() {
+    let (a, b) = (position(52.46, 5.52), position(51.45, 5.37));
+    assert_close(a.distance(&b), b.distance(&a), 1e-9);
+}
+
+#[test]
+fn course_to_the_cardinal_directions() {
+    let origin = position(0.0, 0.0);
+    assert_close(origin.course(&position(1.0, 0.0)), 0.0, 1e-9);
+    assert_close(origin.course(&position(0.0, 1.0)), 90.0, 1e-9);
+    assert_close(origin.course(&position(-1.0, 0.0)), 180.0, 1e-9);
+    assert_close(origin.course(&position(0.0, -1.0)), 270.0, 1e-9);
+}
+
+#[test]
+fn course_back_differs_by_half_a_turn_near_the_equator() {
+    let (a, b) = (position(0.0, 0.0), position(0.5, 0.5));
+    let difference = (b.course(&a) - a.course(&b)).rem_euclid(360.0);
+    assert_close(difference, 180.0, 0.01);
+}
+
+#[test]
+fn positions_outside_the_globe_are_rejected() {
+    assert!(Position::new(91.0, 0.0).is_err());
+    assert!(Position::new(0.0, -181.0).is_err());
+    assert!(Position::new(f64::NAN, 0.0).is_err());
+}
+
+#[test]
+fn headwind_and_tailwind() {
+    assert_close(
+        ground_speed(100.0, 90.0, wind(90.0, 20.0)).unwrap(),
+        80.0,
+        1e-9,
+    );
+    assert_close(
+        ground_speed(100.0, 90.0, wind(270.0, 20.0)).unwrap(),
+        120.0,
+        1e-9,
+    );
+    assert_close(
+        ground_speed(100.0, 90.0, wind(0.0, 0.0)).unwrap(),
+        100.0,
+        1e-9,
+    );
+}
+
+#[test]
+fn crosswind_needs_a_wind_correction() {
+    // With the wind square to the course the aircraft crabs into it: 100² = 60² + 80².
+    assert_close(
+        ground_speed(100.0, 0.0, wind(90.0, 60.0)).unwrap(),
+        80.0,
+        1e-9,
+    );
+    assert_close(
+        ground_speed(100.0, 0.0, wind(270.0, 60.0)).unwrap(),
+        80.0,
+        1e-9,
+    );
+}
+
+#[test]
+fn wind_the_aircraft_cannot_fly_against() {
+    assert_eq!(ground_speed(100.0, 0.0, wind(0.0, 100.0)), None);
+    assert_eq!(ground_speed(100.0, 0.0, wind(90.0, 100.0)), None);
+    assert_eq!(ground_speed(100.0, 0.0, wind(0.0, 120.0)), None);
+}
+
+#[test]
+fn route_adds_up_its_legs() {
+    let waypoints = [
+        waypoint("A", 0.0, 0.0),
+        waypoint("B", 1.0, 0.0),
+        waypoint("C", 1.0, 1.0),
+    ];
+    let plan = RoutePlan::new(&waypoints, 100.0, wind(0.0, 20.0)).unwrap();
+
+    assert_eq!(plan.legs.len(), 2);
+    assert_eq!(
+        (plan.legs[0].from.as_str(), plan.legs[0].to.as_str()),
+        ("A", "B")
+    );
+    assert_close(plan.legs[0].ground_speed, 80.0, 1e-9);
+    assert_close(plan.legs[0].minutes, DEGREE / 80.0 * 60.0, 1e-9);
+    assert_close(
+        plan.distance,
+        plan.legs.iter().map(|l| l.distance).sum(),
+        1e-9,
+    );
+    assert_close(
+        plan.minutes,
+        plan.legs.iter().map(|l| l.minutes).sum(),
+        1e-9,
+    );
+}
+
+#[test]
+fn trip_duration_is_rounded_up_to_whole_minutes() {
+    let waypoints = [waypoint("A", 0.0, 0.0), waypoint("B", 1.0, 0.0)];
+    let plan = RoutePlan::new(&waypoints, 100.0, Wind::default()).unwrap();
+
+    // 60.04 NM at 100 kt is 36.02 minutes.
+    assert_eq!(plan.trip_duration().as_secs(), 37 * 60);
+}
+
+#[test]
+fn invalid_routes_are_rejected() {
+    let waypoints = [waypoint("A", 0.0, 0.0), waypoint("B", 1.0, 0.0)];
+
+    assert!(RoutePlan::new(&waypoints[..1], 100.0, Wind::default()).is_err());
+    assert!(RoutePlan::new(&waypoints, 0.0, Wind::default()).is_err());
+    assert!(RoutePlan::new(&waypoints, 100.0, wind(0.0, -5.0)).is_err());
+    assert!(RoutePlan::new(&waypoints, 100.0, wind(0.0, 100.0)).is_err());
+}
+
+#[test]
+fn waypoints_are_airport_codes_or_coordinates() {
+    let airports = AirportDatabase::load(None).unwrap();
+
+    let home = airports.waypoint("ehle").unwrap();
+    assert_eq!(home.name, "EHLE");
+
+    let position = airports.waypoint("52.5, 5.25").unwrap();
+    assert_eq!(position.position, self::position(52.5, 5.25));
+
+    assert!(airports.waypoint("XXXX").is_err());
+    assert!(airports.waypoint("95,5").is_err());
+    assert_eq!(airports.route("EHLE 52.5,5.25 EHTE").unwrap().len(), 3);
+}
+
+#[test]
+fn embedded_airports_have_an_elevation() {
+    let airports = AirportDatabase::load(None).unwrap();
+
+    assert!(!airports.airports.is_empty());
+    assert!(airports.airports.iter().all(|a| a.elevation.is_some()));
+}
+
+#[test]
+fn nearest_airports_come_first() {
+    let airports = AirportDatabase::load(None).unwrap();
+    let home = airports.find("EHLE").unwrap();
+
+    let nearest = airports.nearest(&home.position());
+    assert_eq!(nearest.len(), airports.airports.len());
+    assert_eq!(nearest[0].0.code, "EHLE");
+    assert_close(nearest[0].1, 0.0, 1e-9);
+    assert!(nearest.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+
+    for (airport, distance) in &nearest {
+        assert_close(
+            *distance,
+            home.position().distance(&airport.position()),
+            1e-9,
+        );
+    }
+}
+
+#[test]
+fn nearest_of_a_small_database() {
+    let airports: AirportDatabase = toml::from_str(
+        r#"
+        [[airport]]
+        code = "FAR"
+        name = "Far"
+        latitude = 2.0
+        longitude = 0.0
+
+        [[airport]]
+        code = "NEAR"
+        name = "Near"
+        latitude = 0.5
+        longitude = 0.0
+        "#,
+    )
+    .unwrap();
+
+    let nearest = airports.nearest(&position(0.0, 0.0));
+    let codes: Vec<&str> = nearest.iter().map(|(a, _)| a.code.as_str()).collect();
+    assert_eq!(codes, ["NEAR", "FAR"]);
+    assert_close(nearest[0].1, DEGREE / 2.0, 1e-9);
+    assert!(AirportDatabase::default()
+        .nearest(&position(0.0, 0.0))
+        .is_empty());
+}
+
+#[test]
+fn diversion_is_a_single_leg_from_the_destination() {
+    let airports = AirportDatabase::load(None).unwrap();
+    let destination = airports.waypoint("EHLE").unwrap();
+
+    // The nearest other aerodrome, as offered first for the alternate.
+    let (alternate, distance) = airports
+        .nearest(&destination.position)
+        .into_iter()
+        .find(|(a, _)| a.code != destination.name)
+        .unwrap();
+    let diversion = RoutePlan::new(
+        &[destination.clone(), Waypoint::from(alternate)],
+        100.0,
+        Wind::default(),
+    )
+    .unwrap();
+
+    assert_eq!(diversion.legs.len(), 1);
+    assert_eq!(diversion.legs[0].to, alternate.code);
+    assert_close(diversion.distance, distance, 1e-9);
+    assert_close(diversion.minutes, distance / 100.0 * 60.0, 1e-9);
+    assert!(alternate.elevation.is_some());
+}
+
+#[test]
+fn diversion_into_the_wind_takes_longer() {
+    let destination = waypoint("DEST", 0.0, 0.0);
+    let alternate = waypoint("ALTN", 1.0, 0.0);
+    let still = RoutePlan::new(
+        &[destination.clone(), alternate.clone()],
+        100.0,
+        Wind::default(),
+    )
+    .unwrap();
+    let headwind = RoutePlan::new(&[destination, alternate], 100.0, wind(0.0, 25.0)).unwrap();
+
+    assert_close(headwind.minutes, still.minutes * 100.0 / 75.0, 1e-9);
+}
@@ -0,0 +1,133 @@
+//! Signed loadsheets: the code on the printout and its verification on the /verify page.
+
+mod common;
+
+use std::{fs, process::Stdio};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use common::{command, Server, FLIGHT};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const KEY: &str = "0123456789abcdef0123456789abcdef";
+
+const SIGN_OFF: &str = "pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-10-19&declaration=true";
+
+fn key_file(name: &str, key: &str) -> String {
+    let path = format!("{}/{}", env!("CARGO_TARGET_TMPDIR"), name);
+    fs::write(&path, key).unwrap();
+    path
+}
+
+/// Prints the loadsheet of the flight and returns the code on the printout.
+fn print(server: &Server) -> String {
+    let response = server.post(&format!("/print?{}&{}", FLIGHT, SIGN_OFF), &[]);
+    assert_eq!(response.status, 200);
+
+    let (_, code) = response
+        .body
+        .split_once(r#"<p class="font-mono break-all">"#)
+        .unwrap();
+    code.split_once("</p>").unwrap().0.to_string()
+}
+
+fn verify(server: &Server, code: &str) -> String {
+    let response = server.get(&format!("/verify?payload={}", code));
+    assert_eq!(response.status, 200);
+    response.body
+}
+
+/// Replaces the first character of a base64url part with another one.
+fn tamper(part: &str) -> String {
+    let replacement = if part.starts_with('A') { "B" } else { "A" };
+    format!("{}{}", replacement, &part[1..])
+}
+
+#[test]
+fn signed_loadsheet_is_verified() {
+    let server = Server::start_with(&["--signing-key-file", &key_file("key-valid", KEY)]);
+    let code = print(&server);
+    assert!(code.starts_with("v1."));
+
+    let body = verify(&server, &code);
+    assert!(body.contains("<strong class=\"font-bold\">Geldig:</strong>"));
+    assert!(body.contains("PHDHA (AT01)"));
+
+    // The full verification URL of the QR code is accepted as well.
+    let body = verify(
+        &server,
+        &format!("http%3A%2F%2Flocalhost%2Fverify%3Fpayload%3D{}", code),
+    );
+    assert!(body.contains("<strong class=\"font-bold\">Geldig:</strong>"));
+}
+
+#[test]
+fn altered_loadsheets_are_rejected() {
+    let server = Server::start_with(&["--signing-key-file", &key_file("key-altered", KEY)]);
+    let code = print(&server);
+    let (signed, signature) = code.rsplit_once('.').unwrap();
+
+    let body = verify(&server, &format!("{}.{}", tamper(signed), signature));
+    assert!(body.contains("signature does not match, the loadsheet has been altered"));
+
+    let body = verify(&server, &format!("{}.{}", signed, tamper(signature)));
+    assert!(body.contains("signature does not match, the loadsheet has been altered"));
+
+    let body = verify(&server, &format!("{}.not*base64", signed));
+    assert!(body.contains("malformed signature"));
+
+    let body = verify(&server, "garbage");
+    assert!(body.contains("malformed loadsheet code"));
+    assert!(!body.contains("Geldig:"));
+}
+
+#[test]
+fn other_versions_are_rejected() {
+    let server = Server::start_with(&["--signing-key-file", &key_file("key-version", KEY)]);
+    let code = print(&server);
+    let (signed, _) = code.rsplit_once('.').unwrap();
+    let (_, payload) = signed.split_once('.').unwrap();
+
+    // Signed with the right key, so only the version is wrong.
+    let signed = format!("v2.{}", payload);
+    let mut mac = Hmac::<Sha256>::new_from_slice(KEY.as_bytes()).unwrap();
+    mac.update(signed.as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+
+    let body = verify(&server, &format!("{}.{}", signed, signature));
+    assert!(body.contains("unsupported loadsheet version v2"));
+}
+
+#[test]
+fn codes_of_another_key_are_rejected() {
+    let server = Server::start_with(&["--signing-key-file", &key_file("key-first", KEY)]);
+    let code = print(&server);
+
+    let other = key_file("key-second", "fedcba9876543210fedcba9876543210");
+    let server = Server::start_with(&["--signing-key-file", &other]);
+    let body = verify(&server, &code);
+    assert!(body.contains("signature does not match, the loadsheet has been altered"));
+}
+
+#[test]
+fn short_keys_are_rejected() {
+    let path = key_file("key-short", "0123456789abcdef");
+    let output = command()
+        .args(["--signing-key-file", &path])
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("must be at least 32 bytes"));
+}
+
+#[test]
+fn loadsheets_are_not_signed_without_a_key() {
+    let server = Server::start();
+    let response = server.post(&format!("/print?{}&{}", FLIGHT, SIGN_OFF), &[]);
+
+    assert_eq!(response.status, 200);
+    assert!(!response.body.contains(r#"<p class="font-mono break-all">"#));
+    assert!(verify(&server, "v1.e30.AAAA").contains("signing is not configured on this server"));
+}
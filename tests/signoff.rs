@@ -0,0 +1,125 @@
+//! Sign-off of the pilot in command before a loadsheet is printed and stored.
+
+mod common;
+
+use std::fs;
+
+use common::{Server, FLIGHT};
+
+const SIGN_OFF: &str = "pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-10-19&declaration=true";
+
+/// Submits the sign-off on the export step and returns the page.
+fn export(server: &Server, flight: &str, sign_off: &str) -> String {
+    let response = server.get(&format!("/export?{}&{}&submit=Opslaan", flight, sign_off));
+    assert_eq!(response.status, 200);
+    response.body
+}
+
+/// Asserts that the sign-off is refused with the message, and that nothing is printed.
+fn refused(server: &Server, flight: &str, sign_off: &str, message: &str) {
+    let body = export(server, flight, sign_off);
+    assert!(body.contains(message), "{} is missing", message);
+    assert!(!body.contains("form.action = '/print?"));
+
+    let response = server.post(&format!("/print?{}&{}", flight, sign_off), &[]);
+    assert_eq!(response.status, 400);
+    assert!(response.body.contains(message));
+}
+
+#[test]
+fn complete_sign_off_is_printed() {
+    let server = Server::start();
+    let body = export(&server, FLIGHT, SIGN_OFF);
+
+    assert!(body.contains("form.action = '/print?"));
+    assert!(!body.contains(r#"role="alert""#));
+}
+
+#[test]
+fn pilot_in_command_is_required() {
+    let server = Server::start();
+
+    refused(
+        &server,
+        FLIGHT,
+        "pic_name=%20&pic_licence=PPL-1&flight_date=2026-10-19&declaration=true",
+        "Vul de naam van de gezagvoerder in.",
+    );
+    refused(
+        &server,
+        FLIGHT,
+        "pic_name=Piloot&flight_date=2026-10-19&declaration=true",
+        "Vul het brevetnummer van de gezagvoerder in.",
+    );
+    refused(
+        &server,
+        FLIGHT,
+        "pic_name=Piloot&pic_licence=PPL%231&flight_date=2026-10-19&declaration=true",
+        "Het brevetnummer bevat ongeldige tekens.",
+    );
+}
+
+#[test]
+fn flight_date_and_declaration_are_required() {
+    let server = Server::start();
+
+    refused(
+        &server,
+        FLIGHT,
+        "pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-13-40&declaration=true",
+        "Vul een geldige vluchtdatum in.",
+    );
+    refused(
+        &server,
+        FLIGHT,
+        "pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-10-19",
+        "Bevestig dat de belading is gecontroleerd.",
+    );
+}
+
+#[test]
+fn override_reason_is_required_outside_the_limits() {
+    let server = Server::start();
+    let overload = FLIGHT.replace("loads=80", "loads=80,150,40");
+    let message =
+        "Gewicht en balans of brandstof voldoet niet, geef een reden op om toch op te slaan.";
+
+    let body = export(&server, &overload, "");
+    assert!(body.contains("Gewicht en balans vallen buiten de limieten."));
+    refused(&server, &overload, SIGN_OFF, message);
+
+    let short = FLIGHT.replace("trip_duration=01:00", "trip_duration=09:00");
+    let body = export(&server, &short, "");
+    assert!(body.contains("Er is onvoldoende brandstof aan boord."));
+    refused(&server, &short, SIGN_OFF, message);
+
+    let body = export(
+        &server,
+        &overload,
+        &format!("{}&override_reason=Veerpilot", SIGN_OFF),
+    );
+    assert!(body.contains("form.action = '/print?"));
+}
+
+#[test]
+fn printing_is_a_post() {
+    let server = Server::start();
+
+    let response = server.get(&format!("/print?{}&{}", FLIGHT, SIGN_OFF));
+    assert_eq!(response.status, 404);
+}
+
+#[test]
+fn printing_again_stores_the_loadsheet_once() {
+    let storage = format!("{}/signoff-storage", env!("CARGO_TARGET_TMPDIR"));
+    let _ = fs::remove_dir_all(&storage);
+    fs::create_dir_all(&storage).unwrap();
+    let server = Server::start_with(&["--storage-dir", &storage]);
+
+    for _ in 0..2 {
+        let response = server.post(&format!("/print?{}&{}", FLIGHT, SIGN_OFF), &[]);
+        assert_eq!(response.status, 200);
+    }
+
+    assert_eq!(fs::read_dir(&storage).unwrap().count(), 1);
+}
@@ -0,0 +1,141 @@
+//! Wizard state carried in a single versioned token, and the routes that resolve it.
+
+mod common;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use common::{encode_json, encode_state, Server, FLIGHT};
+
+/// State token of the flight, as linked from the results.
+fn token_of(server: &Server, flight: &str) -> String {
+    let body = server.get(&format!("/fuel?{}", flight)).body;
+    let (_, token) = body.split_once("/wb-chart?state=").unwrap();
+    token.split(['"', '&']).next().unwrap().to_string()
+}
+
+const VALUES: [(&str, &str); 11] = [
+    ("callsign", "PHDHA"),
+    ("loads", "80"),
+    ("oat", "15"),
+    ("pressure_altitude", "0"),
+    ("wind", "5"),
+    ("wind_direction", "headwind"),
+    ("fuel_type", "avgas"),
+    ("fuel_unit", "liter"),
+    ("fuel_extra", "60"),
+    ("trip_duration", "01:00"),
+    ("alternate_duration", "00:30"),
+];
+
+#[test]
+fn token_round_trips_the_flight() {
+    let server = Server::start();
+    let token = token_of(&server, FLIGHT);
+
+    let explicit = server.get(&format!("/wb-chart?{}", FLIGHT)).body;
+    let tokenized = server.get(&format!("/wb-chart?state={}", token)).body;
+    assert!(tokenized.contains("Start: 640.2 kg, zwaartepunt 434 mm"));
+    assert_eq!(tokenized, explicit);
+
+    // A token made outside the application is read the same way.
+    let made = server.get(&format!("/wb-chart?state={}", encode_state('1', &VALUES)));
+    assert_eq!(made.body, explicit);
+}
+
+#[test]
+fn submitted_fields_take_precedence_over_the_token() {
+    let server = Server::start();
+    let token = token_of(&server, FLIGHT);
+
+    let body = server
+        .get(&format!("/wb-chart?state={}&loads=80,80", token))
+        .body;
+    assert!(body.contains("Start: 720.2 kg,"));
+}
+
+#[test]
+fn performance_charts_resolve_the_token() {
+    let server = Server::start();
+    let token = token_of(&server, FLIGHT);
+
+    let response = server.get(&format!("/perf-tod?state={}", token));
+    assert_eq!(response.status, 200);
+    assert!(response
+        .body
+        .contains("Bij 15 °C, drukhoogte 0 ft, 640 kg en 5 kt tegenwind: startrol"));
+
+    let response = server.get(&format!("/perf-ldr?state={}", token));
+    assert_eq!(response.status, 200);
+    assert!(response
+        .body
+        .contains("Bij 15 °C, drukhoogte 0 ft, 628 kg en 5 kt tegenwind: landingsrol"));
+
+    // The conditions at the destination come from the token as well.
+    let destination = token_of(
+        &server,
+        &format!(
+            "{}&landing_oat=25&landing_wind=3&landing_wind_direction=tailwind",
+            FLIGHT
+        ),
+    );
+    let response = server.get(&format!("/perf-ldr?state={}", destination));
+    assert!(response
+        .body
+        .contains("Bij 25 °C, drukhoogte 0 ft, 628 kg en 3 kt rugwind"));
+}
+
+#[test]
+fn unreadable_tokens_are_ignored() {
+    let server = Server::start();
+    let valid = encode_state('1', &VALUES);
+    let tokens = [
+        // Unknown version.
+        encode_state('9', &VALUES),
+        // Not base64url.
+        "1!!!!".to_string(),
+        // Not deflate compressed.
+        format!("1{}", URL_SAFE_NO_PAD.encode(b"{\"callsign\":\"PHDHA\"}")),
+        // Not a map of form values.
+        encode_json('1', b"[\"PHDHA\"]"),
+    ];
+
+    assert_eq!(
+        server.get(&format!("/perf-tod?state={}", valid)).status,
+        200
+    );
+    for token in &tokens {
+        assert_eq!(
+            server.get(&format!("/perf-tod?state={}", token)).status,
+            400,
+            "{} was read",
+            token
+        );
+        // The submitted fields still apply.
+        let response = server.get(&format!("/wb-chart?{}&state={}", FLIGHT, token));
+        assert!(response.body.contains("Start: 640.2 kg,"));
+    }
+}
+
+#[test]
+fn oversized_tokens_are_rejected() {
+    let server = Server::start();
+    let padding = "x".repeat(15 * 1024);
+    let oversized = "x".repeat(17 * 1024);
+
+    let mut values = VALUES.to_vec();
+    values.push(("padding", &padding));
+    let token = encode_state('1', &values);
+    assert_eq!(
+        server.get(&format!("/perf-tod?state={}", token)).status,
+        200
+    );
+
+    // It compresses well, only the decompressed length is limited.
+    values.pop();
+    values.push(("padding", &oversized));
+    let token = encode_state('1', &values);
+    assert!(token.len() < 1024);
+    assert_eq!(
+        server.get(&format!("/perf-tod?state={}", token)).status,
+        400
+    );
+}
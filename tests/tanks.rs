@@ -0,0 +1,171 @@
+//! Fuel tanks of a type: their contents, filling up to the maximum and the burn sequence.
+
+mod common;
+
+use acm_weight_and_balance::{aircraft::AircraftRegistry, plane};
+use airplane::weight_and_balance::{Mass, Volume};
+use common::{plane, registry, Server, FLIGHT};
+
+const TYPE: &str = r#"
+[[aircraft_type]]
+id = "TST"
+name = "Test"
+
+[aircraft_type.envelope]
+min_mass = 500.0
+max_mass = 800.0
+forward_cg = 0.35
+aft_cg = 0.5
+
+[[aircraft_type.station]]
+name = "Pilot"
+arm = 0.5
+
+[aircraft_type.fuel]
+consumption_per_hour = 20.0
+
+[[aircraft_type.fuel.tank]]
+name = "Links"
+capacity = 40.0
+lever_arm = 0.28
+sequence = 1
+
+[[aircraft_type.fuel.tank]]
+name = "Rechts"
+capacity = 40.0
+lever_arm = 0.32
+sequence = 1
+
+[[aircraft_type.fuel.tank]]
+name = "Aux"
+capacity = 20.0
+unusable = 1.0
+lever_arm = 0.6
+sequence = 2
+
+[[aircraft]]
+callsign = "PHTST"
+type = "TST"
+empty_mass = 500.0
+empty_mass_arm = 0.4
+"#;
+
+fn kilo(liters: f64) -> f64 {
+    Mass::Avgas(Volume::Liter(liters)).kilo()
+}
+
+#[test]
+fn each_tank_has_a_moment_of_its_own() {
+    let registry = registry(TYPE).unwrap();
+    let fuel = &registry.aircraft_type("TST").unwrap().fuel;
+    let plane = plane(&registry, &[], Some(&[30.0, 10.0, 5.0]));
+    let names: Vec<&str> = plane.moments().iter().map(|m| m.name().as_str()).collect();
+
+    assert_eq!(
+        names,
+        [
+            "Empty Mass",
+            "Unusable Fuel Aux",
+            "Pilot",
+            "Fuel Links",
+            "Fuel Rechts",
+            "Fuel Aux"
+        ]
+    );
+    assert_eq!(plane::tank_contents(&plane, fuel), [30.0, 10.0, 5.0]);
+    assert_eq!(plane::fuel_on_board(&plane, fuel), 45.0);
+}
+
+#[test]
+fn maximum_fuel_is_the_capacity_of_all_tanks() {
+    let registry = registry(TYPE).unwrap();
+    let aircraft_type = registry.aircraft_type("TST").unwrap();
+    assert_eq!(registry.fuel_policy(aircraft_type).max_fuel, 100.0);
+
+    let plane = plane(&registry, &[], None);
+    assert_eq!(
+        plane::tank_contents(&plane, &aircraft_type.fuel),
+        [40.0, 40.0, 20.0]
+    );
+}
+
+#[test]
+fn tanks_in_the_same_step_are_burned_in_proportion_to_their_contents() {
+    let registry = registry(TYPE).unwrap();
+    let fuel = &registry.aircraft_type("TST").unwrap().fuel;
+    let plane = plane(&registry, &[], Some(&[30.0, 10.0, 20.0]));
+
+    // The trip of an hour burns 20 liters: 15 from the left and 5 from the right tank.
+    let expected = plane::total_moment(&plane) - kilo(15.0) * 0.28 - kilo(5.0) * 0.32;
+    assert!((plane::total_moment_landing(&plane, fuel) - expected).abs() < 1e-6);
+}
+
+#[test]
+fn next_step_is_burned_once_the_previous_tanks_are_empty() {
+    let registry = registry(TYPE).unwrap();
+    let fuel = &registry.aircraft_type("TST").unwrap().fuel;
+    let plane = plane(&registry, &[], Some(&[5.0, 5.0, 20.0]));
+
+    let expected =
+        plane::total_moment(&plane) - kilo(5.0) * 0.28 - kilo(5.0) * 0.32 - kilo(10.0) * 0.6;
+    assert!((plane::total_moment_landing(&plane, fuel) - expected).abs() < 1e-6);
+}
+
+#[test]
+fn landing_is_checked_against_the_limits() {
+    // Burning the tanks in front of the centre of gravity moves it aft, past this limit.
+    let aft = registry(&TYPE.replace("aft_cg = 0.5", "aft_cg = 0.405")).unwrap();
+    let fuel = &aft.aircraft_type("TST").unwrap().fuel;
+    let landing_aft = plane(&aft, &[], Some(&[40.0, 40.0, 0.0]));
+
+    assert!(landing_aft.within_limits());
+    assert!(!plane::within_limits_landing(&landing_aft, fuel));
+    assert!(!plane::within_limits(&landing_aft, fuel));
+
+    let registry = registry(TYPE).unwrap();
+    let within = plane(&registry, &[], Some(&[40.0, 40.0, 0.0]));
+    assert!(plane::within_limits(&within, fuel));
+}
+
+#[test]
+fn single_tank_of_types_without_tanks() {
+    let registry = AircraftRegistry::load(None).unwrap();
+    let aircraft = &registry.aircraft[0];
+    let fuel = &registry.type_of(aircraft).fuel;
+    let tanks = fuel.tanks();
+
+    assert_eq!(tanks.len(), 1);
+    assert_eq!(tanks[0].name, plane::FUEL);
+    assert_eq!(Some(tanks[0].lever_arm), fuel.lever_arm);
+    assert_eq!(plane::fuel_moment_name(&tanks[0], 1), plane::FUEL);
+}
+
+#[test]
+fn tanks_are_validated() {
+    assert!(registry(&TYPE.replace("\"Rechts\"", "\"Links\"")).is_err());
+    assert!(registry(&TYPE.replace("\"Rechts\"", "\" \"")).is_err());
+    assert!(registry(&TYPE.replace("capacity = 20.0\n", "")).is_err());
+    assert!(registry(&TYPE.replace("capacity = 20.0", "capacity = 0.0")).is_err());
+    assert!(registry(&TYPE.replace("lever_arm = 0.6", "lever_arm = 0.0")).is_err());
+    assert!(registry(&TYPE.replace("unusable = 1.0", "unusable = -1.0")).is_err());
+    assert!(registry(&TYPE.replace(
+        "consumption_per_hour = 20.0",
+        "consumption_per_hour = 20.0\nlever_arm = 0.3"
+    ))
+    .is_err());
+}
+
+#[test]
+fn fuel_on_board_must_be_a_number() {
+    let server = Server::start();
+
+    for quantity in ["veel", "-5", "NaN"] {
+        let body = server
+            .get(&format!(
+                "/fuel?{}",
+                FLIGHT.replace("fuel_extra=60", &format!("fuel_extra={}", quantity))
+            ))
+            .body;
+        assert!(body.contains("Vul de brandstof aan boord in als een getal."));
+    }
+}
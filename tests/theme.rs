@@ -0,0 +1,101 @@
+//! Colour themes of the pages and the server rendered charts, kept in a cookie.
+
+mod common;
+
+use common::{Server, FLIGHT};
+
+const PERF: &str = "type=AT01&units=metric&oat=15&pressure_altitude=0&mtow=700&wind=5\
+                    &wind_direction=headwind";
+
+/// Body of a page or chart requested with the theme cookie.
+fn get(server: &Server, path: &str, theme: &str) -> String {
+    let cookie = format!("theme={}", theme);
+    let response = server.request(path, &[("Cookie", &cookie)]);
+    assert_eq!(response.status, 200);
+    response.body
+}
+
+#[test]
+fn pages_use_the_theme_of_the_cookie() {
+    let server = Server::start();
+
+    let body = server.get("/").body;
+    assert!(body.contains(r#"<html lang="nl" data-theme="system">"#));
+    assert!(body.contains(r#"<option value="system" selected>"#));
+
+    let body = get(&server, "/", "red-night");
+    assert!(body.contains(r#"<html lang="nl" data-theme="red-night">"#));
+    assert!(body.contains(r#"<option value="red-night" selected>"#));
+
+    // An unknown theme falls back to the system theme.
+    let body = get(&server, "/", "sepia");
+    assert!(body.contains(r#"<html lang="nl" data-theme="system">"#));
+}
+
+#[test]
+fn charts_are_drawn_in_the_colours_of_the_theme() {
+    let server = Server::start();
+    let chart = format!("/wb-chart?{}", FLIGHT);
+
+    let light = get(&server, &chart, "light");
+    assert!(light.contains(".chart-background { fill: #ffffff; }"));
+    assert!(!light.contains("prefers-color-scheme"));
+
+    let dark = get(&server, &chart, "dark");
+    assert!(dark.contains(".chart-background { fill: #111827; }"));
+    assert!(dark.contains(".chart-recolour { filter: url(#chart-dark); }"));
+
+    let red_night = get(&server, &chart, "red-night");
+    assert!(red_night.contains(".chart-background { fill: #000000; }"));
+    assert!(red_night.contains(".chart-recolour { filter: url(#chart-red-night); }"));
+
+    // The system theme follows the colour scheme of the device.
+    let system = server.get(&chart).body;
+    assert!(system.contains(".chart-background { fill: #ffffff; }"));
+    assert!(system.contains("@media (prefers-color-scheme: dark)"));
+}
+
+#[test]
+fn theme_in_the_query_takes_precedence_over_the_cookie() {
+    let server = Server::start();
+
+    let body = get(
+        &server,
+        &format!("/wb-chart?{}&theme=light", FLIGHT),
+        "dark",
+    );
+    assert!(body.contains(".chart-background { fill: #ffffff; }"));
+    assert!(body.contains(".chart-recolour { filter: none; }"));
+}
+
+#[test]
+fn drawings_of_other_sources_are_recoloured() {
+    let server = Server::start();
+
+    let body = get(&server, &format!("/perf-tod?{}", PERF), "red-night");
+    assert!(body.contains(r#"<filter id="chart-red-night""#));
+    assert!(body.contains(".chart-recolour { filter: url(#chart-red-night); }"));
+
+    let body = get(&server, &format!("/wb-table?{}", FLIGHT), "dark");
+    assert!(body.contains(r#"<g class="chart-recolour">"#));
+    assert!(body.contains(".chart-recolour { filter: url(#chart-dark); }"));
+}
+
+#[test]
+fn printout_asks_for_light_charts() {
+    let server = Server::start();
+    let response = server.post(
+        &format!(
+            "/print?{}&pic_name=Piloot&pic_licence=PPL-1&flight_date=2026-10-19\
+             &declaration=true",
+            FLIGHT
+        ),
+        &[("Cookie", "theme=red-night")],
+    );
+    assert_eq!(response.status, 200);
+    let body = response.body;
+
+    assert!(body.contains("&theme=light\" alt=\"Massa en zwaartepunt\""));
+    assert!(body.contains("&theme=light\" alt=\"Startafstand\""));
+    assert!(body.contains("&theme=light\" alt=\"Landingsafstand\""));
+}
@@ -0,0 +1,94 @@
+//! Imperial unit mode: conversions of masses, arms and distances, and the pages in pounds,
+//! inches and feet.
+
+mod common;
+
+use acm_weight_and_balance::units::{round, UnitSystem};
+use common::{Server, FLIGHT};
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "{} differs from {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn masses_in_pounds() {
+    let imperial = UnitSystem::Imperial;
+
+    assert_close(imperial.mass_from_kilo(1.0), 2.204_622_621_8);
+    assert_close(imperial.mass_to_kilo(220.462_262_18), 100.0);
+    assert_eq!(UnitSystem::Metric.mass_from_kilo(80.0), 80.0);
+    assert_eq!(UnitSystem::Metric.mass_to_kilo(80.0), 80.0);
+}
+
+#[test]
+fn arms_in_inches_and_distances_in_feet() {
+    let imperial = UnitSystem::Imperial;
+
+    assert_close(imperial.arm_from_meter(1.0), 39.370_078_740_2);
+    assert_close(imperial.cg_from_meter(0.0254), 1.0);
+    assert_close(UnitSystem::Metric.cg_from_meter(0.434), 434.0);
+    assert_close(imperial.distance_from_meter(0.3048), 1.0);
+    assert_close(imperial.distance_from_meter(100.0), 328.083_989_5);
+    assert_close(
+        imperial.moment_from_kilo_meter(1.0),
+        2.204_622_621_8 * 39.370_078_740_2,
+    );
+}
+
+#[test]
+fn conversions_round_trip() {
+    let imperial = UnitSystem::Imperial;
+    for kilo in [0.0, 0.5, 80.0, 640.2, 750.0] {
+        assert_close(imperial.mass_to_kilo(imperial.mass_from_kilo(kilo)), kilo);
+    }
+
+    // A mass shown rounded to two decimals is the same mass once it is submitted again.
+    let shown = round(imperial.mass_from_kilo(80.0), 2);
+    assert_eq!(shown, 176.37);
+    assert_eq!(round(imperial.mass_to_kilo(shown), 2), 80.0);
+
+    for units in [UnitSystem::Metric, UnitSystem::Imperial] {
+        assert_eq!(UnitSystem::parse(units.as_str()), Some(units));
+    }
+    assert_eq!(UnitSystem::parse("furlongs"), None);
+}
+
+#[test]
+fn fractional_masses_are_accepted() {
+    let server = Server::start();
+    let response = server.get("/?callsign=PHDHA&units=imperial&loads=176.37");
+
+    assert_eq!(response.status, 200);
+    assert!(response.body.contains(r#"name="loads" value="176.37,,""#));
+    assert!(response.body.contains(r#"value="176.37""#));
+    assert!(response.body.contains(r#"min="0" step="any""#));
+}
+
+#[test]
+fn pages_in_imperial_units() {
+    let server = Server::start();
+    let response = server.get(&format!(
+        "/fuel?{}&units=imperial",
+        FLIGHT.replace("loads=80", "loads=176.37")
+    ));
+
+    assert_eq!(response.status, 200);
+    for header in [
+        "Lever Arm [in]",
+        "Mass [lb]",
+        "Moment [lb in]",
+        "Distance [ft]",
+    ] {
+        assert!(response.body.contains(header), "{} is missing", header);
+    }
+    // The pilot of 176.37 lb gives the same flight as one of 80 kg.
+    assert!(response.body.contains(
+        "Start: 1411.4 lb, zwaartepunt 17.1 in, moment 24117 lb in, binnen de limieten."
+    ));
+    assert!(response.body.contains("startrol 590 ft"));
+}
@@ -0,0 +1,130 @@
+// Runs the golden cases of the native code against the WebAssembly build.
+//
+//   wasm-pack build --target nodejs --out-dir pkg
+//   node --test tests/wasm/
+
+import assert from 'node:assert/strict';
+import { readFileSync } from 'node:fs';
+import { createRequire } from 'node:module';
+import { test } from 'node:test';
+
+const require = createRequire(import.meta.url);
+const core = require('../../pkg/acm_weight_and_balance.js');
+
+const golden = (name) =>
+    JSON.parse(readFileSync(new URL(`../golden/${name}.json`, import.meta.url), 'utf8'));
+
+const call = (fn, input) => JSON.parse(fn(JSON.stringify(input)));
+
+function assertClose(actual, expected, tolerance, message) {
+    assert.ok(
+        Math.abs(actual - expected) <= tolerance,
+        `${message}: expected ${expected} ± ${tolerance}, got ${actual}`,
+    );
+}
+
+test('performance golden cases', () => {
+    const { tolerance, cases } = golden('performance');
+
+    for (const c of cases) {
+        const result = call(core.performance, {
+            oat: c.oat,
+            pressure_altitude: c.pressure_altitude,
+            wind: c.wind,
+            takeoff_mass: c.mass,
+            landing_mass: c.mass,
+        });
+
+        for (const [name, expected] of Object.entries(c.expected)) {
+            assertClose(result[name], expected, tolerance, `${c.name}: ${name}`);
+        }
+    }
+});
+
+test('fuel golden cases', () => {
+    const { tolerance, cases } = golden('fuel');
+
+    for (const c of cases) {
+        const result = call(core.fuelPlan, {
+            fuel: c.fuel,
+            trip_minutes: c.trip_minutes,
+            alternate_minutes: c.alternate_minutes,
+        });
+
+        for (const [name, expected] of Object.entries(c.expected)) {
+            if (typeof expected === 'number') {
+                assertClose(result[name], expected, tolerance, `${c.name}: ${name}`);
+            } else {
+                assert.equal(result[name], expected, `${c.name}: ${name}`);
+            }
+        }
+    }
+});
+
+test('mass and balance of a typical flight', () => {
+    const result = call(core.massAndBalance, {
+        aircraft: { callsign: 'PHDHA', empty_mass: 517.0, empty_mass_arm: 0.4294 },
+        loads: [{ mass: 80.0, seat: 'm' }, { mass: 75.0, seat: 'm' }, { mass: 5.0 }],
+        fuel_type: 'avgas',
+        fuel: 60.0,
+        trip_minutes: 60,
+    });
+
+    assert.deepEqual(
+        result.loads.map((l) => l.name),
+        ['Empty Mass', 'Piloot', 'Passagier', 'Bagage', 'Fuel'],
+    );
+    assert.ok(result.landing_mass < result.takeoff_mass);
+    assert.ok(result.within_limits);
+});
+
+test('mass and balance of the input from before the loading stations', () => {
+    const flight = {
+        aircraft: { callsign: 'PHDHA', empty_mass: 517.0, empty_mass_arm: 0.4294 },
+        fuel_type: 'avgas',
+        fuel: 60.0,
+        trip_minutes: 60,
+    };
+    const result = call(core.massAndBalance, {
+        ...flight,
+        pilot: { mass: 80.0, seat: 'm' },
+        passenger: { mass: 75.0, seat: 'm' },
+        baggage: 5.0,
+    });
+
+    assert.deepEqual(
+        result,
+        call(core.massAndBalance, {
+            ...flight,
+            loads: [{ mass: 80.0, seat: 'm' }, { mass: 75.0, seat: 'm' }, { mass: 5.0 }],
+        }),
+    );
+    assert.throws(
+        () => call(core.massAndBalance, {
+            ...flight,
+            loads: [{ mass: 80.0, seat: 'm' }],
+            pilot: { mass: 80.0, seat: 'm' },
+        }),
+        /either loads or the pilot/,
+    );
+});
+
+test('invalid input is rejected', () => {
+    assert.throws(() => core.performance('{}'), /invalid input/);
+    assert.throws(
+        () => call(core.performance, {
+            oat: 15, pressure_altitude: 9000, wind: 0, takeoff_mass: 750, landing_mass: 750,
+        }),
+        /outside the chart range/,
+    );
+    assert.throws(
+        () => call(core.massAndBalance, {
+            aircraft: { callsign: 'PHDHA', empty_mass: 517.0, empty_mass_arm: 0.4294 },
+            loads: [{ mass: 80.0, seat: 'm' }],
+            fuel_type: 'jet-a1',
+            fuel: 60.0,
+            trip_minutes: 60,
+        }),
+        /unknown fuel type/,
+    );
+});
@@ -0,0 +1,117 @@
+//! Weighing reports of an aircraft and the selection of the empty mass by flight date.
+
+mod common;
+
+use acm_weight_and_balance::{aircraft::Aircraft, date::Date};
+use common::registry;
+
+const REPORTS: &str = r#"
+[[aircraft]]
+callsign = "PHTST"
+
+[[aircraft.weighing]]
+date = "2019-05-01"
+reference = "WR-2019"
+empty_mass = 510.0
+empty_mass_arm = 0.42
+
+[[aircraft.weighing]]
+date = "2023-03-15"
+reference = "WR-2023"
+empty_mass = 517.0
+empty_mass_arm = 0.43
+
+[[aircraft.weighing]]
+date = "2021-08-20"
+reference = "MOD-2021"
+empty_mass = 514.0
+empty_mass_arm = 0.425
+"#;
+
+fn aircraft() -> Aircraft {
+    registry(REPORTS).unwrap().aircraft.remove(0)
+}
+
+fn reference(aircraft: &Aircraft, on: Option<Date>) -> Option<String> {
+    aircraft.weighing(on).map(|w| w.reference.unwrap())
+}
+
+#[test]
+fn latest_report_without_a_flight_date() {
+    let weighing = aircraft().weighing(None).unwrap();
+
+    assert_eq!(weighing.reference.as_deref(), Some("WR-2023"));
+    assert_eq!(weighing.empty_mass, 517.0);
+    assert_eq!(weighing.empty_mass_arm, 0.43);
+}
+
+#[test]
+fn report_valid_on_the_flight_date() {
+    let aircraft = aircraft();
+
+    assert_eq!(
+        reference(&aircraft, Some((2020, 1, 1))).as_deref(),
+        Some("WR-2019")
+    );
+    assert_eq!(
+        reference(&aircraft, Some((2022, 12, 31))).as_deref(),
+        Some("MOD-2021")
+    );
+    assert_eq!(
+        reference(&aircraft, Some((2030, 1, 1))).as_deref(),
+        Some("WR-2023")
+    );
+}
+
+#[test]
+fn report_applies_from_its_own_date() {
+    let aircraft = aircraft();
+
+    assert_eq!(
+        reference(&aircraft, Some((2023, 3, 14))).as_deref(),
+        Some("MOD-2021")
+    );
+    assert_eq!(
+        reference(&aircraft, Some((2023, 3, 15))).as_deref(),
+        Some("WR-2023")
+    );
+}
+
+#[test]
+fn no_report_before_the_first_weighing() {
+    assert!(aircraft().weighing(Some((2019, 4, 30))).is_none());
+}
+
+#[test]
+fn aircraft_without_reports_has_a_single_undated_report() {
+    let registry =
+        registry("[[aircraft]]\ncallsign = \"PHOLD\"\nempty_mass = 517.0\nempty_mass_arm = 0.43\n")
+            .unwrap();
+    let aircraft = &registry.aircraft[0];
+
+    for on in [None, Some((1990, 1, 1)), Some((2030, 1, 1))] {
+        let weighing = aircraft.weighing(on).unwrap();
+        assert_eq!(weighing.date, None);
+        assert_eq!(weighing.reference, None);
+        assert_eq!(weighing.empty_mass, 517.0);
+    }
+}
+
+#[test]
+fn reports_need_a_date_and_a_reference() {
+    assert!(registry(&REPORTS.replace("date = \"2019-05-01\"\n", "")).is_err());
+    assert!(registry(&REPORTS.replace("2019-05-01", "01-05-2019")).is_err());
+    assert!(registry(&REPORTS.replace("reference = \"WR-2019\"\n", "")).is_err());
+    assert!(registry(&REPORTS.replace("\"WR-2019\"", "\" \"")).is_err());
+    assert!(registry(&REPORTS.replace("empty_mass = 510.0", "empty_mass = 0.0")).is_err());
+}
+
+#[test]
+fn empty_mass_comes_from_the_reports_alone() {
+    let both = REPORTS.replace(
+        "callsign = \"PHTST\"\n",
+        "callsign = \"PHTST\"\nempty_mass = 517.0\nempty_mass_arm = 0.43\n",
+    );
+    assert!(registry(&both).is_err());
+    assert!(registry("[[aircraft]]\ncallsign = \"PHNONE\"\n").is_err());
+}
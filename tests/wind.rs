@@ -0,0 +1,78 @@
+//! Gusts and wind limits in the performance assessment.
+
+mod common;
+
+use acm_weight_and_balance::{
+    aircraft::WindLimits,
+    performance::{self, performance_wind, PerfQueryParams, MAX_HEADWIND, MAX_TAILWIND},
+};
+use common::{embedded_type, perf_params};
+
+fn params(wind: f64) -> PerfQueryParams {
+    perf_params(15.0, 0.0, 700.0, wind)
+}
+
+#[test]
+fn headwind_counts_at_its_steady_speed() {
+    assert_eq!(performance_wind(10.0, None), 10.0);
+    assert_eq!(performance_wind(10.0, Some(18.0)), 10.0);
+    assert_eq!(performance_wind(0.0, Some(10.0)), 0.0);
+}
+
+#[test]
+fn tailwind_counts_at_its_gust_speed() {
+    assert_eq!(performance_wind(-5.0, None), -5.0);
+    assert_eq!(performance_wind(-5.0, Some(9.0)), -9.0);
+    assert_eq!(performance_wind(-5.0, Some(-9.0)), -9.0);
+    // A gust below the steady wind never reduces the tailwind.
+    assert_eq!(performance_wind(-5.0, Some(3.0)), -5.0);
+}
+
+#[test]
+fn wind_limits_stay_within_the_chart() {
+    assert!(WindLimits::default().validate(MAX_TAILWIND).is_ok());
+    assert!(WindLimits { max_tailwind: 0.0 }
+        .validate(MAX_TAILWIND)
+        .is_ok());
+    assert!(WindLimits {
+        max_tailwind: MAX_TAILWIND + 1.0
+    }
+    .validate(MAX_TAILWIND)
+    .is_err());
+    assert!(WindLimits { max_tailwind: -1.0 }
+        .validate(MAX_TAILWIND)
+        .is_err());
+    assert!(WindLimits {
+        max_tailwind: f64::NAN
+    }
+    .validate(MAX_TAILWIND)
+    .is_err());
+}
+
+#[test]
+fn type_limits_are_checked_against_its_performance_model() {
+    let mut aircraft_type = embedded_type();
+    assert!(aircraft_type.validate().is_ok());
+
+    aircraft_type.wind_limits.max_tailwind = MAX_TAILWIND + 5.0;
+    assert!(aircraft_type.validate().is_err());
+
+    // Without a performance model there is no chart to limit the tailwind.
+    aircraft_type.performance = None;
+    assert!(aircraft_type.validate().is_ok());
+}
+
+#[test]
+fn wind_beyond_the_chart_scale_is_rejected() {
+    let scale = performance::model("aquila-at01").unwrap().scale();
+
+    assert!(params(MAX_HEADWIND).validate(&scale).is_ok());
+    assert!(params(-MAX_TAILWIND).validate(&scale).is_ok());
+    assert!(params(MAX_HEADWIND + 1.0).validate(&scale).is_err());
+    assert!(params(-MAX_TAILWIND - 1.0).validate(&scale).is_err());
+    let crosswind = PerfQueryParams {
+        wind_direction: "crosswind".to_string(),
+        ..params(5.0)
+    };
+    assert!(crosswind.validate(&scale).is_err());
+}